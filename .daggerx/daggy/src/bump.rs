@@ -0,0 +1,222 @@
+use crate::error::{Error, Result};
+use crate::event::{Event, EventSink};
+use crate::log::Logger;
+use crate::release::{tag_name, Bump, ReleaseConfig, ReleaseVersion, Releases};
+use crate::repo::Module;
+
+/// How bumping one module went.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum BumpStatus {
+    /// The module was tagged with `tag`, releasing `version` after `previous`, which is `None`
+    /// for a first release.
+    Tagged { tag: String, version: ReleaseVersion, previous: Option<ReleaseVersion> },
+    /// No commits touched the module since its latest tag, `None` when it has none.
+    Unchanged { since: Option<String> },
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BumpOutcome {
+    pub module: String,
+    pub status: BumpStatus,
+}
+
+/// The release a module is due, as `bump` would tag it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BumpPlan {
+    /// The latest version, or v0.0.0 before the first release.
+    pub current: ReleaseVersion,
+    /// The tag of `current`, `None` before the first release.
+    pub since: Option<String>,
+    /// Commits touching the module since `since`, or since the first commit.
+    pub commits: usize,
+    /// The version to tag next, `None` when there are no commits and no level was given.
+    pub next: Option<ReleaseVersion>,
+    /// `<module>/v*` tags that are not valid release versions.
+    pub malformed: Vec<String>,
+}
+
+/// Outcome of bumping a list of modules.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct BumpReport {
+    pub outcomes: Vec<BumpOutcome>,
+    /// Whether the new tags were pushed.
+    pub pushed: bool,
+}
+
+impl BumpReport {
+    /// The new tags, in order.
+    pub fn tags(&self) -> Vec<&str> {
+        self.outcomes
+            .iter()
+            .filter_map(|outcome| match &outcome.status {
+                BumpStatus::Tagged { tag, .. } => Some(tag.as_str()),
+                BumpStatus::Unchanged { .. } => None,
+            })
+            .collect()
+    }
+
+    pub fn unchanged(&self) -> usize {
+        self.outcomes.iter().filter(|outcome| matches!(outcome.status, BumpStatus::Unchanged { .. })).count()
+    }
+}
+
+/// Tags the next release of modules, bumping their latest version by a given level or by the
+/// level the conventional commits since then call for, and optionally pushes the tags.
+pub struct Bumper {
+    releases: Releases,
+    config: ReleaseConfig,
+    level: Option<Bump>,
+    preid: String,
+    remote: Option<String>,
+    logger: Logger,
+    events: EventSink,
+}
+
+impl Bumper {
+    pub fn new(releases: Releases, config: ReleaseConfig) -> Self {
+        Self {
+            releases,
+            config,
+            level: None,
+            preid: "rc".to_string(),
+            remote: None,
+            logger: Logger::silent(),
+            events: EventSink::disabled(),
+        }
+    }
+
+    /// Bump every module by `level` instead of inferring it from its commits.
+    pub fn with_level(mut self, level: Option<Bump>) -> Self {
+        self.level = level;
+        self
+    }
+
+    /// The prerelease identifier of prerelease bumps, as in v1.2.3-rc.1.
+    pub fn with_preid(mut self, preid: impl Into<String>) -> Self {
+        self.preid = preid.into();
+        self
+    }
+
+    /// Push the new tags to `remote`.
+    pub fn with_remote(mut self, remote: Option<String>) -> Self {
+        self.remote = remote;
+        self
+    }
+
+    pub fn with_logger(mut self, logger: Logger) -> Self {
+        self.logger = logger;
+        self
+    }
+
+    pub fn with_events(mut self, events: EventSink) -> Self {
+        self.events = events;
+        self
+    }
+
+    /// Tag the next version of every module of `modules`, in order, skipping those without
+    /// commits since their latest tag unless the level is given, then push all new tags at
+    /// once. When creating or pushing any tag fails, the tags created so far are deleted again,
+    /// so either every tag is created (and pushed) or none is.
+    pub fn bump(&self, modules: &[Module]) -> Result<BumpReport> {
+        let mut report = BumpReport { outcomes: Vec::new(), pushed: self.remote.is_some() };
+        let created = self.tag_all(modules, &mut report.outcomes).and_then(|_| match &self.remote {
+            Some(remote) => self.push(remote, &report),
+            None => Ok(()),
+        });
+        if let Err(e) = created {
+            self.roll_back(&report);
+            return Err(e);
+        }
+
+        for outcome in &report.outcomes {
+            if let BumpStatus::Tagged { tag, version, previous } = &outcome.status {
+                self.events.emit(Event::TagCreated {
+                    module: outcome.module.clone(),
+                    tag: tag.clone(),
+                    version: version.to_string(),
+                    previous: previous.as_ref().map(ReleaseVersion::to_string),
+                    pushed: report.pushed,
+                });
+            }
+        }
+        Ok(report)
+    }
+
+    /// The release `module` is due: by the configured level, or by the level its commits
+    /// since its latest tag call for.
+    pub fn plan(&self, module: &str) -> Result<BumpPlan> {
+        let tags = self.releases.tags(module)?;
+        let (current, since) = match tags.latest() {
+            Some((version, tag)) => (version.clone(), Some(tag.to_string())),
+            None => (ReleaseVersion::initial(), None),
+        };
+        let commits = self.releases.commits_since(module, since.as_deref())?;
+        let level = match self.level {
+            Some(level) => Some(level),
+            None if commits.is_empty() => None,
+            None => {
+                let level = self.config.infer_bump(&current, &commits);
+                let since_label = since.as_deref().unwrap_or("the first commit");
+                self.logger.info(format!("{}: {} commits since {} call for a {} bump", module, commits.len(), since_label, level));
+                Some(level)
+            }
+        };
+        let next = match level {
+            Some(level) => {
+                Some(current.bump(level, &self.preid).map_err(|reason| Error::Release { module: module.to_string(), reason })?)
+            }
+            None => None,
+        };
+        Ok(BumpPlan { current, since, commits: commits.len(), next, malformed: tags.malformed })
+    }
+
+    /// Create the tag of every module of `modules` that needs one, recording each in `outcomes`
+    /// as soon as it exists.
+    fn tag_all(&self, modules: &[Module], outcomes: &mut Vec<BumpOutcome>) -> Result<()> {
+        let log = &self.logger;
+        for module in modules {
+            let plan = self.plan(&module.name)?;
+            for tag in &plan.malformed {
+                log.warn(format!("Ignoring malformed tag {}", tag));
+            }
+            let Some(next) = plan.next else {
+                let since = plan.since.as_deref().unwrap_or("the first commit");
+                log.info(format!("Skipped {}{}: no commits since {}", log.emoji("⏭️ "), module.name, since));
+                outcomes.push(BumpOutcome { module: module.name.clone(), status: BumpStatus::Unchanged { since: plan.since } });
+                continue;
+            };
+
+            let tag = tag_name(&module.name, &next);
+            self.releases.create_tag(&module.name, &tag, &format!("Bump {} to {}", module.name, next))?;
+            log.success(format!("{}Tagged {} ({} → {})", log.emoji("✅ "), tag, plan.current, next));
+            let previous = plan.since.map(|_| plan.current);
+            outcomes.push(BumpOutcome { module: module.name.clone(), status: BumpStatus::Tagged { tag, version: next, previous } });
+        }
+        Ok(())
+    }
+
+    fn push(&self, remote: &str, report: &BumpReport) -> Result<()> {
+        let tags: Vec<String> = report.tags().into_iter().map(str::to_string).collect();
+        if tags.is_empty() {
+            return Ok(());
+        }
+        self.releases.push_tags(remote, &tags)?;
+        self.logger.success(format!("{}Pushed {} to {}", self.logger.emoji("✅ "), tags.join(", "), remote));
+        Ok(())
+    }
+
+    /// Delete the tags created for `report`, after a later tag could not be created or pushed.
+    fn roll_back(&self, report: &BumpReport) {
+        let tags: Vec<String> = report.tags().into_iter().map(str::to_string).collect();
+        if tags.is_empty() {
+            return;
+        }
+        match self.releases.delete_tags(&tags) {
+            Ok(()) => self.logger.warn(format!("Deleted the tags created before the failure: {}", tags.join(", "))),
+            Err(e) => {
+                self.logger.warn(format!("Could not delete the tags created before the failure: {}", e));
+                self.logger.warn(format!("Delete them with: git tag --delete {}", tags.join(" ")));
+            }
+        }
+    }
+}
@@ -0,0 +1,68 @@
+use std::process;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread;
+
+use signal_hook::consts::{SIGINT, SIGTERM};
+use signal_hook::iterator::Signals;
+
+use crate::error::{Error, Result};
+
+/// Exit code used when daggy is interrupted, following the shell convention for SIGINT.
+pub const CANCELLED_EXIT_CODE: i32 = 130;
+
+/// A cancellation flag shared between the signal handler and running commands, along with
+/// the process groups of the commands still running.
+#[derive(Debug, Clone, Default)]
+pub struct Cancellation {
+    cancelled: Arc<AtomicBool>,
+    groups: Arc<Mutex<Vec<libc::pid_t>>>,
+}
+
+impl Cancellation {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn cancel(&self) {
+        self.cancelled.store(true, Ordering::SeqCst);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::SeqCst)
+    }
+
+    /// Remember the process group `group` of a running command until it is released.
+    pub(crate) fn track(&self, group: libc::pid_t) {
+        self.groups.lock().unwrap().push(group);
+    }
+
+    pub(crate) fn release(&self, group: libc::pid_t) {
+        self.groups.lock().unwrap().retain(|tracked| *tracked != group);
+    }
+
+    /// Kill the process groups of every running command.
+    fn kill_running(&self) {
+        for group in self.groups.lock().unwrap().iter() {
+            // SAFETY: kill(2) has no memory safety requirements; a negative pid addresses the group.
+            unsafe { libc::kill(-group, libc::SIGKILL) };
+        }
+    }
+
+    /// Cancel on SIGINT/SIGTERM. A second signal kills the running commands and terminates
+    /// daggy immediately.
+    pub fn install_signal_handlers(&self) -> Result<()> {
+        let mut signals = Signals::new([SIGINT, SIGTERM]).map_err(|e| Error::io("signal handler", e))?;
+        let cancellation = self.clone();
+        thread::spawn(move || {
+            for _ in signals.forever() {
+                if cancellation.is_cancelled() {
+                    cancellation.kill_running();
+                    process::exit(CANCELLED_EXIT_CODE);
+                }
+                cancellation.cancel();
+            }
+        });
+        Ok(())
+    }
+}
@@ -0,0 +1,88 @@
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt;
+use std::path::PathBuf;
+
+use crate::error::Result;
+use crate::fingerprint::local_dependencies;
+use crate::repo::Module;
+use crate::select::{owning_module, ModuleKind};
+
+/// Why a module counts as changed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Reason {
+    /// Files of the module, its tests or its examples changed.
+    Changed,
+    /// It depends, possibly through other modules, on this changed module.
+    DependsOn(String),
+}
+
+impl fmt::Display for Reason {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Reason::Changed => f.write_str("changed"),
+            Reason::DependsOn(module) => write!(f, "depends on {}", module),
+        }
+    }
+}
+
+/// A parent module affected by a set of changed files.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Affected {
+    pub module: String,
+    pub reason: Reason,
+}
+
+/// The parent module `module` belongs to: itself, or the innermost parent module containing
+/// its tests or examples.
+pub fn parent_of<'a>(modules: &'a [Module], module: &'a Module) -> &'a Module {
+    if ModuleKind::of(module) == ModuleKind::Parent {
+        return module;
+    }
+    modules
+        .iter()
+        .filter(|parent| ModuleKind::of(parent) == ModuleKind::Parent && module.path.starts_with(&parent.path))
+        .max_by_key(|parent| parent.name.len())
+        .unwrap_or(module)
+}
+
+/// The parent modules affected by `files`, paths relative to the repository root: those
+/// owning a changed file, then every module depending on an affected one through the local
+/// `dependencies` of its dagger.json files. Sorted by module name.
+pub fn affected_modules(modules: &[Module], files: &[PathBuf]) -> Result<Vec<Affected>> {
+    let mut affected: BTreeMap<String, Reason> = files
+        .iter()
+        .filter_map(|file| owning_module(modules, file))
+        .map(|module| (parent_of(modules, module).name.clone(), Reason::Changed))
+        .collect();
+
+    // dependency -> parents depending on it, including through their tests and examples.
+    let mut dependents: BTreeMap<String, BTreeSet<String>> = BTreeMap::new();
+    for module in modules {
+        let dependent = parent_of(modules, module);
+        for (_, dir) in local_dependencies(&module.path)? {
+            let Some(dependency) = modules.iter().find(|m| m.path.canonicalize().is_ok_and(|path| path == dir)) else {
+                continue;
+            };
+            let dependency = parent_of(modules, dependency);
+            if dependency.name != dependent.name {
+                dependents.entry(dependency.name.clone()).or_default().insert(dependent.name.clone());
+            }
+        }
+    }
+
+    let mut queue: Vec<String> = affected.keys().cloned().collect();
+    while let Some(changed) = queue.pop() {
+        let via = match &affected[&changed] {
+            Reason::Changed => changed.clone(),
+            Reason::DependsOn(via) => via.clone(),
+        };
+        for dependent in dependents.get(&changed).into_iter().flatten() {
+            if !affected.contains_key(dependent) {
+                affected.insert(dependent.clone(), Reason::DependsOn(via.clone()));
+                queue.push(dependent.clone());
+            }
+        }
+    }
+
+    Ok(affected.into_iter().map(|(module, reason)| Affected { module, reason }).collect())
+}
@@ -0,0 +1,167 @@
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use serde::Deserialize;
+
+use crate::error::{Error, IoResultExt, Result};
+use crate::release::{Commit, ReleaseConfig, ReleaseVersion, Releases};
+
+/// Name of the changelog kept in every released module.
+pub const CHANGELOG_FILE: &str = "CHANGELOG.md";
+
+const TITLE: &str = "# Changelog";
+
+/// One `changelog-sections` entry of `release-please-config.json`.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct ChangelogSection {
+    /// The conventional commit type, e.g. `feat`.
+    #[serde(rename = "type")]
+    pub kind: String,
+    /// The heading commits of this type are listed under.
+    pub section: String,
+    /// Leave commits of this type out of the changelog.
+    #[serde(default)]
+    pub hidden: bool,
+}
+
+impl ChangelogSection {
+    pub fn new(kind: &str, section: &str) -> Self {
+        Self { kind: kind.to_string(), section: section.to_string(), hidden: false }
+    }
+
+    /// The sections release-please uses when none are configured.
+    pub fn defaults() -> Vec<Self> {
+        vec![
+            Self::new("feat", "Features"),
+            Self::new("fix", "Bug Fixes"),
+            Self::new("perf", "Performance Improvements"),
+            Self::new("revert", "Reverts"),
+        ]
+    }
+}
+
+/// The changelog entry of `version`, released on `date`, listing the conventional `commits`
+/// by section in the order of `sections`, breaking changes first. Commits of hidden or
+/// unknown types and commits that are not conventional are left out.
+pub fn render_entry(version: &ReleaseVersion, date: &str, commits: &[Commit], sections: &[ChangelogSection]) -> String {
+    let mut entry = format!("## {} ({})\n", version, date);
+    let line = |commit: &Commit| {
+        let conventional = commit.conventional()?;
+        let scope = conventional.scope.map(|scope| format!("**{}:** ", scope)).unwrap_or_default();
+        let short = &commit.hash[..commit.hash.len().min(7)];
+        Some(format!("* {}{} ({})\n", scope, conventional.description, short))
+    };
+
+    let mut groups: Vec<(&str, Vec<String>)> = Vec::new();
+    let breaking: Vec<String> = commits.iter().filter(|commit| commit.is_breaking()).filter_map(line).collect();
+    if !breaking.is_empty() {
+        groups.push(("⚠ BREAKING CHANGES", breaking));
+    }
+    for section in sections.iter().filter(|section| !section.hidden) {
+        let lines: Vec<String> = commits
+            .iter()
+            .filter(|commit| commit.conventional().is_some_and(|c| c.kind == section.kind))
+            .filter_map(line)
+            .collect();
+        if lines.is_empty() {
+            continue;
+        }
+        match groups.iter_mut().find(|(heading, _)| *heading == section.section) {
+            Some((_, existing)) => existing.extend(lines),
+            None => groups.push((&section.section, lines)),
+        }
+    }
+
+    if groups.is_empty() {
+        entry.push_str("\nNo notable changes.\n");
+    }
+    for (heading, lines) in groups {
+        entry.push_str(&format!("\n\n### {}\n\n", heading));
+        entry.extend(lines);
+    }
+    entry
+}
+
+/// Whether the changelog `content` already has an entry for `version`.
+pub fn has_entry(content: &str, version: &ReleaseVersion) -> bool {
+    let heading = format!("## {} ", version);
+    content.lines().any(|line| line.starts_with(&heading))
+}
+
+/// `existing` changelog content with `entry` inserted as the newest one, below the title.
+pub fn prepend(existing: Option<&str>, entry: &str) -> String {
+    let existing = existing.unwrap_or_default();
+    let rest = existing.strip_prefix(TITLE).map_or(existing, |rest| rest.trim_start_matches('\n'));
+    if rest.is_empty() {
+        format!("{}\n\n{}", TITLE, entry)
+    } else {
+        format!("{}\n\n{}\n{}", TITLE, entry, rest)
+    }
+}
+
+/// The changelog entry of a release tag of a module.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ReleaseEntry {
+    pub module: String,
+    pub tag: String,
+    pub version: ReleaseVersion,
+    /// The release before it, whose commits are left out; `None` for the first release.
+    pub previous: Option<String>,
+    /// How many commits touching the module it covers.
+    pub commits: usize,
+    /// The rendered entry, heading included.
+    pub entry: String,
+}
+
+/// What [`ReleaseEntry::write`] did to a changelog.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ChangelogUpdate {
+    Added(PathBuf),
+    /// The changelog already had an entry for the version and was left alone.
+    Present(PathBuf),
+}
+
+impl ReleaseEntry {
+    /// The entry of the release `tag` of `module`, or of its latest release without one. A
+    /// release covers everything since the previous release, including its prereleases.
+    pub fn read(releases: &Releases, config: &ReleaseConfig, module: &str, tag: Option<&str>) -> Result<Self> {
+        let tags = releases.tags(module)?;
+        let release = |reason: String| Error::Release { module: module.to_string(), reason };
+        let index = match tag {
+            Some(tag) => tags.versions.iter().position(|(_, t)| t == tag).ok_or_else(|| release(format!("no release tag {}", tag)))?,
+            None => tags.versions.len().checked_sub(1).ok_or_else(|| release("no release tags yet, create one with --task bump".to_string()))?,
+        };
+        let (version, tag) = &tags.versions[index];
+        let previous = tags.versions[..index]
+            .iter()
+            .rev()
+            .find(|(earlier, _)| version.pre.is_some() || earlier.pre.is_none())
+            .map(|(_, tag)| tag.clone());
+
+        let commits = releases.commits_between(module, previous.as_deref(), tag)?;
+        let entry = render_entry(version, &releases.tag_date(tag)?, &commits, &config.sections());
+        Ok(Self { module: module.to_string(), tag: tag.clone(), version: version.clone(), previous, commits: commits.len(), entry })
+    }
+
+    /// The release notes: the entry without its heading.
+    pub fn notes(&self) -> &str {
+        self.entry.split_once('\n').map_or("", |(_, notes)| notes).trim_start_matches('\n')
+    }
+
+    /// Add the entry to the changelog of the module in `module_dir`, unless it already has
+    /// one for the version.
+    pub fn write(&self, module_dir: &Path) -> Result<ChangelogUpdate> {
+        let path = module_dir.join(CHANGELOG_FILE);
+        let existing = match fs::read_to_string(&path) {
+            Ok(content) => Some(content),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => None,
+            Err(e) => return Err(Error::io(&path, e)),
+        };
+        if existing.as_deref().is_some_and(|content| has_entry(content, &self.version)) {
+            return Ok(ChangelogUpdate::Present(path));
+        }
+        fs::write(&path, prepend(existing.as_deref(), &self.entry)).at_path(&path)?;
+        Ok(ChangelogUpdate::Added(path))
+    }
+}
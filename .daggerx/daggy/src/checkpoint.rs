@@ -0,0 +1,85 @@
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+use crate::error::{Error, IoResultExt, Result};
+use crate::scaffold::{Components, CreateStep};
+
+/// Name of the state file `create` keeps inside the module it is creating.
+pub const STATE_FILE: &str = ".daggy-create.json";
+
+/// Progress of a `create` run, saved after every finished step so that a failed or
+/// interrupted run can be resumed. Removed once the module is complete.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CreateState {
+    pub module: String,
+    /// Whether the module is scaffolded without a dagger engine.
+    #[serde(default)]
+    pub offline: bool,
+    /// The parts of the module being created.
+    #[serde(default)]
+    pub components: Components,
+    /// Names of the finished steps, see [`CreateStep::name`].
+    pub completed: Vec<String>,
+    #[serde(skip)]
+    path: PathBuf,
+}
+
+impl CreateState {
+    /// Path of the state file of the module in `module_dir`.
+    pub fn path(module_dir: &Path) -> PathBuf {
+        module_dir.join(STATE_FILE)
+    }
+
+    pub fn exists(module_dir: &Path) -> bool {
+        Self::path(module_dir).is_file()
+    }
+
+    /// Start tracking a new module, creating its directory and an empty state file.
+    pub fn begin(module: &str, module_dir: &Path, offline: bool, components: Components) -> Result<Self> {
+        fs::create_dir_all(module_dir).at_path(module_dir)?;
+        let state = Self {
+            module: module.to_string(),
+            offline,
+            components,
+            completed: Vec::new(),
+            path: Self::path(module_dir),
+        };
+        state.save()?;
+        Ok(state)
+    }
+
+    /// Load the state file of the module in `module_dir`.
+    pub fn load(module_dir: &Path) -> Result<Self> {
+        let path = Self::path(module_dir);
+        let content = fs::read_to_string(&path).at_path(&path)?;
+        let mut state: Self = serde_json::from_str(&content)
+            .map_err(|e| Error::io(&path, io::Error::new(io::ErrorKind::InvalidData, e)))?;
+        state.path = path;
+        Ok(state)
+    }
+
+    pub fn is_completed(&self, step: CreateStep) -> bool {
+        self.completed.iter().any(|name| name == step.name())
+    }
+
+    /// Record `step` as finished and save the state.
+    pub fn complete(&mut self, step: CreateStep) -> Result<()> {
+        if !self.is_completed(step) {
+            self.completed.push(step.name().to_string());
+        }
+        self.save()
+    }
+
+    /// Remove the state file once every step has finished.
+    pub fn finish(&self) -> Result<()> {
+        fs::remove_file(&self.path).at_path(&self.path)
+    }
+
+    fn save(&self) -> Result<()> {
+        let content = serde_json::to_string_pretty(self).expect("create state serializes to JSON");
+        fs::write(&self.path, content + "\n").at_path(&self.path)
+    }
+}
@@ -0,0 +1,437 @@
+use std::collections::VecDeque;
+use std::fmt;
+use std::fs::{self, File};
+use std::io::{self, BufRead, BufReader, Read, Write};
+use std::path::PathBuf;
+use std::os::unix::process::CommandExt;
+use std::process::{Child, Command, ExitStatus, Stdio};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::mpsc::{self, RecvTimeoutError};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use crate::cancel::Cancellation;
+use crate::error::{Error, Result};
+use crate::event::{Event, EventSink};
+use crate::log::Logger;
+
+/// How often a running command is checked for timeouts and cancellation.
+const POLL_INTERVAL: Duration = Duration::from_millis(50);
+
+/// How long a process group gets to exit after SIGTERM before it is killed.
+const TERMINATE_GRACE: Duration = Duration::from_secs(2);
+
+/// A program invocation: program, argument vector, extra environment and working directory.
+/// Arguments are passed verbatim to the program, no shell is involved.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Cmd {
+    pub program: String,
+    pub args: Vec<String>,
+    pub env: Vec<(String, String)>,
+    pub cwd: PathBuf,
+    /// Capture stdout and stderr instead of streaming them to the terminal.
+    pub capture: bool,
+}
+
+impl Cmd {
+    pub fn new(program: impl Into<String>, cwd: impl Into<PathBuf>) -> Self {
+        Self { program: program.into(), args: Vec::new(), env: Vec::new(), cwd: cwd.into(), capture: false }
+    }
+
+    pub fn capture_output(mut self) -> Self {
+        self.capture = true;
+        self
+    }
+
+    pub fn arg(mut self, arg: impl Into<String>) -> Self {
+        self.args.push(arg.into());
+        self
+    }
+
+    pub fn args<I, S>(mut self, args: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.args.extend(args.into_iter().map(Into::into));
+        self
+    }
+
+    pub fn env(mut self, key: impl Into<String>, value: impl Into<String>) -> Self {
+        self.env.push((key.into(), value.into()));
+        self
+    }
+}
+
+impl fmt::Display for Cmd {
+    /// Render the command line, quoting arguments the way a shell would need them.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", quote(&self.program))?;
+        for arg in &self.args {
+            write!(f, " {}", quote(arg))?;
+        }
+        Ok(())
+    }
+}
+
+fn quote(arg: &str) -> String {
+    let plain = !arg.is_empty()
+        && arg.chars().all(|c| c.is_ascii_alphanumeric() || "-_./=:@+,".contains(c));
+    if plain {
+        arg.to_string()
+    } else {
+        format!("'{}'", arg.replace('\'', r"'\''"))
+    }
+}
+
+/// Number of trailing stdout and stderr lines kept for error messages and reports.
+pub const DEFAULT_STDERR_TAIL_LINES: usize = 20;
+
+/// Result of a finished command.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CommandOutput {
+    /// Exit code, `None` when the process was terminated by a signal.
+    pub code: Option<i32>,
+    /// Everything written to stdout by commands built with [`Cmd::capture_output`], the last
+    /// lines for all others.
+    pub stdout: Vec<u8>,
+    /// The last lines the command wrote to stderr.
+    pub stderr: Vec<u8>,
+    pub elapsed: Duration,
+    /// Set when daggy stopped the command before it exited on its own.
+    pub interrupted: Option<Interruption>,
+}
+
+/// Why daggy stopped a command.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Interruption {
+    TimedOut(Duration),
+    Cancelled,
+}
+
+impl CommandOutput {
+    pub fn success(&self) -> bool {
+        self.code == Some(0)
+    }
+
+    /// The last `lines` lines of stdout.
+    pub fn stdout_tail(&self, lines: usize) -> Vec<String> {
+        last_lines(&self.stdout, lines)
+    }
+
+    /// The last `lines` lines of stderr.
+    pub fn stderr_tail(&self, lines: usize) -> Vec<String> {
+        last_lines(&self.stderr, lines)
+    }
+}
+
+fn last_lines(output: &[u8], lines: usize) -> Vec<String> {
+    let output = String::from_utf8_lossy(output);
+    let all: Vec<&str> = output.lines().collect();
+    all[all.len().saturating_sub(lines)..].iter().map(|line| line.to_string()).collect()
+}
+
+/// Runs commands on behalf of daggy. Tests substitute a runner recording the commands for
+/// the real thing.
+pub trait CommandRunner: fmt::Debug + Send + Sync {
+    /// Run `cmd` to completion. Only failures to start the program are errors.
+    fn run(&self, cmd: &Cmd) -> Result<CommandOutput>;
+
+    /// Run `cmd` and turn an unsuccessful exit into [`Error::CommandFailed`].
+    fn run_checked(&self, cmd: &Cmd) -> Result<CommandOutput> {
+        let output = self.run(cmd)?;
+        match output.interrupted {
+            Some(Interruption::Cancelled) => return Err(Error::Cancelled),
+            Some(Interruption::TimedOut(timeout)) => {
+                return Err(Error::CommandTimedOut { command: cmd.to_string(), cwd: cmd.cwd.clone(), timeout });
+            }
+            None => {}
+        }
+        if !output.success() {
+            return Err(Error::CommandFailed {
+                command: cmd.to_string(),
+                cwd: cmd.cwd.clone(),
+                code: output.code,
+                elapsed: output.elapsed,
+                stdout_tail: output.stdout_tail(DEFAULT_STDERR_TAIL_LINES),
+                stderr_tail: output.stderr_tail(DEFAULT_STDERR_TAIL_LINES),
+            });
+        }
+        Ok(output)
+    }
+}
+
+/// Runs commands as child processes.
+///
+/// Output is streamed live to the terminal and tee'd into bounded buffers holding the last
+/// lines of each stream, and optionally into a per-command log file. Every command runs in its own
+/// process group, which is terminated as a whole on timeout or cancellation.
+#[derive(Debug, Clone)]
+pub struct SystemRunner {
+    tail_lines: usize,
+    log_dir: Option<PathBuf>,
+    sequence: Arc<AtomicUsize>,
+    timeout: Option<Duration>,
+    cancellation: Cancellation,
+    logger: Logger,
+    events: EventSink,
+}
+
+impl Default for SystemRunner {
+    fn default() -> Self {
+        Self {
+            tail_lines: DEFAULT_STDERR_TAIL_LINES,
+            log_dir: None,
+            sequence: Arc::new(AtomicUsize::new(0)),
+            timeout: None,
+            cancellation: Cancellation::new(),
+            logger: Logger::silent(),
+            events: EventSink::default(),
+        }
+    }
+}
+
+impl SystemRunner {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Keep the last `lines` lines of stdout and stderr for error messages.
+    pub fn with_tail_lines(mut self, lines: usize) -> Self {
+        self.tail_lines = lines;
+        self
+    }
+
+    /// Stop commands that run longer than `timeout`.
+    pub fn with_timeout(mut self, timeout: Option<Duration>) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// Stop running commands once `cancellation` is triggered.
+    pub fn with_cancellation(mut self, cancellation: Cancellation) -> Self {
+        self.cancellation = cancellation;
+        self
+    }
+
+    /// Echo commands and stream their output according to `logger`.
+    pub fn with_logger(mut self, logger: Logger) -> Self {
+        self.logger = logger;
+        self
+    }
+
+    /// Report every command starting and finishing to `events`.
+    pub fn with_events(mut self, events: EventSink) -> Self {
+        self.events = events;
+        self
+    }
+
+    /// Write the full output of every command to a log file inside `dir`.
+    pub fn with_log_dir(mut self, dir: impl Into<PathBuf>) -> Self {
+        self.log_dir = Some(dir.into());
+        self
+    }
+
+    fn open_log(&self, cmd: &Cmd) -> Result<Option<File>> {
+        let Some(dir) = &self.log_dir else {
+            return Ok(None);
+        };
+        fs::create_dir_all(dir).map_err(|e| Error::io(dir, e))?;
+
+        let millis = SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default().as_millis();
+        let sequence = self.sequence.fetch_add(1, Ordering::SeqCst) + 1;
+        let mut name = format!("{}-{:03}-{}", millis, sequence, cmd.program);
+        if let Some(first) = cmd.args.first() {
+            name.push('-');
+            name.push_str(first);
+        }
+        let name: String = name.chars().map(|c| if c.is_ascii_alphanumeric() || c == '-' { c } else { '_' }).collect();
+
+        let path = dir.join(format!("{}.log", name));
+        let mut file = File::create(&path).map_err(|e| Error::io(&path, e))?;
+        writeln!(file, "$ {}\n# cwd: {}\n", cmd, cmd.cwd.display()).map_err(|e| Error::io(&path, e))?;
+        Ok(Some(file))
+    }
+}
+
+type SharedLog = Option<Arc<Mutex<File>>>;
+
+impl CommandRunner for SystemRunner {
+    fn run(&self, cmd: &Cmd) -> Result<CommandOutput> {
+        if self.cancellation.is_cancelled() {
+            return Ok(CommandOutput { interrupted: Some(Interruption::Cancelled), ..Default::default() });
+        }
+
+        self.logger.debug(format!("Running command: {}", cmd));
+        self.logger.debug(format!("Running command in directory: {}", cmd.cwd.display()));
+
+        let log: SharedLog = self.open_log(cmd)?.map(|file| Arc::new(Mutex::new(file)));
+        self.events.emit(Event::command_started(cmd));
+        let started = Instant::now();
+
+        let mut child = Command::new(&cmd.program)
+            .args(&cmd.args)
+            .envs(cmd.env.iter().map(|(k, v)| (k, v)))
+            .current_dir(&cmd.cwd)
+            .stdin(Stdio::null())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .process_group(0)
+            .spawn()
+            .map_err(|e| spawn_error(cmd, e))?;
+
+        let stdout = child.stdout.take().expect("stdout is piped");
+        let stderr = child.stderr.take().expect("stderr is piped");
+        let (capture, tail_lines) = (cmd.capture, self.tail_lines);
+        let echo = !capture && self.logger.shows_command_output();
+
+        let stdout_log = log.clone();
+        let stdout_pump = thread::spawn(move || {
+            let mut captured = Vec::new();
+            let mut tail = Tail::new(tail_lines);
+            pump(stdout, &stdout_log, |line| {
+                if capture {
+                    captured.extend_from_slice(line);
+                } else {
+                    if echo {
+                        let _ = io::stderr().write_all(line);
+                    }
+                    tail.push(line);
+                }
+            });
+            if capture { captured } else { tail.into_bytes() }
+        });
+
+        let stderr_log = log.clone();
+        let stderr_pump = thread::spawn(move || {
+            let mut tail = Tail::new(tail_lines);
+            pump(stderr, &stderr_log, |line| {
+                if echo {
+                    let _ = io::stderr().write_all(line);
+                }
+                tail.push(line);
+            });
+            tail.into_bytes()
+        });
+
+        let (status, interrupted) = self.wait(child, started).map_err(|e| Error::io(&cmd.cwd, e))?;
+        let stdout = stdout_pump.join().unwrap_or_default();
+        let stderr = stderr_pump.join().unwrap_or_default();
+        let elapsed = started.elapsed();
+
+        if let Some(log) = &log {
+            let mut file = log.lock().unwrap();
+            let _ = writeln!(file, "\n# exit code: {:?}, elapsed: {:.2?}", status.code(), elapsed);
+            if let Some(interrupted) = interrupted {
+                let _ = writeln!(file, "# interrupted: {:?}", interrupted);
+            }
+        }
+
+        let output = CommandOutput { code: status.code(), stdout, stderr, elapsed, interrupted };
+        self.events.emit(Event::command_finished(cmd, &output));
+        Ok(output)
+    }
+}
+
+impl SystemRunner {
+    /// Wait for `child`, terminating its process group on timeout or cancellation.
+    fn wait(&self, child: Child, started: Instant) -> io::Result<(ExitStatus, Option<Interruption>)> {
+        let group = child.id() as libc::pid_t;
+        self.cancellation.track(group);
+        let result = self.wait_group(child, group, started);
+        self.cancellation.release(group);
+        result
+    }
+
+    fn wait_group(&self, child: Child, group: libc::pid_t, started: Instant) -> io::Result<(ExitStatus, Option<Interruption>)> {
+        let (tx, rx) = mpsc::channel();
+        let mut child = child;
+        thread::spawn(move || {
+            let _ = tx.send(child.wait());
+        });
+
+        let mut interrupted = None;
+        let mut kill_at = None;
+        loop {
+            match rx.recv_timeout(POLL_INTERVAL) {
+                Ok(status) => return Ok((status?, interrupted)),
+                Err(RecvTimeoutError::Disconnected) => return Err(io::Error::other("lost track of child process")),
+                Err(RecvTimeoutError::Timeout) => {}
+            }
+
+            match kill_at {
+                None => {
+                    interrupted = if self.cancellation.is_cancelled() {
+                        Some(Interruption::Cancelled)
+                    } else {
+                        self.timeout.filter(|timeout| started.elapsed() >= *timeout).map(Interruption::TimedOut)
+                    };
+                    if interrupted.is_some() {
+                        signal_group(group, libc::SIGTERM);
+                        kill_at = Some(Instant::now() + TERMINATE_GRACE);
+                    }
+                }
+                Some(deadline) if Instant::now() >= deadline => signal_group(group, libc::SIGKILL),
+                Some(_) => {}
+            }
+        }
+    }
+}
+
+/// Send `signal` to every process of the process group `group`.
+fn signal_group(group: libc::pid_t, signal: libc::c_int) {
+    // SAFETY: kill(2) has no memory safety requirements; a negative pid addresses the group.
+    unsafe { libc::kill(-group, signal) };
+}
+
+/// The last lines of a stream, bounded to `limit` lines.
+struct Tail {
+    lines: VecDeque<Vec<u8>>,
+    limit: usize,
+}
+
+impl Tail {
+    fn new(limit: usize) -> Self {
+        Self { lines: VecDeque::with_capacity(limit), limit }
+    }
+
+    fn push(&mut self, line: &[u8]) {
+        if self.limit == 0 {
+            return;
+        }
+        if self.lines.len() == self.limit {
+            self.lines.pop_front();
+        }
+        self.lines.push_back(line.to_vec());
+    }
+
+    fn into_bytes(self) -> Vec<u8> {
+        self.lines.into_iter().flatten().collect()
+    }
+}
+
+/// Read `reader` line by line, handing every line to `sink` and appending it to `log`.
+fn pump(reader: impl Read, log: &SharedLog, mut sink: impl FnMut(&[u8])) {
+    let mut reader = BufReader::new(reader);
+    let mut line = Vec::new();
+    loop {
+        line.clear();
+        match reader.read_until(b'\n', &mut line) {
+            Ok(0) | Err(_) => break,
+            Ok(_) => {
+                sink(&line);
+                if let Some(log) = log {
+                    let _ = log.lock().unwrap().write_all(&line);
+                }
+            }
+        }
+    }
+}
+
+fn spawn_error(cmd: &Cmd, e: io::Error) -> Error {
+    match e.kind() {
+        io::ErrorKind::NotFound if cmd.cwd.is_dir() => Error::ToolMissing { tool: cmd.program.clone() },
+        _ => Error::io(&cmd.cwd, e),
+    }
+}
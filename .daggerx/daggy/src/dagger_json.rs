@@ -0,0 +1,121 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use serde::Serialize;
+use serde_json::{json, Value};
+
+use crate::error::{Error, Result};
+
+/// Exclude list of a parent module's dagger.json.
+pub fn module_excludes() -> Value {
+    json!([
+        "../.direnv",
+        "../.devenv",
+        "../go.work",
+        "../go.work.sum",
+        "tests",
+        "examples/go",
+    ])
+}
+
+/// Exclude list of a module's `tests/dagger.json`.
+pub fn tests_excludes() -> Value {
+    json!([
+        "../../.direnv",
+        "../../.devenv",
+        "../../go.work",
+        "../../go.work.sum"
+    ])
+}
+
+/// Exclude list of a module's `examples/go/dagger.json`.
+pub fn examples_excludes() -> Value {
+    json!([
+        "../../../.direnv",
+        "../../../.devenv",
+        "../../../go.work",
+        "../../../go.work.sum"
+    ])
+}
+
+/// An entry of the `dependencies` list of a dagger.json.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Dependency {
+    pub name: String,
+    /// Path of the dependency relative to the module, e.g. `../..`.
+    pub source: String,
+}
+
+/// A Go module's dagger.json with its keys in the order `dagger init` writes them.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Config {
+    pub name: String,
+    pub sdk: String,
+    pub exclude: Value,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub dependencies: Vec<Dependency>,
+    pub source: String,
+    pub engine_version: String,
+}
+
+impl Config {
+    /// A Go module called `name` whose sources live next to its dagger.json.
+    pub fn go(name: impl Into<String>, engine_version: impl Into<String>, exclude: Value) -> Self {
+        Self {
+            name: name.into(),
+            sdk: "go".to_string(),
+            exclude,
+            dependencies: Vec::new(),
+            source: ".".to_string(),
+            engine_version: engine_version.into(),
+        }
+    }
+
+    pub fn with_dependency(mut self, name: impl Into<String>, source: impl Into<String>) -> Self {
+        self.dependencies.push(Dependency { name: name.into(), source: source.into() });
+        self
+    }
+}
+
+/// Path of the dagger.json inside `module_dir`.
+pub fn path(module_dir: &Path) -> PathBuf {
+    module_dir.join("dagger.json")
+}
+
+pub fn read(module_dir: &Path) -> Result<Value> {
+    let dagger_json_path = path(module_dir);
+
+    let content = fs::read_to_string(&dagger_json_path)
+        .map_err(|e| Error::DaggerJson { path: dagger_json_path.clone(), reason: format!("failed to read: {}", e) })?;
+    serde_json::from_str(&content)
+        .map_err(|e| Error::DaggerJson { path: dagger_json_path, reason: format!("failed to parse: {}", e) })
+}
+
+pub fn write(module_dir: &Path, content: &impl Serialize) -> Result<PathBuf> {
+    let dagger_json_path = path(module_dir);
+    let serialized = serde_json::to_string_pretty(content)
+        .map_err(|e| Error::DaggerJson { path: dagger_json_path.clone(), reason: format!("failed to serialize: {}", e) })?;
+
+    fs::write(&dagger_json_path, serialized)
+        .map_err(|e| Error::DaggerJson { path: dagger_json_path.clone(), reason: format!("failed to write: {}", e) })?;
+
+    Ok(dagger_json_path)
+}
+
+/// The directory holding the Go sources of the module in `module_dir`, from the `source`
+/// of its dagger.json, e.g. `<module>/dagger`. Defaults to the module directory itself.
+pub fn source_dir(module_dir: &Path) -> Result<PathBuf> {
+    let json = read(module_dir)?;
+    Ok(match json["source"].as_str() {
+        Some(source) if !source.is_empty() && source != "." => module_dir.join(source),
+        _ => module_dir.to_path_buf(),
+    })
+}
+
+/// Rewrite the `exclude` list of the dagger.json found in `module_dir`.
+pub fn set_excludes(module_dir: &Path, exclude: Value) -> Result<PathBuf> {
+    let mut json_content = read(module_dir)?;
+    json_content["exclude"] = exclude;
+    write(module_dir, &json_content)
+}
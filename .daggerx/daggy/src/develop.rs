@@ -0,0 +1,335 @@
+use std::path::Path;
+use std::time::{Duration, Instant};
+
+use crate::drift::{FileChange, TreeSnapshot};
+use crate::error::{Error, Result};
+use crate::event::{millis, ErrorDetails, Event, EventSink};
+use crate::fingerprint::{fingerprint, CacheEntry, FingerprintCache};
+use crate::github::GithubActions;
+use crate::log::Logger;
+use crate::repo::{Module, Repo};
+use crate::scaffold::{DevelopOutcome, DevelopReport, DevelopStatus, Scaffolder};
+
+/// A dagger engine modules are developed with: the scaffolder running its binary, and the
+/// label it is reported under, usually its version.
+#[derive(Debug, Clone)]
+pub struct Engine {
+    pub label: String,
+    pub scaffolder: Scaffolder,
+}
+
+/// How developing a list of modules ended.
+#[derive(Debug, Default)]
+pub struct DevelopRun {
+    pub report: DevelopReport,
+    /// Modules that were not reached, in order: after an interruption, or after the first
+    /// failure of [`Developer::develop_until_failure`].
+    pub pending: Vec<Module>,
+    /// The cancellation that stopped the run early.
+    pub interrupted: Option<Error>,
+}
+
+impl DevelopRun {
+    /// The modules developed before an interruption, e.g. `alpha ✅` with the emoji of `log`,
+    /// skipped ones left out.
+    pub fn finished(&self, log: &Logger) -> Vec<String> {
+        self.report
+            .outcomes
+            .iter()
+            .filter_map(|outcome| match outcome.status {
+                DevelopStatus::Succeeded => Some(format!("{}{}", outcome.title(), log.emoji(" ✅"))),
+                DevelopStatus::Failed(_) | DevelopStatus::Drifted(_) => Some(format!("{}{}", outcome.title(), log.emoji(" ❌"))),
+                DevelopStatus::Skipped | DevelopStatus::Unchanged => None,
+            })
+            .collect()
+    }
+
+    /// The modules `dagger develop` changed under `--check`, with how many files each.
+    pub fn drifted(&self) -> Vec<(&str, usize)> {
+        self.report
+            .outcomes
+            .iter()
+            .filter_map(|outcome| match &outcome.status {
+                DevelopStatus::Drifted(changes) => Some((outcome.module.name.as_str(), changes.len())),
+                _ => None,
+            })
+            .collect()
+    }
+}
+
+/// Develops the modules of a repository with one or more engines, skipping modules whose
+/// inputs did not change and, with `check`, listing what develop changed.
+///
+/// Progress goes to the logger, the event sink and GitHub Actions as modules are developed;
+/// the outcome is returned as a [`DevelopRun`] for the caller to summarize.
+#[derive(Debug, Clone)]
+pub struct Developer {
+    repo: Repo,
+    engines: Vec<Engine>,
+    check: bool,
+    restore: bool,
+    force: bool,
+    logger: Logger,
+    events: EventSink,
+    github: GithubActions,
+}
+
+impl Developer {
+    pub fn new(repo: Repo) -> Self {
+        Self {
+            repo,
+            engines: Vec::new(),
+            check: false,
+            restore: false,
+            force: false,
+            logger: Logger::silent(),
+            events: EventSink::disabled(),
+            github: GithubActions::disabled(),
+        }
+    }
+
+    /// Develop every module with `engine` too; more than one engine makes a matrix.
+    pub fn with_engine(mut self, engine: Engine) -> Self {
+        self.engines.push(engine);
+        self
+    }
+
+    /// Develop every module and fail those that develop changed, as drifted.
+    pub fn with_check(mut self, check: bool) -> Self {
+        self.check = check;
+        self
+    }
+
+    /// With `check`, put the files develop changed back the way they were.
+    pub fn with_restore(mut self, restore: bool) -> Self {
+        self.restore = restore;
+        self
+    }
+
+    /// Develop modules even when their inputs did not change.
+    pub fn with_force(mut self, force: bool) -> Self {
+        self.force = force;
+        self
+    }
+
+    pub fn with_logger(mut self, logger: Logger) -> Self {
+        self.logger = logger;
+        self
+    }
+
+    pub fn with_events(mut self, events: EventSink) -> Self {
+        self.events = events;
+        self
+    }
+
+    pub fn with_github(mut self, github: GithubActions) -> Self {
+        self.github = github;
+        self
+    }
+
+    pub fn engines(&self) -> &[Engine] {
+        &self.engines
+    }
+
+    pub fn is_matrix(&self) -> bool {
+        self.engines.len() > 1
+    }
+
+    /// Develop every module with every engine, in order. Failures are recorded in the run;
+    /// an interruption stops it and leaves the remaining modules pending.
+    pub fn develop(&self, modules: &[Module]) -> Result<DevelopRun> {
+        let log = &self.logger;
+        let matrix = self.is_matrix();
+        // A matrix is run to try every engine and a check to see what develop changes, so
+        // both always develop every module.
+        let mut cache = if matrix || self.check { None } else { Some(FingerprintCache::load(&self.repo)?) };
+        let mut run = DevelopRun::default();
+
+        for (index, module) in modules.iter().enumerate() {
+            for engine in &self.engines {
+                let label = matrix.then(|| engine.label.clone());
+                let reason = match &cache {
+                    Some(cache) => match self.stale_reason(cache, module, &engine.label) {
+                        Some(reason) => Some(reason),
+                        None => {
+                            let status = DevelopStatus::Unchanged;
+                            let outcome = DevelopOutcome { module: module.clone(), status, elapsed: Duration::ZERO, engine: label };
+                            log.info(format!("Skipped {}{}: unchanged since the last successful develop", log.emoji("⏭️ "), outcome.title()));
+                            self.emit(&outcome);
+                            run.report.outcomes.push(outcome);
+                            continue;
+                        }
+                    },
+                    None => None,
+                };
+
+                let title = DevelopOutcome::title_of(module, label.as_deref());
+                self.github.group(&format!("dagger develop: {}", title));
+                log.info(format!("Developing module: {}...", title));
+                if let Some(reason) = reason {
+                    log.info(format!("Reason: {}", reason));
+                }
+
+                let before = if self.check { Some(TreeSnapshot::take(&module.path)?) } else { None };
+                let started = Instant::now();
+                let status = engine.scaffolder.develop(module);
+                let elapsed = started.elapsed();
+                self.github.end_group();
+                let status = match (status, &before) {
+                    (DevelopStatus::Failed(e), _) if matches!(e.root_cause(), Error::Cancelled) => {
+                        run.pending = modules[index..].to_vec();
+                        run.interrupted = Some(e);
+                        return Ok(run);
+                    }
+                    // Under --check a module that develop changed fails, with the files it changed.
+                    (DevelopStatus::Succeeded, Some(before)) => match self.check_drift(module, before)? {
+                        changes if changes.is_empty() => DevelopStatus::Succeeded,
+                        changes => DevelopStatus::Drifted(changes),
+                    },
+                    (status, _) => status,
+                };
+                let outcome = DevelopOutcome { module: module.clone(), status, elapsed, engine: label };
+
+                match &outcome.status {
+                    DevelopStatus::Succeeded => {
+                        log.success(format!("{}Successfully developed module: {}", log.emoji("✅ "), title));
+                        // Fingerprinted after develop, which may itself rewrite go.mod and go.sum.
+                        if let (Some(cache), Ok(fingerprint)) = (&mut cache, fingerprint(&module.path)) {
+                            cache.record(&module.name, CacheEntry { fingerprint, engine: engine.label.clone() })?;
+                        }
+                    }
+                    DevelopStatus::Failed(e) => {
+                        log.error(format!("{}Failed to develop module: {}", log.emoji("❌ "), title));
+                        log.error(format!("Error: {}", e));
+                        let file = format!("{}/dagger.json", module.name);
+                        self.github.error(&file, &format!("dagger develop failed in {}", title), &e.root_cause().to_string());
+                    }
+                    DevelopStatus::Skipped => log.info(format!("Skipped {}No dagger.json found in: {}", log.emoji("🚫 "), module.name)),
+                    DevelopStatus::Drifted(_) | DevelopStatus::Unchanged => {}
+                }
+                self.emit(&outcome);
+                run.report.outcomes.push(outcome);
+            }
+        }
+        Ok(run)
+    }
+
+    /// Develop `modules` in order with the first engine, stopping at the first failure, as
+    /// `--watch` does after every change. Modules are always developed and never checked.
+    pub fn develop_until_failure(&self, modules: &[Module]) -> DevelopRun {
+        let mut run = DevelopRun::default();
+        let Some(engine) = self.engines.first() else { return run };
+        for (index, module) in modules.iter().enumerate() {
+            let started = Instant::now();
+            let status = match engine.scaffolder.develop(module) {
+                DevelopStatus::Failed(e) if matches!(e.root_cause(), Error::Cancelled) => {
+                    run.pending = modules[index..].to_vec();
+                    run.interrupted = Some(e);
+                    return run;
+                }
+                status => status,
+            };
+            let outcome = DevelopOutcome { module: module.clone(), status, elapsed: started.elapsed(), engine: None };
+            self.emit(&outcome);
+            let failed = matches!(outcome.status, DevelopStatus::Failed(_));
+            if let DevelopStatus::Failed(e) = &outcome.status {
+                self.logger.error(format!("Error: {}", e));
+            }
+            run.report.outcomes.push(outcome);
+            if failed {
+                run.pending = modules[index + 1..].to_vec();
+                break;
+            }
+        }
+        run
+    }
+
+    /// Why `module` needs developing with `engine`, or `None` when its inputs are unchanged
+    /// since its last successful develop with that engine.
+    fn stale_reason(&self, cache: &FingerprintCache, module: &Module, engine: &str) -> Option<&'static str> {
+        if self.force {
+            return Some("--force");
+        }
+        let Some(entry) = cache.get(&module.name) else {
+            return Some("no previous develop");
+        };
+        if entry.engine != engine {
+            return Some("last developed with another engine");
+        }
+        match fingerprint(&module.path) {
+            Ok(current) if current == entry.fingerprint => None,
+            _ => Some("inputs changed"),
+        }
+    }
+
+    /// List the files of `module` that its develop changed since `before`, and put them back
+    /// with `restore`.
+    fn check_drift(&self, module: &Module, before: &TreeSnapshot) -> Result<Vec<FileChange>> {
+        let log = &self.logger;
+        let changes = before.changes(&TreeSnapshot::take(&module.path)?);
+        if changes.is_empty() {
+            log.info(format!("Generated code of {} is up to date", module.name));
+            return Ok(changes);
+        }
+
+        log.error(format!("{}dagger develop changed {} files of {}:", log.emoji("❌ "), changes.len(), module.name));
+        for change in &changes {
+            log.error(format!("  {:<8}  {}", change.kind, change.path.display()));
+            let file = Path::new(&module.name).join(&change.path);
+            let message = format!("{} by dagger develop; run it and commit the result", change.kind);
+            self.github.error(&file.to_string_lossy(), "generated code is out of date", &message);
+        }
+        let files: Vec<String> = changes.iter().map(|change| format!("{} ({})", change.path.display(), change.kind)).collect();
+        self.github.error(
+            &format!("{}/dagger.json", module.name),
+            &format!("generated code of {} is out of date", module.name),
+            &format!("dagger develop changed {}", files.join(", ")),
+        );
+        if self.restore {
+            before.restore(&changes)?;
+        }
+        self.events.emit(Event::DevelopDrift { module: module.name.clone(), files: changes.clone(), restored: self.restore });
+        Ok(changes)
+    }
+
+    /// The event of a finished module; drift is reported by [`Developer::check_drift`].
+    fn emit(&self, outcome: &DevelopOutcome) {
+        let (module, engine, duration_ms) = (outcome.module.name.clone(), outcome.engine.clone(), millis(outcome.elapsed));
+        match &outcome.status {
+            DevelopStatus::Succeeded => self.events.emit(Event::DevelopSucceeded { module, engine, duration_ms }),
+            DevelopStatus::Failed(e) => self.events.emit(Event::DevelopFailed { module, engine, duration_ms, error: ErrorDetails::from(e) }),
+            DevelopStatus::Skipped => self.events.emit(Event::DevelopSkipped { module, engine, reason: "no_dagger_json" }),
+            DevelopStatus::Unchanged => self.events.emit(Event::DevelopSkipped { module, engine, reason: "unchanged" }),
+            DevelopStatus::Drifted(_) => {}
+        }
+    }
+}
+
+/// A plain text table of how every module fared with every engine, in the order of `labels`.
+pub fn engine_matrix(report: &DevelopReport, modules: &[Module], labels: &[&str]) -> String {
+    let width = modules.iter().map(|m| m.name.len()).chain(["module".len()]).max().unwrap_or_default();
+    let column = labels.iter().map(|label| label.len()).chain(["skipped".len()]).max().unwrap_or_default();
+
+    let mut rows = vec![format!("  {:<width$}", "module", width = width)];
+    for label in labels {
+        rows[0].push_str(&format!("  {:<column$}", label, column = column));
+    }
+    for module in modules {
+        let mut row = format!("  {:<width$}", module.name, width = width);
+        for label in labels {
+            let status = report
+                .outcomes
+                .iter()
+                .find(|o| o.module.name == module.name && o.engine.as_deref() == Some(label))
+                .map_or("-", |o| match o.status {
+                    DevelopStatus::Succeeded => "ok",
+                    DevelopStatus::Failed(_) | DevelopStatus::Drifted(_) => "FAILED",
+                    DevelopStatus::Skipped | DevelopStatus::Unchanged => "skipped",
+                });
+            row.push_str(&format!("  {:<column$}", status, column = column));
+        }
+        rows.push(row);
+    }
+    let rows: Vec<&str> = rows.iter().map(|row| row.trim_end()).collect();
+    format!("Module × engine matrix:\n{}", rows.join("\n"))
+}
@@ -0,0 +1,118 @@
+use std::collections::BTreeMap;
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use serde::Serialize;
+
+use crate::dagger_json;
+use crate::error::{IoResultExt, Result};
+use crate::repo::IGNORED_DIRS;
+
+/// How a file of a module differs after `dagger develop`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ChangeKind {
+    Added,
+    Modified,
+    Removed,
+}
+
+impl fmt::Display for ChangeKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.pad(match self {
+            ChangeKind::Added => "added",
+            ChangeKind::Modified => "modified",
+            ChangeKind::Removed => "removed",
+        })
+    }
+}
+
+/// A file that `dagger develop` changed, relative to the module directory.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct FileChange {
+    pub path: PathBuf,
+    pub kind: ChangeKind,
+}
+
+/// The content of every file of a module, so that what `dagger develop` did to it can be
+/// listed and undone. Child modules and [`IGNORED_DIRS`] are left out.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct TreeSnapshot {
+    dir: PathBuf,
+    files: BTreeMap<PathBuf, Vec<u8>>,
+}
+
+impl TreeSnapshot {
+    pub fn take(dir: &Path) -> Result<Self> {
+        let mut snapshot = Self { dir: dir.to_path_buf(), files: BTreeMap::new() };
+        snapshot.collect(dir)?;
+        Ok(snapshot)
+    }
+
+    fn collect(&mut self, dir: &Path) -> Result<()> {
+        for entry in fs::read_dir(dir).at_path(dir)? {
+            let entry = entry.at_path(dir)?;
+            let path = entry.path();
+            if entry.file_type().at_path(&path)?.is_dir() {
+                let ignored = IGNORED_DIRS.iter().any(|ignored| entry.file_name() == *ignored);
+                if !ignored && !dagger_json::path(&path).is_file() {
+                    self.collect(&path)?;
+                }
+            } else {
+                let relative = path.strip_prefix(&self.dir).unwrap_or(&path).to_path_buf();
+                self.files.insert(relative, fs::read(&path).at_path(&path)?);
+            }
+        }
+        Ok(())
+    }
+
+    /// The files that differ in `after`, sorted by path.
+    pub fn changes(&self, after: &TreeSnapshot) -> Vec<FileChange> {
+        let mut changes: Vec<FileChange> = after
+            .files
+            .iter()
+            .filter_map(|(path, content)| match self.files.get(path) {
+                None => Some(FileChange { path: path.clone(), kind: ChangeKind::Added }),
+                Some(before) if before != content => Some(FileChange { path: path.clone(), kind: ChangeKind::Modified }),
+                Some(_) => None,
+            })
+            .collect();
+        changes.extend(
+            self.files
+                .keys()
+                .filter(|path| !after.files.contains_key(*path))
+                .map(|path| FileChange { path: path.clone(), kind: ChangeKind::Removed }),
+        );
+        changes.sort_by(|a, b| a.path.cmp(&b.path));
+        changes
+    }
+
+    /// Put the files in `changes` back the way they were in this snapshot, removing added
+    /// files and the directories they leave empty.
+    pub fn restore(&self, changes: &[FileChange]) -> Result<()> {
+        for change in changes {
+            let path = self.dir.join(&change.path);
+            match (change.kind, self.files.get(&change.path)) {
+                (ChangeKind::Added, _) => {
+                    fs::remove_file(&path).at_path(&path)?;
+                    let mut parent = path.parent();
+                    while let Some(dir) = parent.filter(|dir| *dir != self.dir) {
+                        if fs::remove_dir(dir).is_err() {
+                            break;
+                        }
+                        parent = dir.parent();
+                    }
+                }
+                (_, Some(content)) => {
+                    if let Some(parent) = path.parent() {
+                        fs::create_dir_all(parent).at_path(parent)?;
+                    }
+                    fs::write(&path, content).at_path(&path)?;
+                }
+                (_, None) => {}
+            }
+        }
+        Ok(())
+    }
+}
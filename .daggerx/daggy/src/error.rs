@@ -0,0 +1,218 @@
+//! Error types of daggy.
+//!
+//! Every error maps to a stable process exit code so scripts can tell failures apart:
+//!
+//! | Code | Error class                                          |
+//! |------|------------------------------------------------------|
+//! | 1    | Unexpected I/O failure                               |
+//! | 2    | Invalid usage (unknown task, missing arguments)      |
+//! | 3    | Not inside a git repository                          |
+//! | 4    | Invalid module name, or the module already exists    |
+//! | 5    | A required tool (`dagger`, `go`, `git`) is missing   |
+//! | 6    | A template file or directory is missing              |
+//! | 7    | An external command exited unsuccessfully            |
+//! | 8    | One or more modules failed to develop                |
+//! | 9    | A dagger.json could not be read, parsed or written   |
+//! | 10   | An external command timed out                        |
+//! | 11   | A required tool has an unsupported version           |
+//! | 12   | `develop --check` found out of date generated code   |
+//! | 13   | A module cannot be released, e.g. its tag exists     |
+//! | 14   | One or more modules failed to publish                |
+//! | 130  | Interrupted by Ctrl-C (SIGINT) or SIGTERM            |
+
+use std::io;
+use std::path::PathBuf;
+use std::time::Duration;
+
+use serde_json::{json, Value};
+
+pub type Result<T, E = Error> = std::result::Result<T, E>;
+
+#[derive(Debug, thiserror::Error)]
+pub enum Error {
+    #[error("invalid usage: {0}")]
+    Usage(String),
+
+    #[error("not in a git repository: {0}")]
+    NotInGitRepo(PathBuf),
+
+    #[error("invalid module name {name:?}: {reason}")]
+    InvalidModuleName { name: String, reason: String },
+
+    #[error("module {module:?} already exists at {}", path.display())]
+    ModuleExists { module: String, path: PathBuf },
+
+    #[error("{tool} is not installed or not on PATH")]
+    ToolMissing { tool: String },
+
+    #[error("{tool}: {reason}")]
+    ToolVersion { tool: String, reason: String },
+
+    #[error("template not found: {}", path.display())]
+    TemplateMissing { path: PathBuf },
+
+    #[error("command `{command}` failed in {} with {} after {elapsed:.2?}{}", cwd.display(), exit_status(*code), stderr_block(stderr_tail))]
+    CommandFailed {
+        command: String,
+        cwd: PathBuf,
+        code: Option<i32>,
+        elapsed: Duration,
+        /// Last lines of stdout; not part of the message since they rarely explain a failure.
+        stdout_tail: Vec<String>,
+        stderr_tail: Vec<String>,
+    },
+
+    #[error("command `{command}` in {} timed out after {timeout:?}", cwd.display())]
+    CommandTimedOut { command: String, cwd: PathBuf, timeout: Duration },
+
+    #[error("interrupted")]
+    Cancelled,
+
+    #[error("{failed} of {total} modules failed to develop")]
+    DevelopFailed { failed: usize, total: usize },
+
+    #[error("generated code is out of date: dagger develop changed {files} files in {modules} modules")]
+    Drift { modules: usize, files: usize },
+
+    #[error("cannot release {module}: {reason}")]
+    Release { module: String, reason: String },
+
+    #[error("{failed} of {total} modules failed to publish")]
+    PublishFailed { failed: usize, total: usize },
+
+    #[error("{}: {reason}", path.display())]
+    DaggerJson { path: PathBuf, reason: String },
+
+    #[error("{}: {source}", path.display())]
+    Io { path: PathBuf, source: io::Error },
+
+    #[error("{context}: {source}")]
+    Context { context: String, source: Box<Error> },
+}
+
+fn exit_status(code: Option<i32>) -> String {
+    match code {
+        Some(code) => format!("exit code {}", code),
+        None => "no exit code (terminated by signal)".to_string(),
+    }
+}
+
+fn stderr_block(lines: &[String]) -> String {
+    if lines.is_empty() {
+        return String::new();
+    }
+    let mut block = String::from(", last lines of stderr:");
+    for line in lines {
+        block.push_str("\n    | ");
+        block.push_str(line);
+    }
+    block
+}
+
+impl Error {
+    /// Wrap an I/O error with the path it happened on.
+    pub fn io(path: impl Into<PathBuf>, source: io::Error) -> Self {
+        Error::Io { path: path.into(), source }
+    }
+
+    /// The error at the bottom of the context chain.
+    pub fn root_cause(&self) -> &Error {
+        match self {
+            Error::Context { source, .. } => source.root_cause(),
+            other => other,
+        }
+    }
+
+    /// The contexts wrapped around the root cause, outermost first.
+    pub fn contexts(&self) -> Vec<&str> {
+        let mut contexts = Vec::new();
+        let mut current = self;
+        while let Error::Context { context, source } = current {
+            contexts.push(context.as_str());
+            current = source;
+        }
+        contexts
+    }
+
+    /// Machine readable name of the error class.
+    pub fn code(&self) -> &'static str {
+        match self.root_cause() {
+            Error::Usage(_) => "usage",
+            Error::NotInGitRepo(_) => "not_in_git_repo",
+            Error::InvalidModuleName { .. } => "invalid_module_name",
+            Error::ModuleExists { .. } => "module_exists",
+            Error::ToolMissing { .. } => "tool_missing",
+            Error::ToolVersion { .. } => "tool_version",
+            Error::TemplateMissing { .. } => "template_missing",
+            Error::CommandFailed { .. } => "command_failed",
+            Error::CommandTimedOut { .. } => "command_timed_out",
+            Error::Cancelled => "cancelled",
+            Error::DevelopFailed { .. } => "develop_failed",
+            Error::Drift { .. } => "drift",
+            Error::Release { .. } => "release",
+            Error::PublishFailed { .. } => "publish_failed",
+            Error::DaggerJson { .. } => "dagger_json",
+            Error::Io { .. } => "io",
+            Error::Context { .. } => unreachable!("root_cause never returns a context"),
+        }
+    }
+
+    /// Process exit code of the error class, see the module documentation.
+    pub fn exit_code(&self) -> i32 {
+        match self.root_cause() {
+            Error::Io { .. } => 1,
+            Error::Usage(_) => 2,
+            Error::NotInGitRepo(_) => 3,
+            Error::InvalidModuleName { .. } | Error::ModuleExists { .. } => 4,
+            Error::ToolMissing { .. } => 5,
+            Error::TemplateMissing { .. } => 6,
+            Error::CommandFailed { .. } => 7,
+            Error::CommandTimedOut { .. } => 10,
+            Error::ToolVersion { .. } => 11,
+            Error::Cancelled => crate::cancel::CANCELLED_EXIT_CODE,
+            Error::DevelopFailed { .. } => 8,
+            Error::Drift { .. } => 12,
+            Error::Release { .. } => 13,
+            Error::PublishFailed { .. } => 14,
+            Error::DaggerJson { .. } => 9,
+            Error::Context { .. } => unreachable!("root_cause never returns a context"),
+        }
+    }
+
+    pub fn to_json(&self) -> Value {
+        json!({
+            "error": self.code(),
+            "exit_code": self.exit_code(),
+            "message": self.root_cause().to_string(),
+            "context": self.contexts(),
+        })
+    }
+}
+
+/// Attach context to errors as they bubble up.
+pub trait ResultExt<T> {
+    fn context(self, context: impl Into<String>) -> Result<T>;
+
+    fn with_context<C: Into<String>>(self, f: impl FnOnce() -> C) -> Result<T>;
+}
+
+impl<T, E: Into<Error>> ResultExt<T> for std::result::Result<T, E> {
+    fn context(self, context: impl Into<String>) -> Result<T> {
+        self.map_err(|e| Error::Context { context: context.into(), source: Box::new(e.into()) })
+    }
+
+    fn with_context<C: Into<String>>(self, f: impl FnOnce() -> C) -> Result<T> {
+        self.map_err(|e| Error::Context { context: f().into(), source: Box::new(e.into()) })
+    }
+}
+
+/// Attach the offending path to I/O errors.
+pub trait IoResultExt<T> {
+    fn at_path(self, path: impl Into<PathBuf>) -> Result<T>;
+}
+
+impl<T> IoResultExt<T> for io::Result<T> {
+    fn at_path(self, path: impl Into<PathBuf>) -> Result<T> {
+        self.map_err(|e| Error::io(path, e))
+    }
+}
@@ -0,0 +1,241 @@
+use std::fmt;
+use std::io::{self, Write};
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use serde::Serialize;
+
+use crate::changed::{Affected, Reason};
+use crate::command::{Cmd, CommandOutput, Interruption};
+use crate::drift::FileChange;
+use crate::error::Error;
+use crate::publish::PublishEntry;
+use crate::status::ModuleStatus;
+
+/// One step of a daggy run, written as a JSON object per line with `--output json`.
+///
+/// The `event` field names the variant in snake_case; every event also carries a
+/// `timestamp_ms` with the milliseconds since the Unix epoch. Fields are only ever added.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum Event {
+    ModuleDiscovered { module: String, path: PathBuf },
+    CommandStarted { command: String, cwd: PathBuf },
+    CommandFinished {
+        command: String,
+        cwd: PathBuf,
+        exit_code: Option<i32>,
+        duration_ms: u64,
+        /// `timed_out` or `cancelled` when daggy stopped the command.
+        #[serde(skip_serializing_if = "Option::is_none")]
+        interrupted: Option<&'static str>,
+    },
+    StepStarted { module: String, step: &'static str },
+    StepFinished { module: String, step: &'static str, duration_ms: u64 },
+    /// A step an earlier, resumed run already finished.
+    StepSkipped { module: String, step: &'static str },
+    FileWritten { path: PathBuf },
+    DevelopSucceeded {
+        module: String,
+        /// Version of the dagger engine, only set when developing against several.
+        #[serde(skip_serializing_if = "Option::is_none")]
+        engine: Option<String>,
+        duration_ms: u64,
+    },
+    DevelopFailed {
+        module: String,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        engine: Option<String>,
+        duration_ms: u64,
+        error: ErrorDetails,
+    },
+    DevelopSkipped {
+        module: String,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        engine: Option<String>,
+        /// `no_dagger_json`, or `unchanged` since the last successful develop.
+        reason: &'static str,
+    },
+    /// `develop --check` found files that `dagger develop` changed.
+    DevelopDrift {
+        module: String,
+        files: Vec<FileChange>,
+        /// Whether the files were put back the way they were.
+        restored: bool,
+    },
+    /// `changed` found a module affected by the changes.
+    ModuleAffected {
+        module: String,
+        /// `changed`, or `depends_on` the changed module `dependency`.
+        reason: &'static str,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        dependency: Option<String>,
+    },
+    /// `bump` tagged the next version of a module.
+    TagCreated {
+        module: String,
+        tag: String,
+        version: String,
+        /// The version it follows, `None` for a first release.
+        #[serde(skip_serializing_if = "Option::is_none")]
+        previous: Option<String>,
+        /// Whether the tag was pushed to the remote.
+        pushed: bool,
+    },
+    /// `changelog` rendered the release notes of a tag.
+    ReleaseNotes {
+        module: String,
+        tag: String,
+        version: String,
+        /// The notes in Markdown, without the version heading.
+        notes: String,
+    },
+    /// `publish` planned to publish a module version.
+    PublishPlanned(PublishEntry),
+    /// `publish` skipped a module that was never released.
+    PublishSkipped { module: String },
+    /// `dagger publish` published a module version.
+    ModulePublished {
+        module: String,
+        version: String,
+        /// The published reference, `<address>/<module>@<version>`.
+        reference: String,
+        duration_ms: u64,
+    },
+    /// `dagger publish` failed to publish a module version.
+    PublishFailed { module: String, version: String, duration_ms: u64, error: ErrorDetails },
+    /// `status` read the release state of a module.
+    ReleaseStatus(ModuleStatus),
+    /// The error that aborted the task.
+    Error(ErrorDetails),
+    /// Always the last event of a run.
+    Summary {
+        task: String,
+        status: RunStatus,
+        total: usize,
+        succeeded: usize,
+        failed: usize,
+        skipped: usize,
+        duration_ms: u64,
+    },
+}
+
+impl Event {
+    pub fn command_started(cmd: &Cmd) -> Self {
+        Event::CommandStarted { command: cmd.to_string(), cwd: cmd.cwd.clone() }
+    }
+
+    pub fn command_finished(cmd: &Cmd, output: &CommandOutput) -> Self {
+        Event::CommandFinished {
+            command: cmd.to_string(),
+            cwd: cmd.cwd.clone(),
+            exit_code: output.code,
+            duration_ms: millis(output.elapsed),
+            interrupted: output.interrupted.map(|interruption| match interruption {
+                Interruption::TimedOut(_) => "timed_out",
+                Interruption::Cancelled => "cancelled",
+            }),
+        }
+    }
+
+    pub fn module_affected(affected: &Affected) -> Self {
+        let (reason, dependency) = match &affected.reason {
+            Reason::Changed => ("changed", None),
+            Reason::DependsOn(module) => ("depends_on", Some(module.clone())),
+        };
+        Event::ModuleAffected { module: affected.module.clone(), reason, dependency }
+    }
+}
+
+/// The machine readable form of an [`Error`], as printed by `--error-format json`.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ErrorDetails {
+    pub error: &'static str,
+    pub exit_code: i32,
+    pub message: String,
+    pub context: Vec<String>,
+}
+
+impl From<&Error> for ErrorDetails {
+    fn from(e: &Error) -> Self {
+        Self {
+            error: e.code(),
+            exit_code: e.exit_code(),
+            message: e.root_cause().to_string(),
+            context: e.contexts().into_iter().map(String::from).collect(),
+        }
+    }
+}
+
+/// How a task ended.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RunStatus {
+    Succeeded,
+    Failed,
+    Interrupted,
+}
+
+impl RunStatus {
+    pub fn of(e: &Error) -> Self {
+        if matches!(e.root_cause(), Error::Cancelled) {
+            RunStatus::Interrupted
+        } else {
+            RunStatus::Failed
+        }
+    }
+}
+
+#[derive(Serialize)]
+struct Record<'a> {
+    #[serde(flatten)]
+    event: &'a Event,
+    timestamp_ms: u64,
+}
+
+/// Where events go. The default sink drops them, so emitting is free unless asked for.
+#[derive(Clone, Default)]
+pub struct EventSink {
+    out: Option<Arc<Mutex<Box<dyn Write + Send>>>>,
+}
+
+impl fmt::Debug for EventSink {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("EventSink").field("enabled", &self.is_enabled()).finish()
+    }
+}
+
+impl EventSink {
+    /// A sink that drops every event.
+    pub fn disabled() -> Self {
+        Self::default()
+    }
+
+    /// Newline delimited JSON on stdout.
+    pub fn stdout() -> Self {
+        Self::to_writer(io::stdout())
+    }
+
+    /// Newline delimited JSON written to `out`, flushed after every event.
+    pub fn to_writer(out: impl Write + Send + 'static) -> Self {
+        Self { out: Some(Arc::new(Mutex::new(Box::new(out)))) }
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.out.is_some()
+    }
+
+    pub fn emit(&self, event: Event) {
+        let Some(out) = &self.out else { return };
+        let timestamp_ms = SystemTime::now().duration_since(UNIX_EPOCH).map(millis).unwrap_or_default();
+        let line = serde_json::to_string(&Record { event: &event, timestamp_ms }).expect("events serialize to JSON");
+        let mut out = out.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+        let _ = writeln!(out, "{}", line).and_then(|_| out.flush());
+    }
+}
+
+/// Whole milliseconds in `duration`, as used by every `*_ms` field.
+pub fn millis(duration: Duration) -> u64 {
+    duration.as_millis().try_into().unwrap_or(u64::MAX)
+}
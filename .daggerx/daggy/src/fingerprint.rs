@@ -0,0 +1,200 @@
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+use crate::dagger_json;
+use crate::error::{Error, IoResultExt, Result};
+use crate::repo::{Repo, IGNORED_DIRS};
+use crate::scaffold::GENERATED_PATHS;
+
+/// Where the fingerprints of the last successful develop of every module are kept.
+pub const CACHE_FILE: &str = ".daggerx/cache/develop.json";
+
+/// Files of a module's source directory that `dagger develop` depends on besides its Go
+/// sources.
+const MANIFESTS: [&str; 2] = ["go.mod", "go.sum"];
+
+/// A 64-bit FNV-1a hash; simple and stable across Rust releases, unlike `DefaultHasher`.
+struct Fnv(u64);
+
+impl Fnv {
+    fn new() -> Self {
+        Self(0xcbf29ce484222325)
+    }
+
+    fn write(&mut self, bytes: &[u8]) {
+        for byte in bytes {
+            self.0 ^= u64::from(*byte);
+            self.0 = self.0.wrapping_mul(0x100000001b3);
+        }
+    }
+
+    /// Write `bytes` prefixed by their length, so consecutive fields cannot run together.
+    fn write_field(&mut self, bytes: &[u8]) {
+        self.write(&(bytes.len() as u64).to_le_bytes());
+        self.write(bytes);
+    }
+
+    fn finish(&self) -> String {
+        format!("{:016x}", self.0)
+    }
+}
+
+/// The fingerprint of the inputs of `dagger develop` in `module_dir`: its dagger.json, the
+/// go.mod, go.sum and Go sources of the `source` directory it names, except child modules,
+/// and the fingerprints of the local modules it depends on. The generated code counts too, so
+/// a module whose generated code was deleted or edited by hand is developed again.
+pub fn fingerprint(module_dir: &Path) -> Result<String> {
+    fingerprint_of(module_dir, &mut BTreeSet::new())
+}
+
+fn fingerprint_of(module_dir: &Path, visiting: &mut BTreeSet<PathBuf>) -> Result<String> {
+    let module_dir = module_dir.canonicalize().at_path(module_dir)?;
+    let mut hash = Fnv::new();
+
+    let source_dir = dagger_json::source_dir(&module_dir)?;
+    let mut files = vec![dagger_json::path(&module_dir)];
+    for manifest in MANIFESTS {
+        let path = source_dir.join(manifest);
+        if path.is_file() {
+            files.push(path);
+        }
+    }
+    if source_dir.is_dir() {
+        collect_sources(&source_dir, &source_dir, &mut files)?;
+    }
+    for generated in GENERATED_PATHS {
+        let path = source_dir.join(generated.trim_start_matches('/'));
+        if path.is_dir() {
+            collect_files(&path, &mut files)?;
+        } else if path.is_file() {
+            files.push(path);
+        }
+    }
+    files.sort();
+    for path in &files {
+        let relative = path.strip_prefix(&module_dir).unwrap_or(path);
+        hash.write_field(relative.to_string_lossy().as_bytes());
+        hash.write_field(&fs::read(path).at_path(path)?);
+    }
+
+    // A dependency cycle is dagger's to report; the fingerprint simply stops following it.
+    visiting.insert(module_dir.clone());
+    for (name, dir) in local_dependencies(&module_dir)? {
+        if visiting.contains(&dir) {
+            continue;
+        }
+        hash.write_field(name.as_bytes());
+        hash.write_field(fingerprint_of(&dir, visiting)?.as_bytes());
+    }
+    visiting.remove(&module_dir);
+
+    Ok(hash.finish())
+}
+
+/// Go sources below `dir`, skipping ignored directories, child modules and generated code,
+/// which is collected on its own. Generated paths are relative to `source_dir`, where dagger
+/// writes them.
+fn collect_sources(source_dir: &Path, dir: &Path, files: &mut Vec<PathBuf>) -> Result<()> {
+    for entry in fs::read_dir(dir).at_path(dir)? {
+        let entry = entry.at_path(dir)?;
+        let path = entry.path();
+        let relative = path.strip_prefix(source_dir).unwrap_or(&path);
+        if GENERATED_PATHS.iter().any(|generated| relative == Path::new(generated.trim_start_matches('/'))) {
+            continue;
+        }
+        if entry.file_type().at_path(&path)?.is_dir() {
+            let ignored = IGNORED_DIRS.iter().any(|ignored| entry.file_name() == *ignored);
+            if !ignored && !dagger_json::path(&path).is_file() {
+                collect_sources(source_dir, &path, files)?;
+            }
+        } else if path.extension().is_some_and(|ext| ext == "go") {
+            files.push(path);
+        }
+    }
+    Ok(())
+}
+
+/// Every file below `dir`.
+fn collect_files(dir: &Path, files: &mut Vec<PathBuf>) -> Result<()> {
+    for entry in fs::read_dir(dir).at_path(dir)? {
+        let path = entry.at_path(dir)?.path();
+        if path.is_dir() {
+            collect_files(&path, files)?;
+        } else {
+            files.push(path);
+        }
+    }
+    Ok(())
+}
+
+/// The `dependencies` of the dagger.json in `module_dir` that are modules on disk, as
+/// `(name, directory)`. Remote dependencies such as `github.com/...@v1` are left out.
+pub fn local_dependencies(module_dir: &Path) -> Result<Vec<(String, PathBuf)>> {
+    let json = dagger_json::read(module_dir)?;
+    let Some(dependencies) = json.get("dependencies").and_then(|deps| deps.as_array()) else {
+        return Ok(Vec::new());
+    };
+
+    let mut local = Vec::new();
+    for dependency in dependencies {
+        let (Some(name), Some(source)) = (dependency["name"].as_str(), dependency["source"].as_str()) else {
+            continue;
+        };
+        let dir = module_dir.join(source);
+        if dagger_json::path(&dir).is_file() {
+            local.push((name.to_string(), dir.canonicalize().at_path(&dir)?));
+        }
+    }
+    Ok(local)
+}
+
+/// What the last successful develop of a module saw.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CacheEntry {
+    pub fingerprint: String,
+    /// The dagger engine it was developed with.
+    pub engine: String,
+}
+
+/// The fingerprints of the last successful develop of every module, in [`CACHE_FILE`].
+#[derive(Debug, Clone, Default)]
+pub struct FingerprintCache {
+    path: PathBuf,
+    entries: BTreeMap<String, CacheEntry>,
+}
+
+impl FingerprintCache {
+    /// Load the cache of `repo`; a missing cache is empty.
+    pub fn load(repo: &Repo) -> Result<Self> {
+        let path = repo.root().join(CACHE_FILE);
+        let entries = match fs::read_to_string(&path) {
+            // The cache only saves time, so a damaged one is as good as none.
+            Ok(content) => serde_json::from_str(&content).unwrap_or_default(),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => BTreeMap::new(),
+            Err(e) => return Err(Error::io(&path, e)),
+        };
+        Ok(Self { path, entries })
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    pub fn get(&self, module: &str) -> Option<&CacheEntry> {
+        self.entries.get(module)
+    }
+
+    /// Remember a successful develop of `module` and save the cache.
+    pub fn record(&mut self, module: &str, entry: CacheEntry) -> Result<()> {
+        self.entries.insert(module.to_string(), entry);
+        if let Some(parent) = self.path.parent() {
+            fs::create_dir_all(parent).at_path(parent)?;
+        }
+        let content = serde_json::to_string_pretty(&self.entries).expect("fingerprints serialize to JSON");
+        fs::write(&self.path, content + "\n").at_path(&self.path)
+    }
+}
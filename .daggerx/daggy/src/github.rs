@@ -0,0 +1,137 @@
+use std::env;
+use std::fs::OpenOptions;
+use std::io::{self, Write};
+use std::path::PathBuf;
+use std::time::Duration;
+
+use crate::error::{IoResultExt, Result};
+use crate::repo::Module;
+use crate::scaffold::{DevelopReport, DevelopStatus};
+
+/// Workflow commands and the job summary of GitHub Actions.
+///
+/// Detected from `GITHUB_ACTIONS=true`; when disabled every method is a no-op. Workflow
+/// commands go to stderr so they stay in order with daggy's progress and command output,
+/// which also keeps stdout free for `--output json`.
+#[derive(Debug, Clone, Default)]
+pub struct GithubActions {
+    enabled: bool,
+    step_summary: Option<PathBuf>,
+}
+
+impl GithubActions {
+    pub fn disabled() -> Self {
+        Self::default()
+    }
+
+    /// Enabled when `GITHUB_ACTIONS=true`; the job summary goes to `$GITHUB_STEP_SUMMARY`.
+    pub fn from_env() -> Self {
+        let enabled = env::var("GITHUB_ACTIONS").is_ok_and(|v| v == "true");
+        let step_summary = env::var_os("GITHUB_STEP_SUMMARY").filter(|v| !v.is_empty()).map(PathBuf::from);
+        Self { enabled, step_summary: step_summary.filter(|_| enabled) }
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.enabled
+    }
+
+    /// Start a collapsible group in the log.
+    pub fn group(&self, title: &str) {
+        self.command(&format!("::group::{}", escape_data(title)));
+    }
+
+    pub fn end_group(&self) {
+        self.command("::endgroup::");
+    }
+
+    /// Annotate `file` (relative to the repository root) with an error.
+    pub fn error(&self, file: &str, title: &str, message: &str) {
+        self.command(&format!(
+            "::error file={},title={}::{}",
+            escape_property(file),
+            escape_property(title),
+            escape_data(message)
+        ));
+    }
+
+    /// Append `markdown` to the job summary, if the runner provides one.
+    pub fn append_summary(&self, markdown: &str) -> Result<()> {
+        let Some(path) = &self.step_summary else { return Ok(()) };
+        let mut file = OpenOptions::new().create(true).append(true).open(path).at_path(path)?;
+        file.write_all(markdown.as_bytes()).at_path(path)
+    }
+
+    fn command(&self, line: &str) {
+        if self.enabled {
+            let _ = writeln!(io::stderr().lock(), "{}", line);
+        }
+    }
+}
+
+fn escape_data(value: &str) -> String {
+    value.replace('%', "%25").replace('\r', "%0D").replace('\n', "%0A")
+}
+
+fn escape_property(value: &str) -> String {
+    escape_data(value).replace(':', "%3A").replace(',', "%2C")
+}
+
+/// A Markdown table with one row per developed module; `pending` are the modules an
+/// interruption kept from running.
+pub fn develop_summary(report: &DevelopReport, pending: &[Module]) -> String {
+    let matrix = report.outcomes.iter().any(|outcome| outcome.engine.is_some());
+    let mut markdown = String::from("### daggy develop\n\n");
+    if matrix {
+        markdown.push_str("| Module | Engine | Result | Duration | Details |\n| --- | --- | --- | --- | --- |\n");
+    } else {
+        markdown.push_str("| Module | Result | Duration | Details |\n| --- | --- | --- | --- |\n");
+    }
+    for outcome in &report.outcomes {
+        let (result, details) = match &outcome.status {
+            DevelopStatus::Succeeded => ("✅ succeeded", String::new()),
+            DevelopStatus::Failed(e) => ("❌ failed", escape_cell(&e.root_cause().to_string())),
+            DevelopStatus::Skipped => ("⏭️ skipped", "no dagger.json".to_string()),
+            DevelopStatus::Unchanged => ("⏭️ skipped", "unchanged since the last successful develop".to_string()),
+            DevelopStatus::Drifted(changes) => {
+                let files: Vec<String> = changes.iter().map(|change| format!("`{}` {}", change.path.display(), change.kind)).collect();
+                ("❌ out of date", escape_cell(&format!("develop changed {}", files.join(", "))))
+            }
+        };
+        let engine = outcome.engine.as_ref().map(|engine| format!(" {} |", engine)).unwrap_or_default();
+        markdown.push_str(&format!(
+            "| `{}` |{} {} | {} | {} |\n",
+            outcome.module.name,
+            engine,
+            result,
+            format_duration(outcome.elapsed),
+            details
+        ));
+    }
+    for module in pending {
+        let engine = if matrix { " - |" } else { "" };
+        markdown.push_str(&format!("| `{}` |{} ⛔ not run | - | interrupted |\n", module.name, engine));
+    }
+
+    markdown.push_str(&format!(
+        "\n**{} succeeded, {} failed, {} skipped** of {} {}.\n\n",
+        report.succeeded(),
+        report.failed(),
+        report.skipped(),
+        report.outcomes.len() + pending.len(),
+        if matrix { "module and engine combinations" } else { "modules" }
+    ));
+    markdown
+}
+
+/// Keep a message on one table row: only its first line, with pipes escaped.
+fn escape_cell(message: &str) -> String {
+    message.lines().next().unwrap_or_default().replace('|', "\\|")
+}
+
+fn format_duration(duration: Duration) -> String {
+    if duration < Duration::from_secs(1) {
+        format!("{} ms", duration.as_millis())
+    } else {
+        format!("{:.1} s", duration.as_secs_f64())
+    }
+}
@@ -0,0 +1,195 @@
+use std::fs;
+use std::path::Path;
+use std::time::Duration;
+
+use crate::drift::FileChange;
+use crate::error::{Error, IoResultExt, Result};
+use crate::repo::Module;
+use crate::scaffold::{DevelopReport, DevelopStatus};
+
+/// How a single test case ended.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CaseOutcome {
+    Passed,
+    Failed {
+        /// The error code, e.g. `command_failed`.
+        kind: String,
+        message: String,
+        /// Full error text including its context chain.
+        details: String,
+        stdout_tail: Vec<String>,
+        stderr_tail: Vec<String>,
+    },
+    Skipped { message: String },
+}
+
+impl CaseOutcome {
+    pub fn failed(e: &Error) -> Self {
+        let (stdout_tail, stderr_tail) = match e.root_cause() {
+            Error::CommandFailed { stdout_tail, stderr_tail, .. } => (stdout_tail.clone(), stderr_tail.clone()),
+            _ => Default::default(),
+        };
+        let mut details = e.root_cause().to_string();
+        for context in e.contexts() {
+            details.push_str(&format!("\n  while: {}", context));
+        }
+        CaseOutcome::Failed {
+            kind: e.code().to_string(),
+            message: e.root_cause().to_string().lines().next().unwrap_or_default().to_string(),
+            details,
+            stdout_tail,
+            stderr_tail,
+        }
+    }
+
+    /// The failure of a module whose generated code `dagger develop` changed under `--check`.
+    pub fn drifted(changes: &[FileChange]) -> Self {
+        let details: Vec<String> = changes.iter().map(|change| format!("{:<8}  {}", change.kind, change.path.display())).collect();
+        CaseOutcome::Failed {
+            kind: "drift".to_string(),
+            message: format!("dagger develop changed {} files, the generated code is out of date", changes.len()),
+            details: details.join("\n"),
+            stdout_tail: Vec::new(),
+            stderr_tail: Vec::new(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TestCase {
+    pub name: String,
+    pub classname: String,
+    pub elapsed: Duration,
+    pub outcome: CaseOutcome,
+}
+
+/// A JUnit test suite, one test case per module.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct TestSuite {
+    pub name: String,
+    pub cases: Vec<TestCase>,
+}
+
+impl TestSuite {
+    /// The suite of a `develop` run; `pending` modules were never reached and count as skipped.
+    pub fn from_develop(report: &DevelopReport, pending: &[Module]) -> Self {
+        let classname = "daggy.develop".to_string();
+        let mut cases: Vec<TestCase> = report
+            .outcomes
+            .iter()
+            .map(|outcome| TestCase {
+                name: match &outcome.engine {
+                    Some(engine) => format!("{} [dagger {}]", outcome.module.name, engine),
+                    None => outcome.module.name.clone(),
+                },
+                classname: classname.clone(),
+                elapsed: outcome.elapsed,
+                outcome: match &outcome.status {
+                    DevelopStatus::Succeeded => CaseOutcome::Passed,
+                    DevelopStatus::Failed(e) => CaseOutcome::failed(e),
+                    DevelopStatus::Skipped => CaseOutcome::Skipped { message: "no dagger.json found".to_string() },
+                    DevelopStatus::Unchanged => {
+                        CaseOutcome::Skipped { message: "unchanged since the last successful develop".to_string() }
+                    }
+                    DevelopStatus::Drifted(changes) => CaseOutcome::drifted(changes),
+                },
+            })
+            .collect();
+        cases.extend(pending.iter().map(|module| TestCase {
+            name: module.name.clone(),
+            classname: classname.clone(),
+            elapsed: Duration::ZERO,
+            outcome: CaseOutcome::Skipped { message: "interrupted before it ran".to_string() },
+        }));
+        Self { name: "daggy develop".to_string(), cases }
+    }
+
+    pub fn failures(&self) -> usize {
+        self.cases.iter().filter(|case| matches!(case.outcome, CaseOutcome::Failed { .. })).count()
+    }
+
+    pub fn skipped(&self) -> usize {
+        self.cases.iter().filter(|case| matches!(case.outcome, CaseOutcome::Skipped { .. })).count()
+    }
+
+    pub fn elapsed(&self) -> Duration {
+        self.cases.iter().map(|case| case.elapsed).sum()
+    }
+
+    pub fn to_xml(&self) -> String {
+        let counts = format!(
+            "tests=\"{}\" failures=\"{}\" errors=\"0\" skipped=\"{}\" time=\"{}\"",
+            self.cases.len(),
+            self.failures(),
+            self.skipped(),
+            seconds(self.elapsed())
+        );
+        let mut xml = String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+        xml.push_str(&format!("<testsuites name=\"daggy\" {}>\n", counts));
+        xml.push_str(&format!("  <testsuite name=\"{}\" {}>\n", escape(&self.name), counts));
+        for case in &self.cases {
+            let open = format!(
+                "    <testcase name=\"{}\" classname=\"{}\" time=\"{}\"",
+                escape(&case.name),
+                escape(&case.classname),
+                seconds(case.elapsed)
+            );
+            match &case.outcome {
+                CaseOutcome::Passed => xml.push_str(&format!("{}/>\n", open)),
+                CaseOutcome::Skipped { message } => {
+                    xml.push_str(&format!("{}>\n      <skipped message=\"{}\"/>\n    </testcase>\n", open, escape(message)));
+                }
+                CaseOutcome::Failed { kind, message, details, stdout_tail, stderr_tail } => {
+                    xml.push_str(&format!("{}>\n", open));
+                    xml.push_str(&format!(
+                        "      <failure type=\"{}\" message=\"{}\">{}</failure>\n",
+                        escape(kind),
+                        escape(message),
+                        escape(details)
+                    ));
+                    if !stdout_tail.is_empty() {
+                        xml.push_str(&format!("      <system-out>{}</system-out>\n", escape(&stdout_tail.join("\n"))));
+                    }
+                    if !stderr_tail.is_empty() {
+                        xml.push_str(&format!("      <system-err>{}</system-err>\n", escape(&stderr_tail.join("\n"))));
+                    }
+                    xml.push_str("    </testcase>\n");
+                }
+            }
+        }
+        xml.push_str("  </testsuite>\n</testsuites>\n");
+        xml
+    }
+
+    /// Write the report to `path`, creating missing parent directories.
+    pub fn write(&self, path: &Path) -> Result<()> {
+        if let Some(parent) = path.parent().filter(|parent| !parent.as_os_str().is_empty()) {
+            fs::create_dir_all(parent).at_path(parent)?;
+        }
+        fs::write(path, self.to_xml()).at_path(path)
+    }
+}
+
+fn seconds(duration: Duration) -> String {
+    format!("{:.3}", duration.as_secs_f64())
+}
+
+/// Escape text for XML attributes and content, dropping characters XML 1.0 cannot carry
+/// such as the ANSI escapes of colored tool output.
+fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&apos;"),
+            '\n' | '\t' => escaped.push(c),
+            '\r' => escaped.push_str("&#13;"),
+            c if (c as u32) < 0x20 => {}
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
@@ -0,0 +1,42 @@
+//! Daggy scaffolds and maintains the dagger modules of this repository.
+//!
+//! The `daggy` binary is a thin CLI over this library; other tooling can use
+//! [`Repo`] for module discovery, [`TemplateSet`] for template rendering,
+//! [`dagger_json`] for editing module manifests, [`Scaffolder`] for
+//! creating and developing modules, [`develop::Developer`] for developing
+//! many of them at once, and [`bump::Bumper`], [`publish::Publisher`] and
+//! [`status::StatusReport`] for releasing them. Each returns a structured
+//! report for the caller to render.
+
+pub mod bump;
+pub mod cancel;
+pub mod changed;
+pub mod changelog;
+pub mod checkpoint;
+pub mod command;
+pub mod dagger_json;
+pub mod develop;
+pub mod drift;
+pub mod error;
+pub mod event;
+pub mod fingerprint;
+pub mod github;
+pub mod junit;
+pub mod log;
+pub mod preflight;
+pub mod publish;
+pub mod release;
+pub mod repo;
+pub mod retry;
+pub mod scaffold;
+pub mod select;
+pub mod status;
+pub mod template;
+pub mod toolchain;
+pub mod version;
+pub mod watch;
+
+pub use error::{Error, Result};
+pub use repo::{Module, Repo};
+pub use scaffold::{Components, CreateReport, CreateStep, DevelopOutcome, DevelopReport, DevelopStatus, NewModule, Scaffolder, StepReport};
+pub use template::TemplateSet;
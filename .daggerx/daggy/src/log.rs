@@ -0,0 +1,138 @@
+use std::env;
+use std::io::{self, IsTerminal, Write};
+
+/// How much daggy tells about what it is doing.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Verbosity {
+    /// Nothing at all, for library callers that report progress themselves.
+    Silent,
+    /// Only the final summary and errors.
+    Quiet,
+    #[default]
+    Normal,
+    /// Additionally echo every command and its working directory.
+    Verbose,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Level {
+    Debug,
+    Info,
+    Success,
+    Warn,
+    Error,
+    Summary,
+}
+
+impl Level {
+    fn color(self) -> &'static str {
+        match self {
+            Level::Debug => "\x1b[2m",
+            Level::Info | Level::Summary => "",
+            Level::Success => "\x1b[32m",
+            Level::Warn => "\x1b[33m",
+            Level::Error => "\x1b[31m",
+        }
+    }
+}
+
+/// Writes human readable progress to stderr, keeping stdout free for machine output.
+///
+/// Emoji and color are only used when stderr is a terminal and `NO_COLOR` is unset. The
+/// default logger is silent; the CLI picks the verbosity it wants with [`Logger::from_env`].
+#[derive(Debug, Clone, Copy)]
+pub struct Logger {
+    verbosity: Verbosity,
+    color: bool,
+    emoji: bool,
+}
+
+impl Logger {
+    pub fn new(verbosity: Verbosity, color: bool, emoji: bool) -> Self {
+        Self { verbosity, color, emoji }
+    }
+
+    /// A logger that writes nothing.
+    pub fn silent() -> Self {
+        Self::new(Verbosity::Silent, false, false)
+    }
+
+    /// Detect color and emoji support from the environment; `no_color` forces both off.
+    pub fn from_env(verbosity: Verbosity, no_color: bool) -> Self {
+        let fancy = !no_color && env::var_os("NO_COLOR").is_none_or(|v| v.is_empty()) && io::stderr().is_terminal();
+        Self::new(verbosity, fancy, fancy)
+    }
+
+    /// `decoration` when emoji are on, otherwise nothing. Callers pass their emoji through
+    /// this rather than writing them into the message, so that turning emoji off never
+    /// touches the rest of it, such as the output of a failed command. Include the space
+    /// separating the emoji from the text, as in `log.emoji("✅ ")`, so no gap is left behind.
+    pub fn emoji<'a>(&self, decoration: &'a str) -> &'a str {
+        if self.emoji {
+            decoration
+        } else {
+            ""
+        }
+    }
+
+    pub fn verbosity(&self) -> Verbosity {
+        self.verbosity
+    }
+
+    /// Whether output of external commands should be streamed to the terminal.
+    pub fn shows_command_output(&self) -> bool {
+        self.verbosity > Verbosity::Quiet
+    }
+
+    /// Details only shown with `--verbose`.
+    pub fn debug(&self, message: impl AsRef<str>) {
+        if self.verbosity >= Verbosity::Verbose {
+            self.write(Level::Debug, message.as_ref());
+        }
+    }
+
+    /// Progress, hidden with `--quiet`.
+    pub fn info(&self, message: impl AsRef<str>) {
+        if self.verbosity >= Verbosity::Normal {
+            self.write(Level::Info, message.as_ref());
+        }
+    }
+
+    /// Progress that went well, hidden with `--quiet`.
+    pub fn success(&self, message: impl AsRef<str>) {
+        if self.verbosity >= Verbosity::Normal {
+            self.write(Level::Success, message.as_ref());
+        }
+    }
+
+    pub fn warn(&self, message: impl AsRef<str>) {
+        self.write(Level::Warn, message.as_ref());
+    }
+
+    pub fn error(&self, message: impl AsRef<str>) {
+        self.write(Level::Error, message.as_ref());
+    }
+
+    /// Final results, always shown.
+    pub fn summary(&self, message: impl AsRef<str>) {
+        self.write(Level::Summary, message.as_ref());
+    }
+
+    fn write(&self, level: Level, message: &str) {
+        if self.verbosity == Verbosity::Silent {
+            return;
+        }
+        let mut stderr = io::stderr().lock();
+        let _ = if self.color && !level.color().is_empty() {
+            writeln!(stderr, "{}{}\x1b[0m", level.color(), message)
+        } else {
+            writeln!(stderr, "{}", message)
+        };
+    }
+}
+
+impl Default for Logger {
+    fn default() -> Self {
+        Self::silent()
+    }
+}
@@ -1,658 +1,1088 @@
-use std::env;
-use std::fs::{self};
-use std::io::{Write, Error, ErrorKind};
-use std::path::Path;
-use std::process::{Command, Output, Stdio};
-use clap::Parser;
-use serde::Deserialize;
-use regex::Regex;
-use serde_json::{json, Value};
+use std::cell::Cell;
+use std::path::PathBuf;
+use std::process::ExitCode;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use clap::{Parser, ValueEnum};
+use daggy::cancel::Cancellation;
+use daggy::changed::{affected_modules, parent_of, Affected};
+use daggy::changelog::{ChangelogUpdate, ReleaseEntry, CHANGELOG_FILE};
+use daggy::checkpoint::CreateState;
+use daggy::command::{CommandRunner, SystemRunner};
+use daggy::dagger_json;
+use daggy::develop::{engine_matrix, Developer, Engine};
+use daggy::event::{millis, ErrorDetails, Event, EventSink, RunStatus};
+use daggy::log::{Logger, Verbosity};
+use daggy::preflight::{newest_engine_version, InstalledTool, Preflight, DEFAULT_MIN_DAGGER_VERSION};
+use daggy::publish::{PublishEntry, PublishPlan, Publisher, DEFAULT_ADDRESS};
+use daggy::toolchain::{Tool, Toolchain, ToolchainConfig};
+use daggy::version::Version;
+use daggy::watch::Watcher;
+use daggy::bump::Bumper;
+use daggy::release::{Bump, ReleaseConfig, Releases};
+use daggy::retry::{RetryPolicy, RetryingRunner};
+use daggy::github::{self, GithubActions};
+use daggy::junit::TestSuite;
+use daggy::error::{IoResultExt, ResultExt};
+use daggy::scaffold::{DEFAULT_ENGINE_VERSION, DEFAULT_GO_VERSION};
+use daggy::select::{ModuleKind, Selection};
+use daggy::status::{table, StatusReport};
+use daggy::{Components, DevelopReport, DevelopStatus, Error, Module, Repo, Result, Scaffolder};
+
+const EXIT_CODES_HELP: &str = "\
+Exit codes:
+  0  success
+  1  unexpected I/O failure
+  2  invalid usage
+  3  not inside a git repository
+  4  invalid module name, or the module already exists
+  5  a required tool (dagger, go, git) is missing
+  6  a template file or directory is missing
+  7  an external command exited unsuccessfully
+  8  one or more modules failed to develop
+  9  a dagger.json could not be read, parsed or written
+ 10  an external command timed out
+ 11  a required tool has an unsupported version
+ 12  develop --check found out of date generated code
+ 13  a module cannot be released, e.g. its tag already exists
+ 14  one or more modules failed to publish
+130  interrupted by Ctrl-C (SIGINT) or SIGTERM";
 
 #[derive(Parser, Debug)]
-#[command(version, about, long_about = None)]
+#[command(version, about, long_about = None, after_help = EXIT_CODES_HELP)]
 struct Args {
     /// Task is the name of the task to run
     #[arg(short = 't', long = "task")]
     task: String,
 
-    /// Module is the name of the dagger module to generate.
+    /// Module is the name of the dagger module to generate. With `develop`, repeat it to work
+    /// on these modules only, together with the modules nested in them.
     #[arg(short = 'm', long = "module")]
-    module: Option<String>,
+    modules: Vec<String>,
+
+    /// Leave out modules whose name matches this glob (`*` stays within a directory, `**`
+    /// does not), e.g. `--exclude '*/examples/**'`. Repeatable.
+    #[arg(long = "exclude", value_name = "GLOB")]
+    excludes: Vec<String>,
+
+    /// Only work on parent modules, not on their tests or examples.
+    #[arg(long = "only-parents")]
+    only_parents: bool,
+
+    /// Only work on tests modules. Combines with the other --only-* flags.
+    #[arg(long = "only-tests")]
+    only_tests: bool,
+
+    /// Only work on examples modules. Combines with the other --only-* flags.
+    #[arg(long = "only-examples")]
+    only_examples: bool,
+
+    /// Only work on modules with files that changed since this git ref, e.g. `HEAD~1` or
+    /// `origin/main`.
+    #[arg(long = "changed-since", value_name = "REF")]
+    changed_since: Option<String>,
+
+    /// Continue a failed or interrupted `create` of this module from its first unfinished step.
+    #[arg(long = "resume", value_name = "MODULE", conflicts_with = "modules")]
+    resume: Option<String>,
+
+    /// Format used to print the error that aborted the task.
+    #[arg(long = "error-format", value_enum, default_value_t = ErrorFormat::Text)]
+    error_format: ErrorFormat,
+
+    /// Also write one JSON event per line to stdout for every step daggy takes. Stdout then
+    /// only carries events, so task results are reported as events instead of printed.
+    #[arg(long = "output", value_enum, default_value_t = OutputFormat::Text)]
+    output: OutputFormat,
+
+    /// Write a JUnit XML report with one test case per module to this path.
+    #[arg(long = "junit", value_name = "PATH")]
+    junit: Option<PathBuf>,
+
+    /// Write the full output of every external command to .daggerx/logs/.
+    #[arg(long = "command-logs")]
+    command_logs: bool,
+
+    /// Stop external commands running longer than this (e.g. 90s, 15m, 1h); 0 disables it.
+    #[arg(long = "timeout", value_parser = parse_duration, default_value = "30m")]
+    timeout: Duration,
+
+    /// How many times to retry dagger commands that fail to connect to the engine. Timeouts,
+    /// git and `dagger publish` are never retried.
+    #[arg(long = "retries", default_value_t = 2)]
+    retries: u32,
+
+    /// Wait before the first retry, doubled for every following one.
+    #[arg(long = "retry-backoff", value_parser = parse_duration, default_value = "2s")]
+    retry_backoff: Duration,
+
+    /// The dagger binary to run. Repeat it with `develop` to develop every module against
+    /// each binary and report a module × engine matrix. Defaults to the one pinned in
+    /// .daggerx/toolchain.json, then to `dagger`.
+    #[arg(long = "dagger-bin", value_name = "PATH", env = "DAGGY_DAGGER_BIN", value_delimiter = ',')]
+    dagger_bins: Vec<String>,
+
+    /// The go binary to run. Defaults to the one pinned in .daggerx/toolchain.json, then to `go`.
+    #[arg(long = "go-bin", value_name = "PATH", env = "DAGGY_GO_BIN")]
+    go_bin: Option<String>,
+
+    /// The git binary to run. Defaults to the one pinned in .daggerx/toolchain.json, then to `git`.
+    #[arg(long = "git-bin", value_name = "PATH", env = "DAGGY_GIT_BIN")]
+    git_bin: Option<String>,
+
+    /// Oldest dagger CLI version accepted by the preflight checks.
+    #[arg(long = "min-dagger-version", value_name = "VERSION", default_value_t = DEFAULT_MIN_DAGGER_VERSION)]
+    min_dagger_version: Version,
+
+    /// Create the module without a dagger engine: write dagger.json and go.mod directly and
+    /// leave code generation to a later `--task develop`.
+    #[arg(long = "no-engine")]
+    no_engine: bool,
+
+    /// With `develop`, watch this module's Go sources and dagger.json and develop it, then its
+    /// tests and examples/go modules, after every change until Ctrl-C.
+    #[arg(long = "watch", value_name = "MODULE")]
+    watch: Option<String>,
+
+    /// How long files must stay unchanged before `--watch` starts a new cycle.
+    #[arg(long = "debounce", value_parser = parse_duration, default_value = "500ms")]
+    debounce: Duration,
+
+    /// Run develop on every selected module and fail if it changed any file, listing them.
+    #[arg(long = "check", conflicts_with = "watch")]
+    check: bool,
+
+    /// With --check, put the files develop changed back the way they were.
+    #[arg(long = "restore", requires = "check")]
+    restore: bool,
+
+    /// Develop every module, including those unchanged since their last successful develop.
+    #[arg(long = "force")]
+    force: bool,
+
+    /// Create the module without its examples/go module.
+    #[arg(long = "no-examples")]
+    no_examples: bool,
+
+    /// Create the module without its tests module.
+    #[arg(long = "no-tests")]
+    no_tests: bool,
+
+    /// Create the module without a GitHub Actions workflow, e.g. for internal modules.
+    #[arg(long = "no-workflow")]
+    no_workflow: bool,
+
+    /// Create the module without a README.md.
+    #[arg(long = "no-readme")]
+    no_readme: bool,
+
+    /// With `changed`, the git ref to compare from, e.g. the target branch of a pull request.
+    /// Files are compared with the merge base of --base and --head, as in a pull request.
+    #[arg(long = "base", value_name = "REF")]
+    base: Option<String>,
+
+    /// With `changed`, the git ref to compare to.
+    #[arg(long = "head", value_name = "REF", default_value = "HEAD")]
+    head: String,
+
+    /// How `changed` prints the modules, `publish` its plan and `status` the release state on
+    /// stdout. Defaults to names, or to a table for `status`.
+    #[arg(long = "format", value_enum)]
+    format: Option<ListFormat>,
+
+    /// With `changelog`, the release tag to describe, e.g. `alpha/v1.3.0`; defaults to the
+    /// latest tag of --module. With `publish`, the tag to publish; defaults to the latest
+    /// release of every selected module.
+    #[arg(long = "tag", value_name = "TAG")]
+    tag: Option<String>,
+
+    /// With `changelog`, print the release notes of the tag instead of updating CHANGELOG.md.
+    #[arg(long = "notes")]
+    notes: bool,
+
+    /// With `publish`, only print the modules, versions and commits that would be published.
+    #[arg(long = "plan")]
+    plan: bool,
+
+    /// With `publish`, where modules are published, as `<address>/<module>@<version>`.
+    #[arg(long = "address", default_value = DEFAULT_ADDRESS)]
+    address: String,
+
+    /// With `bump`, the part of the version to increase: major, minor, patch or prerelease.
+    /// Inferred from the conventional commits touching the module since its latest tag when
+    /// left out. `status` previews the versions it would tag.
+    #[arg(long = "bump", value_name = "LEVEL")]
+    bump: Option<Bump>,
+
+    /// With `bump --bump prerelease` (and `status`), the prerelease identifier, as in v1.2.3-rc.1.
+    #[arg(long = "preid", default_value = "rc")]
+    preid: String,
+
+    /// With `bump`, push the new tags.
+    #[arg(long = "push")]
+    push: bool,
+
+    /// The git remote tags are pushed to.
+    #[arg(long = "remote", default_value = "origin")]
+    remote: String,
+
+    /// Skip checking that git, dagger and go are installed and recent enough.
+    #[arg(long = "skip-preflight")]
+    skip_preflight: bool,
+
+    /// Echo every command and its working directory.
+    #[arg(short = 'v', long = "verbose", conflicts_with = "quiet")]
+    verbose: bool,
+
+    /// Only print the final summary and errors.
+    #[arg(short = 'q', long = "quiet")]
+    quiet: bool,
+
+    /// Disable color and emoji (also disabled when NO_COLOR is set or stderr is not a terminal).
+    #[arg(long = "no-color")]
+    no_color: bool,
 }
 
-#[derive(Deserialize)]
-struct NewDaggerModule {
-    path: String,
-    name: String,
-    module_src_path: String,
-    module_test_src_path: String,
-    github_actions_workflow_path: String,
-    github_actions_workflow: String,
-}
-fn main() -> Result<(), Error> {
-    let args: Args = Args::parse();
-
-    match args.task.as_str() {
-        "create" => {
-            if let Some(module) = args.module {
-                create_module(&module)?;
-            } else {
-                eprintln!("Module name is required for 'create' task");
-                std::process::exit(1);
-            }
-        },
-        "develop" => {
-            develop_modules()?;
-        },
-        _ => {
-            eprintln!("Unknown task: {}", args.task);
-            std::process::exit(1);
-        }
+/// Parse durations such as `500ms`, `90s`, `15m`, `1h`; a bare number means seconds.
+fn parse_duration(value: &str) -> std::result::Result<Duration, String> {
+    let value = value.trim();
+    let split = value.find(|c: char| !c.is_ascii_digit()).unwrap_or(value.len());
+    let (number, unit) = value.split_at(split);
+    let number: u64 = number.parse().map_err(|_| format!("invalid duration: {:?}", value))?;
+
+    match unit {
+        "ms" => Ok(Duration::from_millis(number)),
+        "" | "s" => Ok(Duration::from_secs(number)),
+        "m" => Ok(Duration::from_secs(number * 60)),
+        "h" => Ok(Duration::from_secs(number * 3600)),
+        _ => Err(format!("invalid duration unit {:?}, expected ms, s, m or h", unit)),
     }
-
-    Ok(())
 }
 
-// Create a new module in the root of the current directory.
-fn create_module(module: &str) -> Result<(), Error> {
-    println!("Creating module 🚀: {}", module);
-    dagger_module_exists(module)?;
-
-    let git_root = get_git_root()?;
-    env::set_current_dir(git_root)?;
-
-    let new_module = get_module_configurations(module)?;
-    println!("Module path: {}", new_module.path);
-    println!("Module src path: {}", new_module.module_src_path);
-    println!("Module test src path: {}", new_module.module_test_src_path);
-    println!("GitHub Actions workflow path: {}", new_module.github_actions_workflow_path);
-
-    // Initialize the new module
-    initialize_module(&new_module)?;
-
-    // Initialize examples and tests
-    initialize_examples(&new_module)?;
-    initialize_tests(&new_module)?;
-
-    // Copy README and LICENSE files
-    copy_readme_and_license(&new_module)?;
-
-    // Update README content
-    update_readme_content(&new_module)?;
-
-    // Generate GitHub Actions workflow
-    generate_github_actions_workflow(&new_module)?;
-
-    // Run go fmt to format the code
-    println!("Running go fmt and ensuring the code is formatted correctly 🧹");
-    run_go_fmt(&new_module.path)?;
-    run_go_fmt(&format!("{}/examples/go", new_module.path))?;
-    run_go_fmt(&new_module.module_test_src_path)?;
-
-    println!("Module \"{}\" initialized successfully 🎉", new_module.name);
-    println!("Don't forget to add it to GitHub Actions workflow 'release.yml' when your module is ready for release.");
-    println!("It's recommended to run just cilocal <newmodule> to test the module locally before releasing it.");
-
-    Ok(())
+#[derive(Clone, Copy, Debug, ValueEnum)]
+enum ErrorFormat {
+    Text,
+    Json,
 }
 
-fn copy_and_process_templates(module_cfg: &NewDaggerModule, template_dir: &str, dest_dir: &str) -> Result<(), Error> {
-    for entry in fs::read_dir(template_dir)? {
-        let entry = entry?;
-        let path = entry.path();
-
-        if path.is_dir() {
-            let new_dir = format!("{}/{}", dest_dir, entry.file_name().to_string_lossy());
-            fs::create_dir_all(&new_dir)?;
-            copy_and_process_templates(module_cfg, &path.to_string_lossy(), &new_dir)?;
-        } else {
-            let content = fs::read_to_string(&path)?;
-            let new_content = process_template_content(&content, module_cfg);
-
-            let dest_file_name = entry.file_name().to_string_lossy().replace(".tmpl", "");
-            let dest_path = format!("{}/{}", dest_dir, dest_file_name);
-            fs::write(dest_path, new_content)?;
-        }
-    }
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+enum ListFormat {
+    /// One module name per line; `publish` adds the version and commit.
+    Names,
+    /// An aligned table with a header row; lists without more columns print names.
+    Table,
+    /// A JSON array of module names, e.g. for a GitHub Actions matrix; `publish` prints
+    /// objects with the module, version, tag and commit.
+    Json,
+}
 
-    Ok(())
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+enum OutputFormat {
+    /// Human readable progress on stderr only.
+    Text,
+    /// Newline delimited JSON events on stdout, see `daggy::event::Event`.
+    Json,
 }
 
-// New function
-// Modified function
-fn copy_dir_recursive(src: &Path, dest: &Path, module_cfg: &NewDaggerModule) -> Result<(), Error> {
-    if !dest.exists() {
-        fs::create_dir_all(dest)?;
+fn main() -> ExitCode {
+    let args: Args = Args::parse();
+    let verbosity = match (args.verbose, args.quiet) {
+        (true, _) => Verbosity::Verbose,
+        (_, true) => Verbosity::Quiet,
+        _ => Verbosity::Normal,
+    };
+    let events = match args.output {
+        OutputFormat::Text => EventSink::disabled(),
+        OutputFormat::Json => EventSink::stdout(),
+    };
+    let mut app = App {
+        logger: Logger::from_env(verbosity, args.no_color),
+        events,
+        github: GithubActions::from_env(),
+        cancellation: Cancellation::new(),
+        tally: Cell::default(),
+        started: Instant::now(),
+        toolchain_config: ToolchainConfig::default(),
+        args,
+    };
+
+    let result = app.cancellation.install_signal_handlers().and_then(|_| app.load_toolchain_config()).and_then(|_| app.run());
+    let status = match &result {
+        Ok(()) => RunStatus::Succeeded,
+        Err(e) => RunStatus::of(e),
+    };
+    if let Err(e) = &result {
+        app.events.emit(Event::Error(ErrorDetails::from(e)));
     }
+    app.emit_summary(status);
 
-    for entry in fs::read_dir(src)? {
-        let entry = entry?;
-        let file_type = entry.file_type()?;
-        let src_path = entry.path();
-        let mut file_name = entry.file_name().to_string_lossy().to_string();
-
-        // Remove .tmpl extension if present
-        if file_name.ends_with(".tmpl") {
-            file_name = file_name.trim_end_matches(".tmpl").to_string();
-        }
-
-        let dest_path = dest.join(&file_name);
-
-        if file_type.is_dir() {
-            copy_dir_recursive(&src_path, &dest_path, module_cfg)?;
-        } else {
-            let content = fs::read_to_string(&src_path)?;
-            let processed_content = process_template_content(&content, module_cfg);
-            fs::write(dest_path, processed_content)?;
+    match result {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            app.report_error(&e);
+            ExitCode::from(e.exit_code() as u8)
         }
     }
-
-    Ok(())
 }
 
-// New function
-fn process_template_content(content: &str, module_cfg: &NewDaggerModule) -> String {
-    // let pkg_name = module_cfg.name.replace("-", "_");
-    let pkg_name = module_cfg.name.to_string().to_lowercase().trim().replace(" ", "-");
-    let pascal_case_name = to_pascal_case(&module_cfg.name);
-    let lowercase_name = module_cfg.name.to_lowercase();
+/// What a task got through, for the final summary event.
+#[derive(Clone, Copy, Debug, Default)]
+struct Tally {
+    total: usize,
+    succeeded: usize,
+    failed: usize,
+    skipped: usize,
+}
 
-    let content = content.replace("{{.module_name_pkg}}", &pkg_name);
-    let content = content.replace("{{.module_name}}", &pascal_case_name);
-    content.replace("{{.module_name_lowercase}}", &lowercase_name)
+/// Everything a task needs: the parsed arguments, where output goes and the cancellation
+/// flag.
+struct App {
+    args: Args,
+    logger: Logger,
+    events: EventSink,
+    github: GithubActions,
+    cancellation: Cancellation,
+    tally: Cell<Tally>,
+    started: Instant,
+    toolchain_config: ToolchainConfig,
 }
 
-fn develop_modules() -> Result<(), Error> {
-    // Ensure we're in a Git repository
-    if !Path::new(".git").exists() {
-        return Err(Error::new(ErrorKind::NotFound, "Error: This script must be run from the root of a Git repository."));
+impl App {
+    fn run(&self) -> Result<()> {
+        match self.args.task.as_str() {
+            "create" => match (&self.args.modules[..], &self.args.resume) {
+                ([module], _) => self.create_module(module, false),
+                ([], Some(module)) => self.create_module(module, true),
+                ([], None) => Err(Error::Usage("module name is required for 'create' task".to_string())),
+                _ => Err(Error::Usage("create takes a single --module".to_string())),
+            },
+            "develop" => match &self.args.watch {
+                Some(module) => self.watch_module(module),
+                None => self.develop_modules(),
+            },
+            "changed" => self.changed_modules(),
+            "bump" => self.bump_modules(),
+            "changelog" => self.write_changelog(),
+            "publish" => self.publish_modules(),
+            "status" => self.release_status(),
+            _ => Err(Error::Usage(format!("unknown task: {}", self.args.task))),
+        }
     }
 
-    println!("Git repository detected. Proceeding...");
-
-    // Find all directories containing a 'dagger.json' file
-    let modules = find_dagger_modules()?;
-
-    if modules.is_empty() {
-        println!("No modules found.");
-        return Ok(());
+    fn update_tally(&self, update: impl FnOnce(&mut Tally)) {
+        let mut tally = self.tally.get();
+        update(&mut tally);
+        self.tally.set(tally);
     }
 
-    // Initialize counters
-    let total_modules = modules.len();
-    let mut successful_modules = 0;
-    let mut failed_modules = 0;
-
-    println!("Identifying modules with dagger.json files...");
-    for dir in &modules {
-        println!("Module identified: {}", dir);
+    fn emit_summary(&self, status: RunStatus) {
+        let Tally { total, succeeded, failed, skipped } = self.tally.get();
+        self.events.emit(Event::Summary {
+            task: self.args.task.clone(),
+            status,
+            total,
+            succeeded,
+            failed,
+            skipped,
+            duration_ms: millis(self.started.elapsed()),
+        });
     }
 
-    println!("\nRunning dagger develop in identified modules...\n");
-
-    for dir in &modules {
-        print!("Developing module: {}... ", dir);
+    /// Print the result of a task on stdout, unless stdout carries the `--output json` events,
+    /// which report the result themselves.
+    fn print_result(&self, result: impl AsRef<str>) {
+        if !self.events.is_enabled() {
+            print!("{}", result.as_ref());
+        }
+    }
 
-        if Path::new(&format!("{}/dagger.json", dir)).exists() {
-            println!("Entering directory: {}", dir);
-            match run_dagger_develop(dir) {
-                Ok(_) => {
-                    println!("✅ Successfully developed module: {}", dir);
-                    successful_modules += 1;
-                }
-                Err(e) => {
-                    println!("❌ Failed to develop module: {}", dir);
-                    eprintln!("Error: {}", e);
-                    failed_modules += 1;
+    fn report_error(&self, e: &Error) {
+        match self.args.error_format {
+            ErrorFormat::Text => {
+                let mut message = format!("Error: {}", e.root_cause());
+                for context in e.contexts() {
+                    message.push_str(&format!("\n  while: {}", context));
                 }
+                self.logger.error(message);
             }
-        } else {
-            println!("Skipped 🚫 No dagger.json found in: {}", dir);
+            ErrorFormat::Json => eprintln!("{}", e.to_json()),
         }
     }
 
-    println!("\n");
+    fn runner(&self, repo: &Repo) -> Arc<dyn CommandRunner> {
+        let args = &self.args;
+        let timeout = Some(args.timeout).filter(|timeout| !timeout.is_zero());
+        let mut runner = SystemRunner::new()
+            .with_timeout(timeout)
+            .with_cancellation(self.cancellation.clone())
+            .with_logger(self.logger)
+            .with_events(self.events.clone());
+        if args.command_logs {
+            runner = runner.with_log_dir(repo.root().join(".daggerx/logs"));
+        }
 
-    if successful_modules == total_modules {
-        println!("Dagger develop completed for all {} modules successfully! 🎉", total_modules);
-    } else if failed_modules > 0 {
-        println!("Dagger develop completed with {} successes ✅ and {} failures ❌.", successful_modules, failed_modules);
-        return Err(Error::new(ErrorKind::Other, "Some modules failed to develop"));
-    } else {
-        println!("Dagger develop completed with {} successes ✅. Please check the output above.", successful_modules);
+        let policy = RetryPolicy { max_retries: args.retries, initial_backoff: args.retry_backoff, ..RetryPolicy::default() };
+        let runner = RetryingRunner::new(Arc::new(runner), policy)
+            .with_programs(self.toolchains().iter().map(|toolchain| toolchain.dagger().to_string()))
+            .with_cancellation(self.cancellation.clone())
+            .with_logger(self.logger);
+        Arc::new(runner)
     }
 
-    Ok(())
-}
-
-fn update_dagger_json(module_cfg: &NewDaggerModule) -> Result<(), Error> {
-    let dagger_json_path = format!("{}/dagger.json", module_cfg.path);
-
-    let mut json_content: Value = fs::read_to_string(&dagger_json_path)
-        .map_err(|e| Error::new(ErrorKind::Other, format!("Failed to read dagger.json: {}", e)))
-        .and_then(|content| serde_json::from_str(&content)
-            .map_err(|e| Error::new(ErrorKind::Other, format!("Failed to parse dagger.json: {}", e))))?;
-
-    json_content["exclude"] = json!([
-        "../.direnv",
-        "../.devenv",
-        "../go.work",
-        "../go.work.sum",
-        "tests",
-        "examples/go",
-    ]);
-
-    fs::write(dagger_json_path, serde_json::to_string_pretty(&json_content)?)
-        .map_err(|e| Error::new(ErrorKind::Other, format!("Failed to write updated dagger.json: {}", e)))?;
-
-    Ok(())
-}
-
-// New function
-fn update_tests_dagger_json(module_cfg: &NewDaggerModule) -> Result<(), Error> {
-    let dagger_json_path = format!("{}/tests/dagger.json", module_cfg.path);
-    let mut json_content: Value = fs::read_to_string(&dagger_json_path)
-        .map_err(|e| Error::new(ErrorKind::Other, format!("Failed to read tests/dagger.json: {}", e)))
-        .and_then(|content| serde_json::from_str(&content)
-            .map_err(|e| Error::new(ErrorKind::Other, format!("Failed to parse tests/dagger.json: {}", e))))?;
-
-    json_content["exclude"] = json!([
-        "../../.direnv",
-        "../../.devenv",
-        "../../go.work",
-        "../../go.work.sum"
-    ]);
-
-    fs::write(dagger_json_path, serde_json::to_string_pretty(&json_content)?)
-        .map_err(|e| Error::new(ErrorKind::Other, format!("Failed to write updated tests/dagger.json: {}", e)))?;
-
-    Ok(())
-}
-
-// New function
-fn update_examples_dagger_json(module_cfg: &NewDaggerModule) -> Result<(), Error> {
-    let dagger_json_path = format!("{}/examples/go/dagger.json", module_cfg.path);
-    let mut json_content: Value = fs::read_to_string(&dagger_json_path)
-        .map_err(|e| Error::new(ErrorKind::Other, format!("Failed to read examples/go/dagger.json: {}", e)))
-        .and_then(|content| serde_json::from_str(&content)
-            .map_err(|e| Error::new(ErrorKind::Other, format!("Failed to parse examples/go/dagger.json: {}", e))))?;
-
-    json_content["exclude"] = json!([
-        "../../../.direnv",
-        "../../../.devenv",
-        "../../../go.work",
-        "../../../go.work.sum"
-    ]);
-
-    fs::write(dagger_json_path, serde_json::to_string_pretty(&json_content)?)
-        .map_err(|e| Error::new(ErrorKind::Other, format!("Failed to write updated examples/go/dagger.json: {}", e)))?;
-
-    Ok(())
-}
-
-fn initialize_module(module_cfg: &NewDaggerModule) -> Result<(), Error> {
-    // Create the module directory
-    fs::create_dir_all(&module_cfg.path)?;
-    println!("Creating parent module 📦: {}", module_cfg.name);
-
-    // Change to the module directory
-    env::set_current_dir(&module_cfg.path)?;
-
-    // Run dagger init
-    run_command_with_output(&format!("dagger init --sdk go --name {} --source .", module_cfg.name), ".")?;
-
-    // Copy and process templates
-    copy_and_process_templates(module_cfg, "../.daggerx/templates/module", ".")?;
-
-    // Update dagger.json
-    update_dagger_json(module_cfg)?;
-
-    // Edit go.mod to set the correct module path
-    let go_mod_edit_command = format!("go mod edit -module github.com/Excoriate/daggerverse/{}", module_cfg.name);
-    run_command_with_output(&go_mod_edit_command, ".")?;
-
-    // Run dagger develop
-    run_command_with_output(&format!("dagger develop -m {}", module_cfg.name), ".")?;
-
-    // Change back to the root directory
-    env::set_current_dir("..")?;
-
-    Ok(())
-}
-
-// New function
-// Modified function
-fn initialize_examples(module_cfg: &NewDaggerModule) -> Result<(), Error> {
-    let examples_path = format!("{}/examples/go", module_cfg.path);
-    println!("Creating examples module (recipes)  📄: {}", module_cfg.name);
-
-    // Create the examples directory
-    fs::create_dir_all(&examples_path)?;
-
-    // Change to the examples directory
-    env::set_current_dir(&examples_path)?;
-
-    // Run dagger init
-    run_command_with_output("dagger init --sdk go --name go --source .", ".")?;
-
-    // Copy and process templates
-    copy_and_process_templates(module_cfg, "../../../.daggerx/templates/examples/go", ".")?;
-
-    // Update dagger.json
-    update_examples_dagger_json(module_cfg)?;
-
-    // Edit go.mod
-    let go_mod_edit_command = format!("go mod edit -module github.com/Excoriate/daggerverse/{}/examples/go", module_cfg.name);
-    run_command_with_output(&go_mod_edit_command, ".")?;
-
-    // Copy testdata/common directory
-    let src_testdata = "../../../.daggerx/templates/examples/go/testdata/common";
-    let dest_testdata = "testdata/common";
-    copy_dir_all(src_testdata, dest_testdata)?;
-
-    // Run dagger install and develop
-    run_command_with_output("dagger install ../../", ".")?;
-    run_command_with_output("dagger develop -m go", ".")?;
-
-    // Change back to the root directory
-    env::set_current_dir("../../..")?;
-
-    Ok(())
-}
-
-// New helper function to copy directories recursively
-fn copy_dir_all(src: impl AsRef<Path>, dst: impl AsRef<Path>) -> Result<(), Error> {
-    fs::create_dir_all(&dst)?;
-    for entry in fs::read_dir(src)? {
-        let entry = entry?;
-        let ty = entry.file_type()?;
-        if ty.is_dir() {
-            copy_dir_all(entry.path(), dst.as_ref().join(entry.file_name()))?;
-        } else {
-            fs::copy(entry.path(), dst.as_ref().join(entry.file_name()))?;
+    /// One toolchain per `--dagger-bin`, all sharing `--go-bin` and `--git-bin`. Binaries
+    /// not given on the command line come from the repository's toolchain config.
+    fn toolchains(&self) -> Vec<Toolchain> {
+        let (args, config) = (&self.args, &self.toolchain_config);
+        let mut shared = Toolchain::new();
+        if let Some(go) = args.go_bin.as_ref().or(config.go.as_ref()) {
+            shared = shared.with_go(expand_home(go));
+        }
+        if let Some(git) = args.git_bin.as_ref().or(config.git.as_ref()) {
+            shared = shared.with_git(expand_home(git));
+        }
+        let daggers: Vec<&String> = if args.dagger_bins.is_empty() { config.dagger.iter().collect() } else { args.dagger_bins.iter().collect() };
+        if daggers.is_empty() {
+            return vec![shared];
         }
+        daggers.into_iter().map(|bin| shared.clone().with_dagger(expand_home(bin))).collect()
     }
-    Ok(())
-}
-
-// Modified function
-fn initialize_tests(module_cfg: &NewDaggerModule) -> Result<(), Error> {
-    let tests_path = format!("{}/tests", module_cfg.path);
-    println!("Creating tests module (tests) 🧪: {}", module_cfg.name);
-
-    // Create the tests directory
-    fs::create_dir_all(&tests_path)?;
 
-    // Change to the tests directory
-    env::set_current_dir(&tests_path)?;
-
-    // Run dagger init
-    run_command_with_output("dagger init --sdk go --name tests --source .", ".")?;
-
-    // Copy and process templates
-    copy_and_process_templates(module_cfg, "../../.daggerx/templates/tests", ".")?;
-
-    // Update dagger.json
-    update_tests_dagger_json(module_cfg)?;
-
-    // Edit go.mod
-    let go_mod_edit_command = format!("go mod edit -module github.com/Excoriate/daggerverse/{}/tests", module_cfg.name);
-    run_command_with_output(&go_mod_edit_command, ".")?;
-
-    // Copy testdata/common directory
-    let src_testdata = "../../.daggerx/templates/tests/testdata/common";
-    let dest_testdata = "testdata/common";
-    copy_dir_all(src_testdata, dest_testdata)?;
-
-    // Run dagger install and develop
-    run_command_with_output("dagger install ../", ".")?;
-    run_command_with_output("dagger develop -m tests", ".")?;
-
-    // Change back to the root directory
-    env::set_current_dir("../..")?;
-
-    Ok(())
-}
+    /// The repository containing the working directory, run with the configured git.
+    fn discover_repo(&self) -> Result<Repo> {
+        Repo::discover_with(self.toolchains()[0].git())
+    }
 
-fn get_git_root() -> Result<String, Error> {
-    let output = Command::new("git")
-        .args(&["rev-parse", "--show-toplevel"])
-        .output()?;
+    /// Pick up the repository's toolchain config, before anything runs a tool.
+    fn load_toolchain_config(&mut self) -> Result<()> {
+        let cwd = std::env::current_dir().at_path(".")?;
+        self.toolchain_config = ToolchainConfig::find(&cwd)?;
+        Ok(())
+    }
 
-    if output.status.success() {
-        Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
-    } else {
-        Err(Error::new(ErrorKind::Other, "Not in a git repository"))
+    /// Fail before touching anything when a tool is missing or too old for `modules`.
+    fn preflight(
+        &self,
+        runner: Arc<dyn CommandRunner>,
+        repo: &Repo,
+        toolchain: &Toolchain,
+        tools: &[Tool],
+        modules: &[Module],
+    ) -> Result<Vec<InstalledTool>> {
+        if self.args.skip_preflight {
+            self.logger.debug("Skipping preflight checks.");
+            return Ok(Vec::new());
+        }
+        let installed = Preflight::new(runner, repo.root())
+            .with_toolchain(toolchain.clone())
+            .with_min_dagger_version(Some(self.args.min_dagger_version))
+            .check(tools, modules)
+            .context("preflight checks")?;
+        let versions: Vec<String> = installed.iter().map(|t| format!("{} {}", toolchain.program(t.tool), t.version)).collect();
+        self.logger.info(format!("Preflight checks passed{}: {}", self.logger.emoji(" ✅"), versions.join(", ")));
+        Ok(installed)
     }
-}
 
-fn copy_and_replace_templates(template_dir: &str, destination_dir: &str, module_name: &str) -> Result<(), Error> {
-    for entry in fs::read_dir(template_dir)? {
-        let entry = entry?;
-        let path = entry.path();
+    // Create a new module in the root of the current git repository.
+    // With `resume`, pick up where the state file of an earlier attempt left off.
+    fn create_module(&self, module: &str, resume: bool) -> Result<()> {
+        let log = &self.logger;
+        if resume {
+            log.info(format!("Resuming creation of module{}: {}", log.emoji(" 🚀"), module));
+        } else {
+            log.info(format!("Creating module{}: {}", log.emoji(" 🚀"), module));
+        }
 
-        if path.is_dir() {
-            let new_dir = format!("{}/{}", destination_dir, entry.file_name().to_string_lossy());
-            fs::create_dir_all(&new_dir)?;
-            copy_and_replace_templates(&path.to_string_lossy(), &new_dir, module_name)?;
+        let [toolchain] = &self.toolchains()[..] else {
+            return Err(Error::Usage("create runs with a single --dagger-bin".to_string()));
+        };
+        let repo = self.discover_repo()?;
+        let existing = repo.find_dagger_modules()?;
+        let runner = self.runner(&repo);
+        let engine_version = newest_engine_version(&existing)?.map_or(DEFAULT_ENGINE_VERSION, |(version, _)| version);
+        let scaffolder = Scaffolder::new(repo)
+            .with_runner(runner.clone())
+            .with_toolchain(toolchain.clone())
+            .with_offline(self.args.no_engine)
+            .with_engine_version(engine_version)
+            .with_logger(self.logger)
+            .with_events(self.events.clone())
+            .with_components(Components {
+                examples: !self.args.no_examples,
+                tests: !self.args.no_tests,
+                workflow: !self.args.no_workflow,
+                readme: !self.args.no_readme,
+            });
+        let (new_module, resumed) = if resume {
+            let (new_module, state) = scaffolder.resume(module)?;
+            (new_module, Some(state))
         } else {
-            let content = fs::read_to_string(&path)?;
-            let new_content = if path.extension().map_or(false, |ext| ext == "go") {
-                replace_module_name(&content, &to_pascal_case(&module_name))
+            let new_module = scaffolder.plan(module).inspect_err(|e| {
+                if let Error::ModuleExists { path, .. } = e {
+                    if CreateState::exists(path) {
+                        log.info(format!("An unfinished create of \"{}\" was found; continue it with --resume {}", module, module));
+                    }
+                }
+            })?;
+            (new_module, None)
+        };
+        let scaffolder = match &resumed {
+            Some(state) => scaffolder.with_offline(state.offline).with_components(state.components),
+            None => scaffolder,
+        };
+        let tools: &[Tool] = if scaffolder.is_offline() { &[Tool::Git, Tool::Go] } else { &[Tool::Git, Tool::Dagger, Tool::Go] };
+        let installed = self.preflight(runner, scaffolder.repo(), toolchain, tools, &existing)?;
+        // The go directive written offline follows the go toolchain: as preflight found it, or
+        // as the name of its binary tells, such as `go1.22.4` in toolchain.json.
+        let go_version = installed.iter().find(|tool| tool.tool == Tool::Go).map(|tool| tool.version);
+        let scaffolder = scaffolder.with_go_version(go_version.or_else(|| Version::find_in(toolchain.go())).unwrap_or(DEFAULT_GO_VERSION));
+        if scaffolder.is_offline() {
+            log.info(format!("Scaffolding without a dagger engine, pinned to engineVersion {}.", engine_version));
+        }
+        log.debug(format!("Module path: {}", new_module.path.display()));
+        log.debug(format!("Module test src path: {}", new_module.tests_path.display()));
+        log.debug(format!("GitHub Actions workflow path: {}", new_module.github_actions_workflow_path.display()));
+
+        let mut state = match resumed {
+            Some(state) => state,
+            None => scaffolder.begin(&new_module)?,
+        };
+        let mut run = scaffolder.run_steps(new_module.clone(), &mut state);
+        self.update_tally(|tally| {
+            tally.total = scaffolder.steps().len();
+            tally.succeeded = run.report.steps.len();
+            tally.skipped = run.report.skipped.len();
+        });
+        if let Some(e) = run.error.take() {
+            if matches!(e.root_cause(), Error::Cancelled) {
+                let finished = run.finished();
+                let done: Vec<&str> = finished.iter().map(|s| s.name()).collect();
+                let pending: Vec<&str> = run.pending.iter().map(|s| s.name()).collect();
+                log.summary(format!("Interrupted{} while creating module \"{}\".", log.emoji(" ⛔"), new_module.name));
+                log.summary(format!("Finished steps: {}", list_or_none(&done)));
+                log.summary(format!("Unfinished steps: {}", list_or_none(&pending)));
             } else {
-                replace_module_name(&content, module_name)
-            };
-
-            let dest_file_name = entry.file_name().to_string_lossy().replace(".tmpl", "");
-            let dest_path = format!("{}/{}", destination_dir, dest_file_name);
-            fs::write(dest_path, new_content)?;
+                self.update_tally(|tally| tally.failed = 1);
+            }
+            log.summary(format!("Continue with: daggy --task create --resume {}", new_module.name));
+            return Err(e);
         }
-    }
-
-    Ok(())
-}
 
-fn copy_readme_and_license(module_cfg: &NewDaggerModule) -> Result<(), Error> {
-    let readme_dest_path = format!("{}/README.md", module_cfg.path);
-    let license_dest_path = format!("{}/LICENSE", module_cfg.path);
-    println!("Copying README.md and LICENSE files 📄: {}", module_cfg.name);
-
-    // Ensure the destination directory exists
-    fs::create_dir_all(&module_cfg.path)?;
+        log.summary(format!("Module \"{}\" initialized successfully{}", new_module.name, log.emoji(" 🎉")));
+        if scaffolder.is_offline() {
+            log.info("No engine was used, so the dagger SDK code is missing: run daggy --task develop once an engine is available.");
+        }
+        log.info("Don't forget to add it to GitHub Actions workflow 'release.yml' when your module is ready for release.");
+        log.info("It's recommended to run just cilocal <newmodule> to test the module locally before releasing it.");
 
-    // Copy the README.md and LICENSE files from the template directory to the module path
-    fs::copy(".daggerx/templates/README.md", &readme_dest_path)?;
-    fs::copy(".daggerx/templates/LICENSE", &license_dest_path)?;
+        Ok(())
+    }
 
-    // Replace placeholders in README.md if any
-    let readme_content = fs::read_to_string(&readme_dest_path)?;
-    let new_readme_content = readme_content.replace("[@MODULE_NAME]", &module_cfg.name);
-    fs::write(readme_dest_path, new_readme_content)?;
+    fn develop_modules(&self) -> Result<()> {
+        let log = &self.logger;
+        let repo = self.discover_repo()?;
 
-    Ok(())
-}
+        log.debug("Git repository detected. Proceeding...");
 
+        let runner = self.runner(&repo);
+        let modules = self.select_modules(&repo, runner.as_ref())?;
 
-// Modified function
-fn get_module_configurations(module: &str) -> Result<NewDaggerModule, Error> {
-    let module_path_full = env::current_dir()?.join(module);
-    let current_root_dir = env::current_dir()?;
+        if modules.is_empty() {
+            log.summary("No modules found.");
+            return Ok(());
+        }
 
-    Ok(NewDaggerModule {
-        path: module_path_full.to_string_lossy().to_string(),
-        module_src_path: module_path_full.to_string_lossy().to_string(),
-        module_test_src_path: module_path_full.join("tests").to_string_lossy().to_string(),
-        name: module.to_string(),
-        github_actions_workflow_path: current_root_dir.join(".github/workflows").to_string_lossy().to_string(),
-        github_actions_workflow: current_root_dir.join(".github/workflows").join(format!("ci-mod-{}.yaml", module)).to_string_lossy().to_string(),
-    })
-}
+        log.info("Identifying modules with dagger.json files...");
+        for module in &modules {
+            log.info(format!("Module identified: {}", module.name));
+            self.events.emit(Event::ModuleDiscovered { module: module.name.clone(), path: module.path.clone() });
+        }
 
-// Modified function
-fn generate_github_actions_workflow(module_cfg: &NewDaggerModule) -> Result<(), Error> {
-    println!("Generating GitHub Actions workflow 🚀: {}", module_cfg.name);
-    fs::create_dir_all(&module_cfg.github_actions_workflow_path)?;
-    let template_path = ".daggerx/templates/github/workflows/mod-template-ci.yaml.tmpl";
-    let output_path = &module_cfg.github_actions_workflow;
+        let toolchains = self.toolchains();
+        let matrix = toolchains.len() > 1;
+        if matrix && self.args.check {
+            return Err(Error::Usage("--check runs with a single --dagger-bin".to_string()));
+        }
+        let mut developer = Developer::new(repo.clone())
+            .with_check(self.args.check)
+            .with_restore(self.args.restore)
+            .with_force(self.args.force)
+            .with_logger(self.logger)
+            .with_events(self.events.clone())
+            .with_github(self.github.clone());
+        for (index, toolchain) in toolchains.into_iter().enumerate() {
+            let tools: &[Tool] = if index == 0 { &[Tool::Git, Tool::Dagger] } else { &[Tool::Dagger] };
+            // A matrix deliberately tries engines other than the modules' engineVersion.
+            let pinned: &[Module] = if matrix { &[] } else { &modules };
+            let installed = self.preflight(runner.clone(), &repo, &toolchain, tools, pinned)?;
+            let mut label = installed
+                .iter()
+                .find(|installed| installed.tool == Tool::Dagger)
+                .map_or_else(|| toolchain.dagger().to_string(), |installed| installed.version.to_string());
+            if developer.engines().iter().any(|engine| engine.label == label) {
+                label = format!("{} ({})", label, toolchain.dagger());
+            }
+            let scaffolder = Scaffolder::new(repo.clone()).with_runner(runner.clone()).with_toolchain(toolchain);
+            developer = developer.with_engine(Engine { label, scaffolder });
+        }
 
-    let template_content = fs::read_to_string(template_path)?;
-    let new_content = process_template_content(&template_content, module_cfg);
-    fs::write(output_path, new_content)?;
+        log.info("Running dagger develop in identified modules...");
+
+        let total_modules = modules.len();
+        let engines = developer.engines().len();
+        let total_runs = total_modules * engines;
+        self.update_tally(|tally| tally.total = total_runs);
+        let mut run = developer.develop(&modules)?;
+        let report = &run.report;
+        self.update_tally(|tally| {
+            tally.succeeded = report.succeeded();
+            tally.failed = report.failed();
+            tally.skipped = report.skipped();
+        });
+        self.write_develop_reports(report, &run.pending)?;
+
+        if let Some(e) = run.interrupted.take() {
+            let finished = run.finished(log);
+            let finished: Vec<&str> = finished.iter().map(String::as_str).collect();
+            let unfinished: Vec<&str> = run.pending.iter().map(|m| m.name.as_str()).collect();
+            log.summary(format!("Interrupted{} after {} of {} modules.", log.emoji(" ⛔"), total_modules - run.pending.len(), total_modules));
+            log.summary(format!("Finished modules: {}", list_or_none(&finished)));
+            log.summary(format!("Unfinished modules: {}", list_or_none(&unfinished)));
+            return Err(e);
+        }
+        if matrix {
+            let labels: Vec<&str> = developer.engines().iter().map(|engine| engine.label.as_str()).collect();
+            log.summary(engine_matrix(report, &modules, &labels));
+        }
 
-    Ok(())
-}
+        let (successful_modules, failed_modules, unchanged) = (report.succeeded(), report.failed(), report.unchanged());
+        if failed_modules == report.drifted() && self.args.check {
+            let drifted = run.drifted();
+            let files = drifted.iter().map(|(_, files)| files).sum();
+            if files == 0 {
+                log.summary(format!("Generated code of all {} modules is up to date{}", successful_modules, log.emoji(" ✅")));
+                return Ok(());
+            }
+            let listed: Vec<String> = drifted.iter().map(|(module, files)| format!("{} ({} files)", module, files)).collect();
+            log.summary(format!("Generated code is out of date{} in: {}", log.emoji(" ❌"), listed.join(", ")));
+            if self.args.restore {
+                log.summary("The changed files were restored.");
+            } else {
+                log.summary("Run daggy --task develop and commit the result.");
+            }
+            return Err(Error::Drift { modules: drifted.len(), files });
+        }
+        if unchanged > 0 {
+            let names: Vec<&str> = report
+                .outcomes
+                .iter()
+                .filter(|o| matches!(o.status, DevelopStatus::Unchanged))
+                .map(|o| o.module.name.as_str())
+                .collect();
+            log.summary(format!(
+                "Skipped {} unchanged modules{}: {} (use --force to develop them anyway)",
+                unchanged,
+                log.emoji(" ⏭️"),
+                names.join(", ")
+            ));
+        }
+        if successful_modules == total_runs && matrix {
+            log.summary(format!("Dagger develop completed for all {} modules with {} engines successfully!{}", total_modules, engines, log.emoji(" 🎉")));
+        } else if successful_modules == total_runs {
+            log.summary(format!("Dagger develop completed for all {} modules successfully!{}", total_modules, log.emoji(" 🎉")));
+        } else if failed_modules > 0 {
+            log.summary(format!(
+                "Dagger develop completed with {} successes{} and {} failures{}.",
+                successful_modules,
+                log.emoji(" ✅"),
+                failed_modules,
+                log.emoji(" ❌")
+            ));
+            return Err(Error::DevelopFailed { failed: failed_modules, total: total_runs });
+        } else if successful_modules + unchanged == total_runs {
+            log.summary(format!(
+                "Dagger develop completed: {} developed{}, {} unchanged{}.",
+                successful_modules,
+                log.emoji(" ✅"),
+                unchanged,
+                log.emoji(" ⏭️")
+            ));
+        } else {
+            log.summary(format!("Dagger develop completed with {} successes{}. Please check the output above.", successful_modules, log.emoji(" ✅")));
+        }
 
-fn dagger_module_exists(module: &str) -> Result<(), Error> {
-    if module.is_empty() {
-        return Err(Error::new(ErrorKind::InvalidInput, "Module name cannot be empty"));
+        Ok(())
     }
 
-    // Check if the module already exists in the root of this directory.
-    if Path::new(module).exists() {
-        return Err(Error::new(ErrorKind::AlreadyExists, "Module already exists"));
+    /// Print the parent modules affected by the changes from --base to --head.
+    fn changed_modules(&self) -> Result<()> {
+        let log = &self.logger;
+        let Some(base) = &self.args.base else {
+            return Err(Error::Usage("--base is required for 'changed' task".to_string()));
+        };
+        let repo = self.discover_repo()?;
+        let runner = self.runner(&repo);
+        let files = repo.changed_between(runner.as_ref(), base, &self.args.head)?;
+        let modules = repo.find_dagger_modules()?;
+        let selected = self.select_modules(&repo, runner.as_ref())?;
+
+        let affected: Vec<Affected> = affected_modules(&modules, &files)?
+            .into_iter()
+            .filter(|affected| selected.iter().any(|module| module.name == affected.module))
+            .collect();
+        log.info(format!("{} files changed from {} to {}", files.len(), base, self.args.head));
+        for affected in &affected {
+            log.info(format!("Module affected: {} ({})", affected.module, affected.reason));
+            self.events.emit(Event::module_affected(affected));
+        }
+        self.update_tally(|tally| {
+            tally.total = affected.len();
+            tally.succeeded = affected.len();
+        });
+        log.summary(format!("{} modules affected.", affected.len()));
+
+        let names: Vec<&str> = affected.iter().map(|affected| affected.module.as_str()).collect();
+        match self.args.format.unwrap_or(ListFormat::Names) {
+            ListFormat::Names | ListFormat::Table => self.print_result(names.iter().map(|name| format!("{}\n", name)).collect::<String>()),
+            ListFormat::Json => self.print_result(serde_json::to_string(&names).expect("module names serialize to JSON") + "\n"),
+        }
+        Ok(())
     }
 
-    Ok(())
-}
-
-fn find_git_root() -> Result<String, Error> {
-    let output = Command::new("git")
-        .args(&["rev-parse", "--show-toplevel"])
-        .output()?;
-
-    if output.status.success() {
-        Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
-    } else {
-        Err(Error::new(ErrorKind::Other, "Not in a git repository"))
-    }
-}
-fn run_command_with_output(command: &str, target_dir: &str) -> Result<Output, Error> {
-    println!("Running command: {}", command);
-    let target_directory = if target_dir.is_empty() { find_git_root()? } else { target_dir.to_string() };
-
-    println!("Running command in directory: {}", target_directory);
-    let output = Command::new("sh")
-        .arg("-c")
-        .arg(command)
-        .current_dir(target_directory)
-        .stdout(Stdio::inherit())
-        .stderr(Stdio::inherit())
-        .output()?;
-
-    if !output.status.success() {
-        return Err(Error::new(ErrorKind::Other, format!("Command failed with exit code: {} and with error: {}", output.status, String::from_utf8_lossy(&output.stderr))));
+    /// Tag the next version of every selected parent module and print the new tags.
+    fn bump_modules(&self) -> Result<()> {
+        let log = &self.logger;
+        let repo = self.discover_repo()?;
+        let runner = self.runner(&repo);
+        if self.args.modules.is_empty() && self.args.changed_since.is_none() {
+            return Err(Error::Usage("select the modules to bump with --module or --changed-since".to_string()));
+        }
+        let modules = self.select_parents(&repo, runner.as_ref())?;
+        let bumper = self
+            .bumper(&repo, runner)?
+            .with_remote(self.args.push.then(|| self.args.remote.clone()))
+            .with_logger(self.logger)
+            .with_events(self.events.clone());
+        self.update_tally(|tally| tally.total = modules.len());
+
+        let report = bumper.bump(&modules)?;
+        let tags = report.tags();
+        self.update_tally(|tally| {
+            tally.succeeded = tags.len();
+            tally.skipped = report.unchanged();
+        });
+        self.print_result(tags.iter().map(|tag| format!("{}\n", tag)).collect::<String>());
+        if !tags.is_empty() && !report.pushed {
+            log.summary(format!("Created {} tags locally; push them with --push.", tags.len()));
+        } else {
+            log.summary(format!("Created {} tags.", tags.len()));
+        }
+        Ok(())
     }
 
-    Ok(output)
-}
-
-fn replace_module_name(content: &str, module_name: &str) -> String {
-    let pascal_case_name = to_pascal_case(module_name);
-    let camel_case_name = to_camel_case(module_name);
-
-    let re_pascal = Regex::new(r"\{\{\s*\.\s*module_name\s*\}\}").unwrap();
-    let re_camel = Regex::new(r"\{\{\s*\.\s*module_name_camel\s*\}\}").unwrap();
-    let re_lowercase = Regex::new(r"\{\{\s*\.\s*module_name_lowercase\s*\}\}").unwrap();
+    /// Bumps versions the way --bump and --preid ask for, for `bump` and the `status` preview.
+    fn bumper(&self, repo: &Repo, runner: Arc<dyn CommandRunner>) -> Result<Bumper> {
+        let config = ReleaseConfig::load(repo)?;
+        Ok(Bumper::new(Releases::new(repo.clone(), runner), config).with_level(self.args.bump).with_preid(&self.args.preid))
+    }
 
-    let content = re_pascal.replace_all(content, &pascal_case_name);
-    let content = re_camel.replace_all(&content, &camel_case_name);
-    re_lowercase.replace_all(&content, module_name).to_string()
-}
+    /// Add the entry of a release tag of a module to its CHANGELOG.md, or print its notes.
+    fn write_changelog(&self) -> Result<()> {
+        let log = &self.logger;
+        let name = match (&self.args.modules[..], &self.args.tag) {
+            ([module], _) => module.trim_end_matches('/'),
+            ([], Some(tag)) => tag.rsplit_once("/v").map_or(tag.as_str(), |(module, _)| module),
+            ([], None) => return Err(Error::Usage("module name is required for 'changelog' task".to_string())),
+            _ => return Err(Error::Usage("changelog takes a single --module".to_string())),
+        };
+        let repo = self.discover_repo()?;
+        let Some(module) = repo.module(name) else {
+            return Err(Error::Usage(format!("no dagger.json found for module {:?}", name)));
+        };
+        let config = ReleaseConfig::load(&repo)?;
+        let releases = Releases::new(repo.clone(), self.runner(&repo));
+
+        let release = ReleaseEntry::read(&releases, &config, &module.name, self.args.tag.as_deref())?;
+        let previous = release.previous.as_deref().unwrap_or("the first commit");
+        log.info(format!("{}: {} commits from {} to {}", module.name, release.commits, previous, release.tag));
+        self.events.emit(Event::ReleaseNotes {
+            module: module.name.clone(),
+            tag: release.tag.clone(),
+            version: release.version.to_string(),
+            notes: release.notes().to_string(),
+        });
+        self.update_tally(|tally| tally.total = 1);
+        if self.args.notes {
+            self.print_result(release.notes());
+            self.update_tally(|tally| tally.succeeded = 1);
+            return Ok(());
+        }
 
-fn capitalize_module_name(module_name: &str) -> String {
-    let mut chars = module_name.chars();
-    match chars.next() {
-        None => String::new(),
-        Some(first) => first.to_uppercase().collect::<String>() + chars.as_str(),
+        let changelog = format!("{}/{}", module.name, CHANGELOG_FILE);
+        match release.write(&module.path)? {
+            ChangelogUpdate::Present(_) => {
+                log.summary(format!("{} already has an entry for {}.", changelog, release.version));
+                self.update_tally(|tally| tally.skipped = 1);
+            }
+            ChangelogUpdate::Added(path) => {
+                self.events.emit(Event::FileWritten { path });
+                self.update_tally(|tally| tally.succeeded = 1);
+                log.summary(format!("Added {} to {}{}", release.version, changelog, log.emoji(" ✅")));
+            }
+        }
+        Ok(())
     }
-}
 
+    /// Print the release state of every selected parent module.
+    fn release_status(&self) -> Result<()> {
+        let log = &self.logger;
+        let repo = self.discover_repo()?;
+        let runner = self.runner(&repo);
+        let modules = self.select_parents(&repo, runner.as_ref())?;
+        let bumper = self.bumper(&repo, runner.clone())?;
+
+        let report = StatusReport::read(&repo, runner.as_ref(), &bumper, &modules)?;
+        let statuses = &report.statuses;
+        for status in statuses {
+            for problem in status.problems() {
+                log.warn(format!("{}: {}", status.module, problem));
+            }
+            self.events.emit(Event::ReleaseStatus(status.clone()));
+        }
+        match self.args.format.unwrap_or(ListFormat::Table) {
+            ListFormat::Names => self.print_result(statuses.iter().map(|status| format!("{}\n", status.module)).collect::<String>()),
+            ListFormat::Table => self.print_result(table(statuses)),
+            ListFormat::Json => self.print_result(serde_json::to_string(statuses).expect("module statuses serialize to JSON") + "\n"),
+        }
+        self.update_tally(|tally| {
+            tally.total = statuses.len();
+            tally.succeeded = statuses.len();
+        });
+        log.summary(format!("{} modules: {} with unreleased commits, {} flagged.", statuses.len(), report.unreleased(), report.flagged()));
+        Ok(())
+    }
 
-fn update_readme_content(module_cfg: &NewDaggerModule) -> Result<(), Error> {
-    let readme_path = format!("{}/README.md", module_cfg.path);
-    println!("Updating README.md content 📄: {}", module_cfg.name);
+    /// Publish the release tag given with --tag, or the latest release of every selected
+    /// parent module, printing the plan first.
+    fn publish_modules(&self) -> Result<()> {
+        let log = &self.logger;
+        let repo = self.discover_repo()?;
+        let runner = self.runner(&repo);
+        let mut toolchains = self.toolchains();
+        if toolchains.len() > 1 {
+            return Err(Error::Usage("publish runs with a single --dagger-bin".to_string()));
+        }
+        let toolchain = toolchains.remove(0);
+        let publisher = Publisher::new(repo.clone(), runner.clone())
+            .with_toolchain(toolchain.clone())
+            .with_address(&self.args.address)
+            .with_logger(self.logger)
+            .with_events(self.events.clone());
+
+        let plan = match &self.args.tag {
+            Some(tag) => PublishPlan { entries: vec![publisher.plan_tag(tag)?], unreleased: Vec::new() },
+            None => {
+                let modules: Vec<Module> =
+                    self.select_modules(&repo, runner.as_ref())?.into_iter().filter(|m| ModuleKind::of(m) == ModuleKind::Parent).collect();
+                publisher.plan(&modules)?
+            }
+        };
+        let entries = &plan.entries;
+        self.update_tally(|tally| {
+            tally.total = entries.len() + plan.unreleased.len();
+            tally.skipped = plan.unreleased.len();
+        });
+        for entry in entries {
+            log.info(format!("Plan: publish {} {} ({})", entry.module, entry.version, &entry.commit[..entry.commit.len().min(7)]));
+            self.events.emit(Event::PublishPlanned(entry.clone()));
+        }
+        match self.args.format.unwrap_or(ListFormat::Names) {
+            ListFormat::Names | ListFormat::Table => {
+                self.print_result(entries.iter().map(|entry| format!("{} {} {}\n", entry.module, entry.version, entry.commit)).collect::<String>())
+            }
+            ListFormat::Json => self.print_result(serde_json::to_string(entries).expect("the publish plan serializes to JSON") + "\n"),
+        }
+        if self.args.plan {
+            self.update_tally(|tally| tally.succeeded = entries.len());
+            log.summary(format!("{} modules to publish.", entries.len()));
+            return Ok(());
+        }
 
-    if !Path::new(&readme_path).exists() {
-        return Err(Error::new(ErrorKind::NotFound, format!("README.md file not found in {}", module_cfg.path)));
+        if !entries.is_empty() {
+            self.preflight(runner, &repo, &toolchain, &[Tool::Git, Tool::Dagger], &[])?;
+        }
+        let mut report = publisher.publish_all(entries);
+        let interrupted = report.interrupted.take();
+        let failed = report.failed();
+        self.update_tally(|tally| {
+            tally.succeeded = report.published();
+            tally.failed = failed.len();
+            tally.skipped += report.pending.len();
+        });
+        let names = |entries: &[&PublishEntry]| entries.iter().map(|entry| format!("{} {}", entry.module, entry.version)).collect::<Vec<_>>();
+        if let Some(e) = interrupted {
+            let pending: Vec<&PublishEntry> = report.pending.iter().collect();
+            log.summary(format!("Interrupted{} after publishing {} of {} modules.", log.emoji(" ⛔"), report.published(), entries.len()));
+            log.summary(format!("Unpublished modules: {}", names(&pending).join(", ")));
+            return Err(e);
+        }
+        if !plan.unreleased.is_empty() {
+            log.summary(format!("Skipped {} unreleased modules{}: {}", plan.unreleased.len(), log.emoji(" ⏭️"), plan.unreleased.join(", ")));
+        }
+        if failed.is_empty() {
+            log.summary(format!("Published {} modules.", report.published()));
+            return Ok(());
+        }
+        log.summary(format!(
+            "Published {} modules{}, {} failed{}: {}",
+            report.published(),
+            log.emoji(" ✅"),
+            failed.len(),
+            log.emoji(" ❌"),
+            names(&failed).join(", ")
+        ));
+        Err(Error::PublishFailed { failed: failed.len(), total: entries.len() })
     }
 
-    let readme_content = fs::read_to_string(&readme_path)?;
-    let new_content = replace_module_name_smart(&readme_content, &module_cfg.name);
-    fs::write(&readme_path, new_content)?;
-
-    Ok(())
-}
+    // Develop `name` and its child modules whenever their sources change, until Ctrl-C.
+    fn watch_module(&self, name: &str) -> Result<()> {
+        let log = &self.logger;
+        let [toolchain] = &self.toolchains()[..] else {
+            return Err(Error::Usage("--watch runs with a single --dagger-bin".to_string()));
+        };
+        let repo = self.discover_repo()?;
+        let Some(module) = repo.module(name) else {
+            return Err(Error::Usage(format!("no dagger.json found for module {:?} to watch", name)));
+        };
+        let modules: Vec<Module> = std::iter::once(module.clone())
+            .chain(["tests", "examples/go"].iter().filter_map(|child| repo.module(&format!("{}/{}", name, child))))
+            .collect();
+
+        let runner = self.runner(&repo);
+        self.preflight(runner.clone(), &repo, toolchain, &[Tool::Git, Tool::Dagger], &modules)?;
+        let scaffolder = Scaffolder::new(repo.clone()).with_runner(runner).with_toolchain(toolchain.clone());
+        let developer = Developer::new(repo.clone())
+            .with_engine(Engine { label: toolchain.dagger().to_string(), scaffolder })
+            .with_logger(self.logger)
+            .with_events(self.events.clone());
+        let mut watcher = Watcher::new(&module.path)?.with_debounce(self.args.debounce).with_cancellation(self.cancellation.clone());
+
+        let names: Vec<&str> = modules.iter().map(|m| m.name.as_str()).collect();
+        log.info(format!("Watching {} files of {}{} (Ctrl-C to stop)", watcher.snapshot().files().count(), names.join(", "), log.emoji(" 👀")));
+
+        // Develop may rewrite the dagger.json files; anything else saved during a cycle
+        // starts the next one.
+        let rewritten: Vec<PathBuf> = modules.iter().map(|m| dagger_json::path(&m.path)).collect();
+        let mut cycles = 0;
+        loop {
+            cycles += 1;
+            if !self.develop_cycle(cycles, &developer, &modules) {
+                break;
+            }
+            watcher.accept(&rewritten)?;
+            match watcher.wait()? {
+                Some(changed) => {
+                    let changed: Vec<String> =
+                        changed.iter().map(|path| path.strip_prefix(repo.root()).unwrap_or(path).display().to_string()).collect();
+                    log.info(format!("Changed: {}", changed.join(", ")));
+                }
+                None => break,
+            }
+        }
 
-fn replace_module_name_smart(content: &str, module_name: &str) -> String {
-    let pascal_case_name = to_pascal_case(module_name);
-    let lowercase_name = module_name.to_lowercase();
+        log.summary(format!("Stopped watching {} after {} cycles.", name, cycles));
+        Ok(())
+    }
 
-    let re = Regex::new(r"```[\s\S]*?```|`[^`\n]+`|\{\{\s*\.\s*module_name\s*\}\}").unwrap();
+    /// Develop `modules` in order, stopping at the first failure, and print one line with the
+    /// outcome. Returns false once interrupted.
+    fn develop_cycle(&self, cycle: usize, developer: &Developer, modules: &[Module]) -> bool {
+        let started = Instant::now();
+        let run = developer.develop_until_failure(modules);
+        let report = &run.report;
+        self.update_tally(|tally| {
+            tally.total += report.outcomes.len();
+            tally.succeeded += report.succeeded();
+            tally.failed += report.failed();
+            tally.skipped += report.skipped();
+        });
+        if run.interrupted.is_some() {
+            return false;
+        }
 
-    re.replace_all(content, |caps: &regex::Captures| {
-        let matched = caps.get(0).unwrap().as_str();
-        if matched.starts_with("```") || matched.starts_with("`") {
-            // Inside code blocks, use lowercase with hyphens
-            matched.replace("{{.module_name}}", &lowercase_name)
+        let results: Vec<String> = report
+            .outcomes
+            .iter()
+            .map(|outcome| match outcome.status {
+                DevelopStatus::Succeeded => format!("{} ok", outcome.module.name),
+                DevelopStatus::Failed(_) | DevelopStatus::Drifted(_) => format!("{} FAILED", outcome.module.name),
+                DevelopStatus::Skipped | DevelopStatus::Unchanged => format!("{} skipped", outcome.module.name),
+            })
+            .chain(run.pending.iter().map(|module| format!("{} not run", module.name)))
+            .collect();
+        let elapsed = started.elapsed().as_secs_f64();
+        if report.failed() > 0 {
+            self.logger.summary(format!("{}Cycle {} failed in {:.1}s: {}", self.logger.emoji("❌ "), cycle, elapsed, results.join(", ")));
         } else {
-            // Outside code blocks, use PascalCase without hyphens
-            matched.replace("{{.module_name}}", &pascal_case_name)
+            self.logger.summary(format!("{}Cycle {} passed in {:.1}s: {}", self.logger.emoji("✅ "), cycle, elapsed, results.join(", ")));
         }
-    }).to_string()
-}
+        true
+    }
 
-fn to_camel_case(s: &str) -> String {
-    s.split('-')
-        .enumerate()
-        .map(|(i, part)| {
-            if i == 0 {
-                part.to_lowercase()
-            } else {
-                capitalize_module_name(part)
-            }
-        })
-        .collect()
-}
+    /// The modules of `repo` picked by the selection flags.
+    fn select_modules(&self, repo: &Repo, runner: &dyn CommandRunner) -> Result<Vec<Module>> {
+        let args = &self.args;
+        let kinds = [(args.only_parents, ModuleKind::Parent), (args.only_tests, ModuleKind::Tests), (args.only_examples, ModuleKind::Examples)]
+            .into_iter()
+            .filter_map(|(only, kind)| only.then_some(kind));
+        let selection = Selection::default()
+            .with_modules(args.modules.iter().cloned())
+            .with_excludes(args.excludes.iter().cloned())
+            .with_kinds(kinds)
+            .with_changed_since(args.changed_since.clone());
+
+        let modules = repo.find_dagger_modules()?;
+        if selection.is_everything() {
+            return Ok(modules);
+        }
+        let total = modules.len();
+        let selected = selection.select(repo, runner, modules)?;
+        self.logger.info(format!("Selected {} of {} modules", selected.len(), total));
+        Ok(selected)
+    }
 
-fn to_pascal_case(s: &str) -> String {
-    s.split('-')
-        .map(capitalize_module_name)
-        .collect()
-}
+    /// The parent modules of the selected modules, for the release tasks: tests and examples are
+    /// released with their parent, so selecting them, or changing them, selects the parent.
+    fn select_parents(&self, repo: &Repo, runner: &dyn CommandRunner) -> Result<Vec<Module>> {
+        let modules = repo.find_dagger_modules()?;
+        let mut parents: Vec<Module> = self.select_modules(repo, runner)?.iter().map(|module| parent_of(&modules, module).clone()).collect();
+        parents.sort_by(|a, b| a.name.cmp(&b.name));
+        parents.dedup_by(|a, b| a.name == b.name);
+        Ok(parents)
+    }
 
-fn run_go_fmt(module_path: &str) -> Result<(), Error> {
-    run_command_with_output("go fmt ./...", module_path)?;
-    Ok(())
+    /// The GitHub step summary and the JUnit report of a develop run.
+    fn write_develop_reports(&self, report: &DevelopReport, pending: &[Module]) -> Result<()> {
+        self.github.append_summary(&github::develop_summary(report, pending))?;
+        if let Some(path) = &self.args.junit {
+            TestSuite::from_develop(report, pending).write(path)?;
+            self.logger.info(format!("JUnit report written to {}", path.display()));
+        }
+        Ok(())
+    }
 }
 
-fn find_dagger_modules() -> Result<Vec<String>, Error> {
-    let output = Command::new("find")
-        .args(&[".", "-type", "f", "-name", "dagger.json"])
-        .output()?;
-
-    if !output.status.success() {
-        return Err(Error::new(ErrorKind::Other, "Failed to execute find command"));
+/// Expand a leading `~/` to the home directory, for binaries given as `--dagger-bin=~/bin/dagger`.
+fn expand_home(path: &str) -> String {
+    match (path.strip_prefix("~/"), std::env::var("HOME")) {
+        (Some(rest), Ok(home)) => format!("{}/{}", home.trim_end_matches('/'), rest),
+        _ => path.to_string(),
     }
-
-    let modules = String::from_utf8_lossy(&output.stdout)
-        .lines()
-        .map(|line| Path::new(line).parent().unwrap().to_string_lossy().into_owned())
-        .collect::<Vec<String>>();
-
-    Ok(modules)
 }
 
-fn run_dagger_develop(dir: &str) -> Result<(), Error> {
-    let output = Command::new("dagger")
-        .arg("develop")
-        .current_dir(dir)
-        .stdout(Stdio::inherit())
-        .stderr(Stdio::inherit())
-        .output()?;
-
-    if !output.status.success() {
-        return Err(Error::new(ErrorKind::Other, format!("dagger develop failed in directory: {}", dir)));
+fn list_or_none(items: &[&str]) -> String {
+    if items.is_empty() {
+        "none".to_string()
+    } else {
+        items.join(", ")
     }
-
-    Ok(())
 }
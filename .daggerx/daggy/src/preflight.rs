@@ -0,0 +1,132 @@
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use crate::command::{Cmd, CommandRunner};
+use crate::dagger_json;
+use crate::error::{Error, Result};
+use crate::repo::Module;
+use crate::toolchain::{Tool, Toolchain};
+use crate::version::Version;
+
+/// Oldest dagger CLI the templates are known to work with; the CI template tests v0.12.0 and up.
+pub const DEFAULT_MIN_DAGGER_VERSION: Version = Version::new(0, 12, 0);
+
+fn version_args(tool: Tool) -> &'static [&'static str] {
+    match tool {
+        Tool::Git => &["--version"],
+        Tool::Dagger | Tool::Go => &["version"],
+    }
+}
+
+/// An installed tool and its version.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InstalledTool {
+    pub tool: Tool,
+    pub version: Version,
+}
+
+/// Checks that the tools a task needs are installed and recent enough, before the task
+/// touches anything.
+#[derive(Debug, Clone)]
+pub struct Preflight {
+    runner: Arc<dyn CommandRunner>,
+    cwd: PathBuf,
+    toolchain: Toolchain,
+    min_dagger_version: Option<Version>,
+}
+
+impl Preflight {
+    /// Run the version commands through `runner` inside `cwd`, usually the repository root.
+    pub fn new(runner: Arc<dyn CommandRunner>, cwd: impl Into<PathBuf>) -> Self {
+        Self { runner, cwd: cwd.into(), toolchain: Toolchain::default(), min_dagger_version: Some(DEFAULT_MIN_DAGGER_VERSION) }
+    }
+
+    /// Check the binaries of `toolchain` instead of the ones on PATH.
+    pub fn with_toolchain(mut self, toolchain: Toolchain) -> Self {
+        self.toolchain = toolchain;
+        self
+    }
+
+    /// Require at least `version` of the dagger CLI; `None` accepts any version.
+    pub fn with_min_dagger_version(mut self, version: Option<Version>) -> Self {
+        self.min_dagger_version = version;
+        self
+    }
+
+    /// Check `tools`, and that the dagger CLI is not older than the `engineVersion` of any of
+    /// `modules`.
+    pub fn check(&self, tools: &[Tool], modules: &[Module]) -> Result<Vec<InstalledTool>> {
+        let mut installed = Vec::new();
+        for tool in tools {
+            let version = self.version_of(*tool)?;
+            if *tool == Tool::Dagger {
+                self.check_dagger(version, modules)?;
+            }
+            installed.push(InstalledTool { tool: *tool, version });
+        }
+        Ok(installed)
+    }
+
+    /// Run the version command of `tool` and parse its output.
+    pub fn version_of(&self, tool: Tool) -> Result<Version> {
+        let program = self.toolchain.program(tool);
+        let cmd = Cmd::new(program, &self.cwd).args(version_args(tool).iter().copied()).capture_output();
+        let output = self.runner.run_checked(&cmd)?;
+        let text = format!("{}{}", String::from_utf8_lossy(&output.stdout), String::from_utf8_lossy(&output.stderr));
+        Version::find_in(&text).ok_or_else(|| Error::ToolVersion {
+            tool: program.to_string(),
+            reason: format!("could not find a version in the output of `{}`: {:?}", cmd, text.trim()),
+        })
+    }
+
+    fn check_dagger(&self, version: Version, modules: &[Module]) -> Result<()> {
+        if let Some(minimum) = self.min_dagger_version.filter(|minimum| version < *minimum) {
+            return Err(Error::ToolVersion {
+                tool: self.toolchain.dagger().to_string(),
+                reason: format!(
+                    "{} is older than the required minimum {}; install a newer dagger CLI \
+                     (https://docs.dagger.io/install) or lower --min-dagger-version",
+                    version, minimum
+                ),
+            });
+        }
+
+        match newest_engine_version(modules)? {
+            Some((engine, module)) if version < engine => Err(Error::ToolVersion {
+                tool: self.toolchain.dagger().to_string(),
+                reason: format!(
+                    "{} is older than the engineVersion {} of {}/dagger.json; upgrade the dagger CLI to {} or newer",
+                    version, engine, module.name, engine
+                ),
+            }),
+            _ => Ok(()),
+        }
+    }
+}
+
+/// The `engineVersion` of the dagger.json in `module_dir`, if it declares one.
+pub fn engine_version(module_dir: &Path) -> Result<Option<Version>> {
+    let json = dagger_json::read(module_dir)?;
+    let Some(value) = json.get("engineVersion") else { return Ok(None) };
+    let parsed = value.as_str().map(str::parse::<Version>);
+    match parsed {
+        Some(Ok(version)) => Ok(Some(version)),
+        _ => Err(Error::DaggerJson {
+            path: dagger_json::path(module_dir),
+            reason: format!("engineVersion {} is not a version like \"v0.12.4\"", value),
+        }),
+    }
+}
+
+/// The newest `engineVersion` declared by any of `modules`, with the module declaring it.
+pub fn newest_engine_version(modules: &[Module]) -> Result<Option<(Version, &Module)>> {
+    let mut newest: Option<(Version, &Module)> = None;
+    for module in modules {
+        if let Some(engine) = engine_version(&module.path)? {
+            if newest.is_none_or(|(newest, _)| engine > newest) {
+                newest = Some((engine, module));
+            }
+        }
+    }
+    Ok(newest)
+}
@@ -0,0 +1,266 @@
+use std::fs;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use serde::Serialize;
+use serde_json::Value;
+
+use crate::command::{Cmd, CommandRunner};
+use crate::error::{Error, IoResultExt, Result, ResultExt};
+use crate::event::{millis, ErrorDetails, Event, EventSink};
+use crate::log::Logger;
+use crate::release::{ModuleTags, ReleaseVersion, Releases};
+use crate::repo::{Module, Repo};
+use crate::toolchain::Toolchain;
+
+/// Where the modules of this repository are published, as `<address>/<module>@<version>`.
+pub const DEFAULT_ADDRESS: &str = "github.com/Excoriate/daggerverse";
+
+/// Where tags are checked out to be published, relative to the repository root.
+const WORKTREE_DIR: &str = ".daggerx/cache/publish";
+
+/// One module version to publish.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct PublishEntry {
+    pub module: String,
+    pub version: String,
+    pub tag: String,
+    /// The full hash of the commit the tag points at.
+    pub commit: String,
+}
+
+impl PublishEntry {
+    /// The daggerverse reference of this version under `address`.
+    pub fn reference(&self, address: &str) -> String {
+        format!("{}/{}@{}", address.trim_end_matches('/'), self.module, self.version)
+    }
+}
+
+/// The module versions to publish, and the modules left out because they were never released.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct PublishPlan {
+    pub entries: Vec<PublishEntry>,
+    pub unreleased: Vec<String>,
+}
+
+/// How publishing one module version went.
+#[derive(Debug)]
+pub enum PublishStatus {
+    /// Published as this reference, `<address>/<module>@<version>`.
+    Published(String),
+    Failed(Error),
+}
+
+#[derive(Debug)]
+pub struct PublishOutcome {
+    pub entry: PublishEntry,
+    pub status: PublishStatus,
+    pub elapsed: Duration,
+}
+
+/// How publishing the entries of a plan ended.
+#[derive(Debug, Default)]
+pub struct PublishReport {
+    pub outcomes: Vec<PublishOutcome>,
+    /// Entries that were not reached because of an interruption, in order.
+    pub pending: Vec<PublishEntry>,
+    /// The cancellation that stopped the run early.
+    pub interrupted: Option<Error>,
+}
+
+impl PublishReport {
+    pub fn published(&self) -> usize {
+        self.outcomes.iter().filter(|outcome| matches!(outcome.status, PublishStatus::Published(_))).count()
+    }
+
+    /// The entries that failed to publish, in order.
+    pub fn failed(&self) -> Vec<&PublishEntry> {
+        self.outcomes.iter().filter(|outcome| matches!(outcome.status, PublishStatus::Failed(_))).map(|outcome| &outcome.entry).collect()
+    }
+}
+
+/// Split a `<module>/v<version>` release tag into its module and version.
+pub fn parse_tag(tag: &str) -> Result<(String, ReleaseVersion)> {
+    let invalid = || Error::Usage(format!("{:?} is not a release tag, expected e.g. alpha/v1.2.3", tag));
+    let (module, version) = tag.rsplit_once("/v").ok_or_else(invalid)?;
+    let version: ReleaseVersion = format!("v{}", version).parse().map_err(|_| invalid())?;
+    if module.is_empty() {
+        return Err(invalid());
+    }
+    Ok((module.to_string(), version))
+}
+
+/// Plans and runs `dagger publish` for release tags of the repository's modules.
+pub struct Publisher {
+    repo: Repo,
+    runner: Arc<dyn CommandRunner>,
+    releases: Releases,
+    toolchain: Toolchain,
+    address: String,
+    logger: Logger,
+    events: EventSink,
+}
+
+impl Publisher {
+    pub fn new(repo: Repo, runner: Arc<dyn CommandRunner>) -> Self {
+        let releases = Releases::new(repo.clone(), runner.clone());
+        Self {
+            repo,
+            runner,
+            releases,
+            toolchain: Toolchain::new(),
+            address: DEFAULT_ADDRESS.to_string(),
+            logger: Logger::silent(),
+            events: EventSink::disabled(),
+        }
+    }
+
+    pub fn with_toolchain(mut self, toolchain: Toolchain) -> Self {
+        self.toolchain = toolchain;
+        self
+    }
+
+    pub fn with_address(mut self, address: impl Into<String>) -> Self {
+        self.address = address.into();
+        self
+    }
+
+    pub fn with_logger(mut self, logger: Logger) -> Self {
+        self.logger = logger;
+        self
+    }
+
+    pub fn with_events(mut self, events: EventSink) -> Self {
+        self.events = events;
+        self
+    }
+
+    pub fn releases(&self) -> &Releases {
+        &self.releases
+    }
+
+    pub fn address(&self) -> &str {
+        &self.address
+    }
+
+    /// The entry publishing the release tag `tag`.
+    pub fn plan_tag(&self, tag: &str) -> Result<PublishEntry> {
+        let (module, version) = parse_tag(tag)?;
+        self.entry(&module, &version, tag)
+    }
+
+    /// The entries publishing the newest release of every module of `modules`, in order.
+    pub fn plan(&self, modules: &[Module]) -> Result<PublishPlan> {
+        let mut plan = PublishPlan::default();
+        for module in modules {
+            let tags = self.releases.tags(&module.name)?;
+            for tag in &tags.malformed {
+                self.logger.warn(format!("Ignoring malformed tag {}", tag));
+            }
+            match self.plan_latest(&module.name, &tags)? {
+                Some(entry) => plan.entries.push(entry),
+                None => {
+                    self.logger.info(format!("Skipped {}{}: not released yet", self.logger.emoji("⏭️ "), module.name));
+                    self.events.emit(Event::PublishSkipped { module: module.name.clone() });
+                    plan.unreleased.push(module.name.clone());
+                }
+            }
+        }
+        Ok(plan)
+    }
+
+    /// The entry publishing the newest release of `module`, leaving prereleases out, or `None`
+    /// when it has not been released yet.
+    pub fn plan_latest(&self, module: &str, tags: &ModuleTags) -> Result<Option<PublishEntry>> {
+        match tags.versions.iter().rev().find(|(version, _)| version.pre.is_none()) {
+            Some((version, tag)) => self.entry(module, version, tag).map(Some),
+            None => Ok(None),
+        }
+    }
+
+    /// Check that `tag`, releasing `version` of `module`, exists and that the module
+    /// directory at that tag holds a dagger.json naming the module.
+    fn entry(&self, module: &str, version: &ReleaseVersion, tag: &str) -> Result<PublishEntry> {
+        let release = |reason: String| Error::Release { module: module.to_string(), reason };
+        let commit = self.releases.tag_commit(tag)?.ok_or_else(|| release(format!("no release tag {}", tag)))?;
+
+        let path = format!("{}/dagger.json", module);
+        let content = self
+            .releases
+            .file_at(&commit, &path)
+            .with_context(|| format!("reading {} at {}", path, tag))?
+            .ok_or_else(|| release(format!("{} has no module directory {} with a dagger.json", tag, module)))?;
+        let json: Value = serde_json::from_str(&content)
+            .map_err(|e| Error::DaggerJson { path: PathBuf::from(&path), reason: format!("failed to parse at {}: {}", tag, e) })?;
+        let expected = module.rsplit('/').next().unwrap_or(module);
+        match json["name"].as_str() {
+            Some(name) if name == expected => {}
+            name => return Err(release(format!("the dagger.json at {} names the module {:?}, not {:?}", tag, name.unwrap_or_default(), expected))),
+        }
+
+        Ok(PublishEntry { module: module.to_string(), version: version.to_string(), tag: tag.to_string(), commit })
+    }
+
+    /// Run `dagger publish` for `entry` in a checkout of its commit, leaving the working tree
+    /// alone. Returns the published reference.
+    pub fn publish(&self, entry: &PublishEntry) -> Result<String> {
+        let worktree = self.repo.root().join(WORKTREE_DIR).join(&entry.tag);
+        if worktree.exists() {
+            // Left behind by an interrupted publish.
+            fs::remove_dir_all(&worktree).at_path(&worktree)?;
+            self.git(["worktree", "prune"])?;
+        }
+        let location = worktree.to_string_lossy().into_owned();
+        self.git(["worktree", "add", "--detach", &location, &entry.commit])
+            .with_context(|| format!("checking out {} to publish it", entry.tag))?;
+
+        let reference = entry.reference(&self.address);
+        let cmd = Cmd::new(self.toolchain.dagger(), &worktree).args(["publish", "-m", &entry.module, &reference]);
+        let published = self.runner.run_checked(&cmd).with_context(|| format!("module {}: dagger publish", entry.module));
+        let removed = self.git(["worktree", "remove", "--force", &location]).with_context(|| format!("removing the checkout of {}", entry.tag));
+        published?;
+        removed?;
+        Ok(reference)
+    }
+
+    /// Publish every entry of `entries`, in order. A failure is recorded and the next entry
+    /// published all the same; an interruption stops the run and leaves the rest pending.
+    pub fn publish_all(&self, entries: &[PublishEntry]) -> PublishReport {
+        let log = &self.logger;
+        let mut report = PublishReport::default();
+        for (index, entry) in entries.iter().enumerate() {
+            log.info(format!("Publishing module: {} {}", entry.module, entry.version));
+            let started = Instant::now();
+            let published = self.publish(entry);
+            let (elapsed, duration_ms) = (started.elapsed(), millis(started.elapsed()));
+            let status = match published {
+                Err(e) if matches!(e.root_cause(), Error::Cancelled) => {
+                    report.pending = entries[index..].to_vec();
+                    report.interrupted = Some(e);
+                    return report;
+                }
+                Ok(reference) => {
+                    log.success(format!("{}Published {}", log.emoji("✅ "), reference));
+                    let (module, version) = (entry.module.clone(), entry.version.clone());
+                    self.events.emit(Event::ModulePublished { module, version, reference: reference.clone(), duration_ms });
+                    PublishStatus::Published(reference)
+                }
+                Err(e) => {
+                    log.error(format!("{}Failed to publish module: {} {}", log.emoji("❌ "), entry.module, entry.version));
+                    log.error(format!("Error: {}", e));
+                    let (module, version) = (entry.module.clone(), entry.version.clone());
+                    self.events.emit(Event::PublishFailed { module, version, duration_ms, error: ErrorDetails::from(&e) });
+                    PublishStatus::Failed(e)
+                }
+            };
+            report.outcomes.push(PublishOutcome { entry: entry.clone(), status, elapsed });
+        }
+        report
+    }
+
+    fn git<const N: usize>(&self, args: [&str; N]) -> Result<()> {
+        self.runner.run_checked(&self.repo.git().args(args).capture_output())?;
+        Ok(())
+    }
+}
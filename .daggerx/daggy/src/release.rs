@@ -0,0 +1,423 @@
+use std::cmp::Ordering;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::str::FromStr;
+use std::sync::{Arc, LazyLock};
+
+use regex::Regex;
+use serde::Deserialize;
+
+use crate::changelog::ChangelogSection;
+use crate::command::CommandRunner;
+use crate::error::{Error, Result, ResultExt};
+use crate::repo::Repo;
+
+/// Where release-please is configured; daggy follows its versioning rules.
+pub const RELEASE_CONFIG: &str = "release-please-config.json";
+
+static RELEASE_VERSION: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"^v(\d+)\.(\d+)\.(\d+)(?:-([0-9A-Za-z-]+(?:\.[0-9A-Za-z-]+)*))?$").unwrap());
+
+static CONVENTIONAL: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"^(\w+)(?:\(([^)]*)\))?(!)?: (.+)$").unwrap());
+
+/// A semantic version of a released module, as found in its `<module>/vX.Y.Z[-pre]` tags.
+///
+/// Unlike [`crate::version::Version`] it keeps the prerelease, which ranks below the release.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct ReleaseVersion {
+    pub major: u64,
+    pub minor: u64,
+    pub patch: u64,
+    /// e.g. `rc.1`
+    pub pre: Option<String>,
+}
+
+impl ReleaseVersion {
+    pub const fn new(major: u64, minor: u64, patch: u64) -> Self {
+        Self { major, minor, patch, pre: None }
+    }
+
+    pub fn with_pre(mut self, pre: impl Into<String>) -> Self {
+        self.pre = Some(pre.into());
+        self
+    }
+
+    /// The version modules are bumped from before their first release.
+    pub const fn initial() -> Self {
+        Self::new(0, 0, 0)
+    }
+
+    /// The version after a `level` bump. Bumping a prerelease to the release it leads up to
+    /// drops the prerelease, so `v1.0.0-rc.2` bumps to `v1.0.0` on any level that allows it;
+    /// prerelease bumps count up `<preid>.N`, starting from the next patch release.
+    ///
+    /// Switching the prerelease identifier of a prerelease restarts at `<preid>.1`, which
+    /// fails when that would not rank above the current version, e.g. `v1.0.0-rc.2` to
+    /// `v1.0.0-beta.1`.
+    pub fn bump(&self, level: Bump, preid: &str) -> Result<Self, String> {
+        let next = self.next(level, preid);
+        if next <= *self {
+            return Err(format!("a {} bump of {} to {} would not increase the version", level, self, next));
+        }
+        Ok(next)
+    }
+
+    fn next(&self, level: Bump, preid: &str) -> Self {
+        let Self { major, minor, patch, .. } = *self;
+        let pre = self.pre.is_some();
+        match level {
+            Bump::Major if pre && minor == 0 && patch == 0 => Self::new(major, 0, 0),
+            Bump::Major => Self::new(major + 1, 0, 0),
+            Bump::Minor if pre && patch == 0 => Self::new(major, minor, 0),
+            Bump::Minor => Self::new(major, minor + 1, 0),
+            Bump::Patch if pre => Self::new(major, minor, patch),
+            Bump::Patch => Self::new(major, minor, patch + 1),
+            Bump::Prerelease => {
+                let counter = self
+                    .pre
+                    .as_deref()
+                    .and_then(|pre| pre.strip_prefix(preid)?.strip_prefix('.')?.parse::<u64>().ok());
+                match (pre, counter) {
+                    (true, Some(n)) => Self::new(major, minor, patch).with_pre(format!("{}.{}", preid, n + 1)),
+                    (true, None) => Self::new(major, minor, patch).with_pre(format!("{}.1", preid)),
+                    (false, _) => Self::new(major, minor, patch + 1).with_pre(format!("{}.1", preid)),
+                }
+            }
+        }
+    }
+}
+
+impl FromStr for ReleaseVersion {
+    type Err = String;
+
+    /// Parse `v1.2.3` or `v1.2.3-rc.1`; the `v` is required, as in tags.
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        let captures = RELEASE_VERSION.captures(value).ok_or_else(|| format!("invalid release version {:?}, expected e.g. v1.2.3", value))?;
+        let number = |i: usize| captures[i].parse::<u64>().map_err(|e| format!("invalid release version {:?}: {}", value, e));
+        Ok(Self { major: number(1)?, minor: number(2)?, patch: number(3)?, pre: captures.get(4).map(|m| m.as_str().to_string()) })
+    }
+}
+
+impl fmt::Display for ReleaseVersion {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "v{}.{}.{}", self.major, self.minor, self.patch)?;
+        match &self.pre {
+            Some(pre) => write!(f, "-{}", pre),
+            None => Ok(()),
+        }
+    }
+}
+
+impl Ord for ReleaseVersion {
+    fn cmp(&self, other: &Self) -> Ordering {
+        (self.major, self.minor, self.patch).cmp(&(other.major, other.minor, other.patch)).then_with(|| {
+            match (&self.pre, &other.pre) {
+                (None, None) => Ordering::Equal,
+                (None, Some(_)) => Ordering::Greater,
+                (Some(_), None) => Ordering::Less,
+                (Some(a), Some(b)) => compare_prerelease(a, b),
+            }
+        })
+    }
+}
+
+impl PartialOrd for ReleaseVersion {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+/// Semver precedence of prereleases: numeric identifiers rank below alphanumeric ones and
+/// compare by value, and a shorter prefix ranks first.
+fn compare_prerelease(a: &str, b: &str) -> Ordering {
+    let mut b_parts = b.split('.');
+    for a_part in a.split('.') {
+        let Some(b_part) = b_parts.next() else {
+            return Ordering::Greater;
+        };
+        let ordering = match (a_part.parse::<u64>(), b_part.parse::<u64>()) {
+            (Ok(x), Ok(y)) => x.cmp(&y),
+            (Ok(_), Err(_)) => Ordering::Less,
+            (Err(_), Ok(_)) => Ordering::Greater,
+            (Err(_), Err(_)) => a_part.cmp(b_part),
+        };
+        if ordering != Ordering::Equal {
+            return ordering;
+        }
+    }
+    if b_parts.next().is_some() {
+        Ordering::Less
+    } else {
+        Ordering::Equal
+    }
+}
+
+/// Which part of a version to increase.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Bump {
+    Major,
+    Minor,
+    Patch,
+    Prerelease,
+}
+
+impl FromStr for Bump {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value {
+            "major" => Ok(Bump::Major),
+            "minor" => Ok(Bump::Minor),
+            "patch" => Ok(Bump::Patch),
+            "prerelease" => Ok(Bump::Prerelease),
+            _ => Err(format!("invalid bump {:?}, expected major, minor, patch or prerelease", value)),
+        }
+    }
+}
+
+impl fmt::Display for Bump {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.pad(match self {
+            Bump::Major => "major",
+            Bump::Minor => "minor",
+            Bump::Patch => "patch",
+            Bump::Prerelease => "prerelease",
+        })
+    }
+}
+
+/// The versioning rules of the root package of [`RELEASE_CONFIG`].
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct ReleaseConfig {
+    /// Breaking changes bump the minor version before v1.0.0.
+    #[serde(default)]
+    pub bump_minor_pre_major: bool,
+    /// Features bump the patch version before v1.0.0.
+    #[serde(default)]
+    pub bump_patch_for_minor_pre_major: bool,
+    #[serde(default)]
+    pub changelog_sections: Vec<ChangelogSection>,
+}
+
+impl ReleaseConfig {
+    /// Load the configuration of `repo`; without a [`RELEASE_CONFIG`] the defaults apply.
+    pub fn load(repo: &Repo) -> Result<Self> {
+        #[derive(Deserialize)]
+        struct File {
+            #[serde(default)]
+            packages: std::collections::BTreeMap<String, ReleaseConfig>,
+        }
+
+        let path = repo.root().join(RELEASE_CONFIG);
+        let content = match fs::read_to_string(&path) {
+            Ok(content) => content,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(Self::default()),
+            Err(e) => return Err(Error::io(&path, e)),
+        };
+        let mut file: File =
+            serde_json::from_str(&content).map_err(|e| Error::io(&path, io::Error::new(io::ErrorKind::InvalidData, e)))?;
+        Ok(file.packages.remove(".").unwrap_or_default())
+    }
+
+    /// The configured changelog sections, or release-please's defaults.
+    pub fn sections(&self) -> Vec<ChangelogSection> {
+        if self.changelog_sections.is_empty() {
+            ChangelogSection::defaults()
+        } else {
+            self.changelog_sections.clone()
+        }
+    }
+
+    /// The bump the conventional `commits` call for, starting from `current`: major for a
+    /// breaking change, minor for a feature and patch for anything else.
+    pub fn infer_bump(&self, current: &ReleaseVersion, commits: &[Commit]) -> Bump {
+        let pre_major = current.major == 0;
+        if commits.iter().any(Commit::is_breaking) {
+            if pre_major && self.bump_minor_pre_major {
+                Bump::Minor
+            } else {
+                Bump::Major
+            }
+        } else if commits.iter().any(|commit| commit.conventional().is_some_and(|c| c.kind == "feat")) {
+            if pre_major && self.bump_patch_for_minor_pre_major {
+                Bump::Patch
+            } else {
+                Bump::Minor
+            }
+        } else {
+            Bump::Patch
+        }
+    }
+}
+
+/// A git commit, as read by [`Releases::commits_since`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Commit {
+    pub hash: String,
+    pub subject: String,
+    pub body: String,
+}
+
+/// The parts of a conventional commit subject, `type(scope)!: description`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Conventional<'a> {
+    pub kind: &'a str,
+    pub scope: Option<&'a str>,
+    pub breaking: bool,
+    pub description: &'a str,
+}
+
+impl Commit {
+    /// The conventional commit parts of the subject, if it follows the convention.
+    pub fn conventional(&self) -> Option<Conventional<'_>> {
+        let captures = CONVENTIONAL.captures(&self.subject)?;
+        Some(Conventional {
+            kind: captures.get(1)?.as_str(),
+            scope: captures.get(2).map(|m| m.as_str()),
+            breaking: captures.get(3).is_some(),
+            description: captures.get(4)?.as_str(),
+        })
+    }
+
+    /// Whether the commit is marked `type!:` or has a `BREAKING CHANGE:` footer.
+    pub fn is_breaking(&self) -> bool {
+        self.conventional().is_some_and(|c| c.breaking)
+            || self.body.lines().any(|line| line.starts_with("BREAKING CHANGE:") || line.starts_with("BREAKING-CHANGE:"))
+    }
+}
+
+/// The `<module>/v*` tags of a module, split into valid release versions, sorted from
+/// oldest to newest, and malformed tags.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ModuleTags {
+    pub versions: Vec<(ReleaseVersion, String)>,
+    pub malformed: Vec<String>,
+}
+
+impl ModuleTags {
+    /// The newest version and its tag.
+    pub fn latest(&self) -> Option<(&ReleaseVersion, &str)> {
+        self.versions.last().map(|(version, tag)| (version, tag.as_str()))
+    }
+}
+
+/// The name of the tag releasing `version` of `module`.
+pub fn tag_name(module: &str, version: &ReleaseVersion) -> String {
+    format!("{}/{}", module, version)
+}
+
+/// Reads and creates the release tags of a repository through git.
+pub struct Releases {
+    repo: Repo,
+    runner: Arc<dyn CommandRunner>,
+}
+
+impl Releases {
+    pub fn new(repo: Repo, runner: Arc<dyn CommandRunner>) -> Self {
+        Self { repo, runner }
+    }
+
+    fn git<I, S>(&self, args: I) -> Result<String>
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        let cmd = self.repo.git().args(args).capture_output();
+        let output = self.runner.run_checked(&cmd)?;
+        Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+    }
+
+    /// The release tags of `module`.
+    pub fn tags(&self, module: &str) -> Result<ModuleTags> {
+        let listed = self.git(["tag", "--list", &format!("{}/v*", module)]).with_context(|| format!("listing the tags of {}", module))?;
+        let mut tags = ModuleTags::default();
+        for tag in listed.lines().filter(|line| !line.is_empty()) {
+            match tag[module.len() + 1..].parse::<ReleaseVersion>() {
+                Ok(version) => tags.versions.push((version, tag.to_string())),
+                Err(_) => tags.malformed.push(tag.to_string()),
+            }
+        }
+        tags.versions.sort();
+        Ok(tags)
+    }
+
+    /// The commits touching `module` after the tag `since`, or all of them, newest first.
+    pub fn commits_since(&self, module: &str, since: Option<&str>) -> Result<Vec<Commit>> {
+        self.commits_between(module, since, "HEAD")
+    }
+
+    /// The commits touching `module` up to `to` and after `from`, or all of them, newest
+    /// first.
+    pub fn commits_between(&self, module: &str, from: Option<&str>, to: &str) -> Result<Vec<Commit>> {
+        let mut args = vec!["log".to_string(), "--format=%H%x1f%s%x1f%b%x1e".to_string()];
+        match from {
+            Some(from) => args.push(format!("{}..{}", from, to)),
+            None => args.push(to.to_string()),
+        }
+        args.extend(["--".to_string(), module.to_string()]);
+        let log = self.git(args).with_context(|| format!("reading the commits of {}", module))?;
+
+        Ok(log
+            .split('\x1e')
+            .filter_map(|record| {
+                let mut fields = record.trim_start_matches('\n').splitn(3, '\x1f');
+                let hash = fields.next().filter(|hash| !hash.is_empty())?;
+                Some(Commit {
+                    hash: hash.to_string(),
+                    subject: fields.next().unwrap_or_default().to_string(),
+                    body: fields.next().unwrap_or_default().trim().to_string(),
+                })
+            })
+            .collect())
+    }
+
+    /// The day `tag` was created, as `YYYY-MM-DD`.
+    pub fn tag_date(&self, tag: &str) -> Result<String> {
+        let date = self
+            .git(["for-each-ref", "--format=%(creatordate:short)", &format!("refs/tags/{}", tag)])
+            .with_context(|| format!("reading the date of tag {}", tag))?;
+        Ok(date.trim().to_string())
+    }
+
+    /// The commit `tag` points at, or `None` when there is no such tag.
+    pub fn tag_commit(&self, tag: &str) -> Result<Option<String>> {
+        let cmd = self.repo.git().args(["rev-parse", "--quiet", "--verify", &format!("refs/tags/{}^{{commit}}", tag)]).capture_output();
+        let output = self.runner.run(&cmd)?;
+        Ok(output.success().then(|| String::from_utf8_lossy(&output.stdout).trim().to_string()))
+    }
+
+    /// The content of `path`, relative to the repository root, at the commit `rev`, or `None`
+    /// when it does not exist there.
+    pub fn file_at(&self, rev: &str, path: &str) -> Result<Option<String>> {
+        let cmd = self.repo.git().args(["show", &format!("{}:{}", rev, path)]).capture_output();
+        let output = self.runner.run(&cmd)?;
+        Ok(output.success().then(|| String::from_utf8_lossy(&output.stdout).into_owned()))
+    }
+
+    /// Create the annotated tag `tag` at HEAD, refusing to move an existing one.
+    pub fn create_tag(&self, module: &str, tag: &str, message: &str) -> Result<()> {
+        let exists = self.repo.git().args(["rev-parse", "--quiet", "--verify", &format!("refs/tags/{}", tag)]).capture_output();
+        if self.runner.run(&exists)?.success() {
+            return Err(Error::Release { module: module.to_string(), reason: format!("tag {} already exists", tag) });
+        }
+        self.git(["tag", "--annotate", tag, "--message", message]).with_context(|| format!("creating tag {}", tag))?;
+        Ok(())
+    }
+
+    /// Push `tags` to `remote` at once; the remote takes all of them or none.
+    pub fn push_tags(&self, remote: &str, tags: &[String]) -> Result<()> {
+        let mut args = vec!["push".to_string(), "--atomic".to_string(), remote.to_string()];
+        args.extend(tags.iter().map(|tag| format!("refs/tags/{}", tag)));
+        self.git(args).with_context(|| format!("pushing {} to {}", tags.join(", "), remote))?;
+        Ok(())
+    }
+
+    /// Delete the local tags `tags`.
+    pub fn delete_tags(&self, tags: &[String]) -> Result<()> {
+        let args = ["tag".to_string(), "--delete".to_string()].into_iter().chain(tags.iter().cloned());
+        self.git(args).with_context(|| format!("deleting tags {}", tags.join(", ")))?;
+        Ok(())
+    }
+}
@@ -0,0 +1,158 @@
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::command::{Cmd, CommandRunner, SystemRunner};
+use crate::error::{Error, IoResultExt, Result, ResultExt};
+use crate::template::TemplateSet;
+use crate::toolchain::Tool;
+
+/// Directories that are never searched for dagger modules.
+pub const IGNORED_DIRS: &[&str] = &[".git", "target", "node_modules"];
+
+/// A git repository holding dagger modules, anchored at its root.
+#[derive(Debug, Clone)]
+pub struct Repo {
+    root: PathBuf,
+    /// The git binary run for the repository.
+    git: String,
+}
+
+/// A directory of the repository that contains a `dagger.json`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Module {
+    /// Path of the module relative to the repository root, e.g. `gitlab-cicd-vars/tests`.
+    pub name: String,
+    /// Absolute path of the module directory.
+    pub path: PathBuf,
+}
+
+impl Repo {
+    /// Discover the repository containing the current working directory.
+    pub fn discover() -> Result<Self> {
+        Self::discover_with(Tool::Git.name())
+    }
+
+    /// Discover the repository containing the current working directory with the git
+    /// binary `git`, which is then run for everything else too.
+    pub fn discover_with(git: &str) -> Result<Self> {
+        let cwd = env::current_dir().at_path(".")?;
+        Self::discover_in(&SystemRunner::new(), &cwd, git)
+    }
+
+    /// Discover the repository containing `dir`, asking `git` through `runner`.
+    pub fn discover_in(runner: &dyn CommandRunner, dir: &Path, git: &str) -> Result<Self> {
+        let cmd = Cmd::new(git, dir).args(["rev-parse", "--show-toplevel"]).capture_output();
+        let output = runner.run(&cmd)?;
+
+        if !output.success() {
+            return Err(Error::NotInGitRepo(dir.to_path_buf()));
+        }
+
+        Ok(Self::at(String::from_utf8_lossy(&output.stdout).trim()).with_git(git))
+    }
+
+    /// Use `root` as the repository root without consulting git.
+    pub fn at(root: impl Into<PathBuf>) -> Self {
+        Self { root: root.into(), git: Tool::Git.name().to_string() }
+    }
+
+    /// Run git from `bin` instead of the one on PATH.
+    pub fn with_git(mut self, bin: impl Into<String>) -> Self {
+        self.git = bin.into();
+        self
+    }
+
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    /// A git command run at the repository root.
+    pub fn git(&self) -> Cmd {
+        Cmd::new(&self.git, &self.root)
+    }
+
+    /// The templates shipped in `.daggerx/templates`.
+    pub fn templates(&self) -> TemplateSet {
+        TemplateSet::new(self.root.join(".daggerx/templates"))
+    }
+
+    /// Look up a single module by its name relative to the repository root.
+    pub fn module(&self, name: &str) -> Option<Module> {
+        let path = self.root.join(name);
+        path.join("dagger.json").is_file().then(|| Module { name: name.to_string(), path })
+    }
+
+    /// Files that differ between the git ref `since` and the working tree, relative to the
+    /// repository root, untracked files that are not ignored included.
+    pub fn changed_files(&self, runner: &dyn CommandRunner, since: &str) -> Result<Vec<PathBuf>> {
+        let mut files = self.diff_names(runner, &[since]).with_context(|| format!("listing the files changed since {}", since))?;
+        let cmd = self.git().args(["ls-files", "--others", "--exclude-standard", "-z"]).capture_output();
+        let output = runner.run_checked(&cmd).context("listing the untracked files")?;
+        files.extend(String::from_utf8_lossy(&output.stdout).split('\0').filter(|path| !path.is_empty()).map(PathBuf::from));
+        Ok(files)
+    }
+
+    /// Files changed on `head` since it forked from `base`, relative to the repository root.
+    /// Like a pull request, this compares against the merge base, so commits that only
+    /// landed on `base` since then are not counted.
+    pub fn changed_between(&self, runner: &dyn CommandRunner, base: &str, head: &str) -> Result<Vec<PathBuf>> {
+        self.diff_names(runner, &[&format!("{}...{}", base, head)])
+            .with_context(|| format!("listing the files changed from {} to {}", base, head))
+    }
+
+    /// Files below `dir`, relative to the repository root, with changes that are not
+    /// committed yet, untracked files included.
+    pub fn uncommitted_files(&self, runner: &dyn CommandRunner, dir: &str) -> Result<Vec<PathBuf>> {
+        let cmd = self.git().args(["status", "--porcelain", "-z", "--untracked-files=all", "--", dir]).capture_output();
+        let output = runner.run_checked(&cmd).with_context(|| format!("listing the uncommitted changes of {}", dir))?;
+
+        // Entries are `XY <path>` terminated by NUL, with paths left unquoted; renames and
+        // copies are followed by one more entry holding the path they came from.
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        let mut entries = stdout.split('\0').filter(|entry| !entry.is_empty());
+        let mut files = Vec::new();
+        while let Some(entry) = entries.next() {
+            let (status, path) = (entry.get(..2).unwrap_or_default(), entry.get(3..).unwrap_or_default());
+            if status.contains(['R', 'C']) {
+                entries.next();
+            }
+            files.push(PathBuf::from(path));
+        }
+        Ok(files)
+    }
+
+    fn diff_names(&self, runner: &dyn CommandRunner, refs: &[&str]) -> Result<Vec<PathBuf>> {
+        let cmd = self.git().args(["diff", "--name-only"]).args(refs.iter().copied()).arg("--").capture_output();
+        let output = runner.run_checked(&cmd)?;
+        Ok(String::from_utf8_lossy(&output.stdout).lines().filter(|line| !line.is_empty()).map(PathBuf::from).collect())
+    }
+
+    /// Find every directory holding a `dagger.json`, sorted by name.
+    pub fn find_dagger_modules(&self) -> Result<Vec<Module>> {
+        let mut modules = Vec::new();
+        self.collect_modules(&self.root, &mut modules)?;
+        modules.sort_by(|a, b| a.name.cmp(&b.name));
+        Ok(modules)
+    }
+
+    fn collect_modules(&self, dir: &Path, modules: &mut Vec<Module>) -> Result<()> {
+        if dir.join("dagger.json").is_file() && dir != self.root {
+            let name = dir.strip_prefix(&self.root).unwrap_or(dir).to_string_lossy().into_owned();
+            modules.push(Module { name, path: dir.to_path_buf() });
+        }
+
+        for entry in fs::read_dir(dir).at_path(dir)? {
+            let entry = entry.at_path(dir)?;
+            if !entry.file_type().at_path(entry.path())?.is_dir() {
+                continue;
+            }
+            if IGNORED_DIRS.iter().any(|ignored| entry.file_name() == *ignored) {
+                continue;
+            }
+            self.collect_modules(&entry.path(), modules)?;
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,154 @@
+use std::sync::Arc;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crate::cancel::Cancellation;
+use crate::command::{Cmd, CommandOutput, CommandRunner, Interruption};
+use crate::error::Result;
+use crate::log::Logger;
+
+/// How the cause in the error line of the dagger CLI, `Error: <cause>`, starts when the engine
+/// could not be reached or the connection to it broke. Only the start of the cause counts: the
+/// same words further down the error chain, or on other lines, come from a module or user code
+/// that failed inside the engine, and would fail the same way again.
+const TRANSIENT_CAUSES: &[&str] = &[
+    "failed to connect to engine",
+    "error connecting to engine",
+    "connect to engine",
+    "failed to start engine",
+    "start engine",
+    "rpc error: code = unavailable",
+    "net/http: tls handshake timeout",
+];
+
+/// Dagger subcommands with effects outside the engine, which must not run twice.
+const NON_IDEMPOTENT: &[&str] = &["publish"];
+
+/// How often and how patiently transient failures are retried.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RetryPolicy {
+    /// Retries after the first attempt; `0` disables retrying.
+    pub max_retries: u32,
+    /// Wait before the first retry; doubled for every following one.
+    pub initial_backoff: Duration,
+    pub max_backoff: Duration,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self { max_retries: 2, initial_backoff: Duration::from_secs(2), max_backoff: Duration::from_secs(30) }
+    }
+}
+
+impl RetryPolicy {
+    pub fn none() -> Self {
+        Self { max_retries: 0, ..Self::default() }
+    }
+
+    /// Wait before retry number `retry` (starting at 1).
+    pub fn backoff(&self, retry: u32) -> Duration {
+        let factor = 2u32.saturating_pow(retry.saturating_sub(1));
+        self.initial_backoff.saturating_mul(factor).min(self.max_backoff)
+    }
+}
+
+/// Whether a failed command is worth retrying: dagger reports that it could not reach the
+/// engine or lost the connection to it. A command that timed out or was cancelled is not, as
+/// it would likely hang again.
+pub fn is_transient(output: &CommandOutput) -> bool {
+    if output.interrupted.is_some() || output.success() {
+        return false;
+    }
+    let stderr = String::from_utf8_lossy(&output.stderr).to_lowercase();
+    stderr
+        .lines()
+        .filter_map(|line| line.trim().strip_prefix("error: "))
+        .any(|cause| TRANSIENT_CAUSES.iter().any(|prefix| cause.starts_with(prefix)))
+}
+
+/// Wraps another runner and retries transient failures of dagger commands with exponential
+/// backoff. Other programs, such as git, and non-idempotent dagger subcommands such as
+/// `dagger publish` always run once.
+#[derive(Debug, Clone)]
+pub struct RetryingRunner {
+    inner: Arc<dyn CommandRunner>,
+    policy: RetryPolicy,
+    programs: Vec<String>,
+    cancellation: Cancellation,
+    logger: Logger,
+}
+
+impl RetryingRunner {
+    pub fn new(inner: Arc<dyn CommandRunner>, policy: RetryPolicy) -> Self {
+        Self { inner, policy, programs: vec!["dagger".to_string()], cancellation: Cancellation::new(), logger: Logger::silent() }
+    }
+
+    /// Retry the dagger binaries `programs`, e.g. every `--dagger-bin`, instead of `dagger`.
+    pub fn with_programs<I, S>(mut self, programs: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.programs = programs.into_iter().map(Into::into).collect();
+        self
+    }
+
+    /// Whether failures of `cmd` may be retried at all.
+    fn is_retryable(&self, cmd: &Cmd) -> bool {
+        let subcommand = cmd.args.first().map(String::as_str);
+        self.programs.contains(&cmd.program) && !subcommand.is_some_and(|subcommand| NON_IDEMPOTENT.contains(&subcommand))
+    }
+
+    /// Report retries through `logger`.
+    pub fn with_logger(mut self, logger: Logger) -> Self {
+        self.logger = logger;
+        self
+    }
+
+    /// Stop waiting for the next retry once `cancellation` is triggered.
+    pub fn with_cancellation(mut self, cancellation: Cancellation) -> Self {
+        self.cancellation = cancellation;
+        self
+    }
+
+    /// Sleep for `duration`, returning early with `false` when cancelled.
+    fn sleep(&self, duration: Duration) -> bool {
+        let deadline = Instant::now() + duration;
+        while Instant::now() < deadline {
+            if self.cancellation.is_cancelled() {
+                return false;
+            }
+            thread::sleep(Duration::from_millis(50).min(deadline - Instant::now()));
+        }
+        !self.cancellation.is_cancelled()
+    }
+}
+
+impl CommandRunner for RetryingRunner {
+    fn run(&self, cmd: &Cmd) -> Result<CommandOutput> {
+        let mut output = self.inner.run(cmd)?;
+        if !self.is_retryable(cmd) {
+            return Ok(output);
+        }
+        for retry in 1..=self.policy.max_retries {
+            if !is_transient(&output) {
+                break;
+            }
+            let backoff = self.policy.backoff(retry);
+            self.logger.warn(format!(
+                "Transient failure of `{}`, retrying in {:?} ({}/{}){}",
+                cmd,
+                backoff,
+                retry,
+                self.policy.max_retries,
+                self.logger.emoji(" 🔁")
+            ));
+            if !self.sleep(backoff) {
+                output.interrupted = Some(Interruption::Cancelled);
+                break;
+            }
+            output = self.inner.run(cmd)?;
+        }
+        Ok(output)
+    }
+}
@@ -0,0 +1,715 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::checkpoint::CreateState;
+use crate::command::{Cmd, CommandRunner, SystemRunner};
+use crate::dagger_json::{self, Config};
+use crate::drift::FileChange;
+use crate::error::{Error, IoResultExt, Result, ResultExt};
+use crate::event::{millis, Event, EventSink};
+use crate::log::Logger;
+use crate::repo::{Module, Repo};
+use crate::template::{self, TemplateSet};
+use crate::toolchain::Toolchain;
+use crate::version::Version;
+
+/// Go module path prefix of the modules in this repository.
+pub const DEFAULT_GO_MODULE_PREFIX: &str = "github.com/Excoriate/daggerverse";
+
+/// engineVersion of modules scaffolded without an engine when the repository pins none.
+pub const DEFAULT_ENGINE_VERSION: Version = Version::new(0, 12, 4);
+
+/// Go version of the go.mod files written when scaffolding without an engine, when the
+/// version of the go toolchain is not known.
+pub const DEFAULT_GO_VERSION: Version = Version::new(1, 22, 5);
+
+/// Paths of the code `dagger develop` generates into a Go module.
+pub const GENERATED_PATHS: [&str; 4] = ["/dagger.gen.go", "/internal/dagger", "/internal/querybuilder", "/internal/telemetry"];
+
+/// All paths of a module about to be created, anchored at the repository root.
+#[derive(Debug, Clone)]
+pub struct NewModule {
+    pub name: String,
+    pub path: PathBuf,
+    pub examples_path: PathBuf,
+    pub tests_path: PathBuf,
+    pub github_actions_workflow_path: PathBuf,
+    pub github_actions_workflow: PathBuf,
+}
+
+/// The steps `create` runs, in order.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CreateStep {
+    InitModule,
+    InitExamples,
+    InitTests,
+    CopyReadmeAndLicense,
+    UpdateReadme,
+    GenerateWorkflow,
+    GoFmt,
+}
+
+impl CreateStep {
+    pub const ALL: [CreateStep; 7] = [
+        CreateStep::InitModule,
+        CreateStep::InitExamples,
+        CreateStep::InitTests,
+        CreateStep::CopyReadmeAndLicense,
+        CreateStep::UpdateReadme,
+        CreateStep::GenerateWorkflow,
+        CreateStep::GoFmt,
+    ];
+
+    /// Stable identifier of the step, used in error contexts.
+    pub fn name(&self) -> &'static str {
+        match self {
+            CreateStep::InitModule => "init-module",
+            CreateStep::InitExamples => "init-examples",
+            CreateStep::InitTests => "init-tests",
+            CreateStep::CopyReadmeAndLicense => "copy-readme-and-license",
+            CreateStep::UpdateReadme => "update-readme",
+            CreateStep::GenerateWorkflow => "generate-workflow",
+            CreateStep::GoFmt => "go-fmt",
+        }
+    }
+
+    pub fn description(&self) -> &'static str {
+        match self {
+            CreateStep::InitModule => "Creating parent module",
+            CreateStep::InitExamples => "Creating examples module (recipes)",
+            CreateStep::InitTests => "Creating tests module (tests)",
+            CreateStep::CopyReadmeAndLicense => "Copying README.md and LICENSE files",
+            CreateStep::UpdateReadme => "Updating README.md content",
+            CreateStep::GenerateWorkflow => "Generating GitHub Actions workflow",
+            CreateStep::GoFmt => "Running go fmt and ensuring the code is formatted correctly",
+        }
+    }
+
+    /// The emoji following the description in the progress output, with its leading space.
+    pub fn emoji(&self) -> &'static str {
+        match self {
+            CreateStep::InitModule => " 📦",
+            CreateStep::InitExamples | CreateStep::CopyReadmeAndLicense | CreateStep::UpdateReadme => " 📄",
+            CreateStep::InitTests => " 🧪",
+            CreateStep::GenerateWorkflow => " 🚀",
+            CreateStep::GoFmt => " 🧹",
+        }
+    }
+}
+
+/// The optional parts of a new module; all of them are created by default.
+///
+/// Omitting one also drops the `{{if .examples}}`, `{{if .tests}}`, ... sections of the
+/// templates and its entry in the parent's dagger.json `exclude` list.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Components {
+    /// The `examples/go` module.
+    pub examples: bool,
+    /// The `tests` module.
+    pub tests: bool,
+    /// The GitHub Actions workflow `.github/workflows/ci-mod-<name>.yaml`.
+    pub workflow: bool,
+    /// The module's README.md.
+    pub readme: bool,
+}
+
+impl Default for Components {
+    fn default() -> Self {
+        Self { examples: true, tests: true, workflow: true, readme: true }
+    }
+}
+
+impl Components {
+    /// The create steps these components need, in order.
+    pub fn steps(&self) -> Vec<CreateStep> {
+        CreateStep::ALL
+            .into_iter()
+            .filter(|step| match step {
+                CreateStep::InitExamples => self.examples,
+                CreateStep::InitTests => self.tests,
+                CreateStep::UpdateReadme => self.readme,
+                CreateStep::GenerateWorkflow => self.workflow,
+                _ => true,
+            })
+            .collect()
+    }
+
+    /// Names of the omitted components, which are also the template sections to drop.
+    pub fn omitted(&self) -> Vec<&'static str> {
+        [("examples", self.examples), ("tests", self.tests), ("workflow", self.workflow), ("readme", self.readme)]
+            .into_iter()
+            .filter(|(_, included)| !included)
+            .map(|(name, _)| name)
+            .collect()
+    }
+}
+
+/// What a single create step did.
+#[derive(Debug, Clone)]
+pub struct StepReport {
+    pub step: CreateStep,
+    pub files_written: Vec<PathBuf>,
+    pub commands: Vec<String>,
+}
+
+impl StepReport {
+    fn new(step: CreateStep) -> Self {
+        Self { step, files_written: Vec::new(), commands: Vec::new() }
+    }
+
+    fn run(&mut self, runner: &dyn CommandRunner, dir: &Path, program: &str, args: &[&str]) -> Result<()> {
+        let cmd = Cmd::new(program, dir).args(args.iter().copied());
+        self.commands.push(cmd.to_string());
+        runner.run_checked(&cmd)?;
+        Ok(())
+    }
+}
+
+/// Outcome of a whole `create` run.
+#[derive(Debug, Clone)]
+pub struct CreateReport {
+    pub module: NewModule,
+    /// The steps run, in order.
+    pub steps: Vec<StepReport>,
+    /// The steps an earlier, resumed run already finished.
+    pub skipped: Vec<CreateStep>,
+}
+
+/// How running the create steps of a module ended.
+#[derive(Debug)]
+pub struct CreateRun {
+    pub report: CreateReport,
+    /// The steps not finished because of `error`, in order.
+    pub pending: Vec<CreateStep>,
+    /// The failure or cancellation that stopped the run.
+    pub error: Option<Error>,
+}
+
+impl CreateRun {
+    /// The steps finished by this or an earlier run, in order.
+    pub fn finished(&self) -> Vec<CreateStep> {
+        let mut finished = self.report.skipped.clone();
+        finished.extend(self.report.steps.iter().map(|report| report.step));
+        finished
+    }
+}
+
+/// Outcome of developing one module.
+#[derive(Debug)]
+pub enum DevelopStatus {
+    Succeeded,
+    Failed(Error),
+    /// The module has no dagger.json.
+    Skipped,
+    /// Its inputs did not change since its last successful develop, see [`crate::fingerprint`].
+    Unchanged,
+    /// Develop ran, but under `--check` it changed these files, so the committed generated
+    /// code is out of date.
+    Drifted(Vec<FileChange>),
+}
+
+/// A module together with how developing it went and how long it took.
+#[derive(Debug)]
+pub struct DevelopOutcome {
+    pub module: Module,
+    pub status: DevelopStatus,
+    pub elapsed: Duration,
+    /// The dagger engine the module was developed with, when several were tried.
+    pub engine: Option<String>,
+}
+
+impl DevelopOutcome {
+    /// The module name, with the engine in a matrix, e.g. `alpha (dagger v0.12.4)`.
+    pub fn title(&self) -> String {
+        Self::title_of(&self.module, self.engine.as_deref())
+    }
+
+    pub fn title_of(module: &Module, engine: Option<&str>) -> String {
+        match engine {
+            Some(engine) => format!("{} (dagger {})", module.name, engine),
+            None => module.name.clone(),
+        }
+    }
+}
+
+/// Outcome of developing every module of the repository.
+#[derive(Debug, Default)]
+pub struct DevelopReport {
+    pub outcomes: Vec<DevelopOutcome>,
+}
+
+impl DevelopReport {
+    pub fn succeeded(&self) -> usize {
+        self.outcomes.iter().filter(|o| matches!(o.status, DevelopStatus::Succeeded)).count()
+    }
+
+    /// Modules that failed to develop or drifted.
+    pub fn failed(&self) -> usize {
+        self.outcomes.iter().filter(|o| matches!(o.status, DevelopStatus::Failed(_) | DevelopStatus::Drifted(_))).count()
+    }
+
+    pub fn drifted(&self) -> usize {
+        self.outcomes.iter().filter(|o| matches!(o.status, DevelopStatus::Drifted(_))).count()
+    }
+
+    /// Modules that were not developed, for either reason.
+    pub fn skipped(&self) -> usize {
+        self.outcomes.iter().filter(|o| matches!(o.status, DevelopStatus::Skipped | DevelopStatus::Unchanged)).count()
+    }
+
+    pub fn unchanged(&self) -> usize {
+        self.outcomes.iter().filter(|o| matches!(o.status, DevelopStatus::Unchanged)).count()
+    }
+}
+
+/// Creates and develops dagger modules of a repository.
+#[derive(Debug, Clone)]
+pub struct Scaffolder {
+    repo: Repo,
+    templates: TemplateSet,
+    go_module_prefix: String,
+    runner: Arc<dyn CommandRunner>,
+    toolchain: Toolchain,
+    offline: bool,
+    engine_version: Version,
+    go_version: Version,
+    components: Components,
+    logger: Logger,
+    events: EventSink,
+}
+
+impl Scaffolder {
+    pub fn new(repo: Repo) -> Self {
+        let templates = repo.templates();
+        Self {
+            repo,
+            templates,
+            go_module_prefix: DEFAULT_GO_MODULE_PREFIX.to_string(),
+            runner: Arc::new(SystemRunner::new()),
+            toolchain: Toolchain::default(),
+            offline: false,
+            engine_version: DEFAULT_ENGINE_VERSION,
+            go_version: DEFAULT_GO_VERSION,
+            components: Components::default(),
+            logger: Logger::silent(),
+            events: EventSink::disabled(),
+        }
+    }
+
+    /// Run external commands through `runner` instead of spawning them directly.
+    pub fn with_runner(mut self, runner: Arc<dyn CommandRunner>) -> Self {
+        self.runner = runner;
+        self
+    }
+
+    /// Run dagger and go from the binaries of `toolchain`.
+    pub fn with_toolchain(mut self, toolchain: Toolchain) -> Self {
+        self.toolchain = toolchain;
+        self
+    }
+
+    pub fn toolchain(&self) -> &Toolchain {
+        &self.toolchain
+    }
+
+    /// Scaffold without a dagger engine: write dagger.json, go.mod and the git files of each
+    /// module directly instead of running `dagger init`, `dagger install` and `dagger develop`.
+    /// The SDK code is generated by the next `develop`.
+    pub fn with_offline(mut self, offline: bool) -> Self {
+        self.offline = offline;
+        self
+    }
+
+    pub fn is_offline(&self) -> bool {
+        self.offline
+    }
+
+    /// The `engineVersion` of the dagger.json files written without an engine.
+    pub fn with_engine_version(mut self, version: Version) -> Self {
+        self.engine_version = version;
+        self
+    }
+
+    /// The `go` directive of the go.mod files written without an engine, normally the version
+    /// of the go toolchain.
+    pub fn with_go_version(mut self, version: Version) -> Self {
+        self.go_version = version;
+        self
+    }
+
+    pub fn with_templates(mut self, templates: TemplateSet) -> Self {
+        self.templates = templates.with_disabled_sections(self.components.omitted());
+        self
+    }
+
+    /// Create only `components` of new modules.
+    pub fn with_components(mut self, components: Components) -> Self {
+        self.components = components;
+        self.templates = self.templates.with_disabled_sections(components.omitted());
+        self
+    }
+
+    pub fn components(&self) -> Components {
+        self.components
+    }
+
+    /// The create steps of a new module, in order.
+    pub fn steps(&self) -> Vec<CreateStep> {
+        self.components.steps()
+    }
+
+    /// Report the progress of create steps to `logger`.
+    pub fn with_logger(mut self, logger: Logger) -> Self {
+        self.logger = logger;
+        self
+    }
+
+    /// Report create steps starting, finishing and writing files to `events`.
+    pub fn with_events(mut self, events: EventSink) -> Self {
+        self.events = events;
+        self
+    }
+
+    pub fn with_go_module_prefix(mut self, prefix: impl Into<String>) -> Self {
+        self.go_module_prefix = prefix.into();
+        self
+    }
+
+    pub fn repo(&self) -> &Repo {
+        &self.repo
+    }
+
+    /// Validate `name` and resolve the paths of the module to create.
+    pub fn plan(&self, name: &str) -> Result<NewModule> {
+        validate_module_name(name)?;
+
+        let module = self.new_module(name);
+        if module.path.exists() {
+            return Err(Error::ModuleExists { module: module.name, path: module.path });
+        }
+        Ok(module)
+    }
+
+    /// Pick up the module `name` where a failed or interrupted `create` left it, from the
+    /// state file inside the module. The state also restores whether it runs offline.
+    pub fn resume(&self, name: &str) -> Result<(NewModule, CreateState)> {
+        validate_module_name(name)?;
+
+        let module = self.new_module(name);
+        if !CreateState::exists(&module.path) {
+            return Err(Error::Usage(format!(
+                "nothing to resume for module {:?}: {} not found",
+                name,
+                CreateState::path(&module.path).display()
+            )));
+        }
+        let state = CreateState::load(&module.path)?;
+        Ok((module, state))
+    }
+
+    /// Start creating `module`, recording its progress in a state file inside it.
+    pub fn begin(&self, module: &NewModule) -> Result<CreateState> {
+        CreateState::begin(&module.name, &module.path, self.offline, self.components)
+    }
+
+    fn new_module(&self, name: &str) -> NewModule {
+        let path = self.repo.root().join(name);
+        let workflows_path = self.repo.root().join(".github/workflows");
+        NewModule {
+            name: name.to_string(),
+            examples_path: path.join("examples/go"),
+            tests_path: path.join("tests"),
+            path,
+            github_actions_workflow: workflows_path.join(format!("ci-mod-{}.yaml", name)),
+            github_actions_workflow_path: workflows_path,
+        }
+    }
+
+    /// Create the module `name` with all of its steps.
+    pub fn create(&self, name: &str) -> Result<CreateReport> {
+        let module = self.plan(name)?;
+        let mut state = self.begin(&module)?;
+        let run = self.run_steps(module, &mut state);
+        match run.error {
+            Some(e) => Err(e),
+            None => Ok(run.report),
+        }
+    }
+
+    /// Run the steps `state` has not finished yet, in order, then remove the state file. The
+    /// first failure stops the run and leaves the state file for a later resume.
+    pub fn run_steps(&self, module: NewModule, state: &mut CreateState) -> CreateRun {
+        let log = &self.logger;
+        let steps = self.steps();
+        let mut run = CreateRun { report: CreateReport { module, steps: Vec::new(), skipped: Vec::new() }, pending: Vec::new(), error: None };
+        let module = run.report.module.clone();
+
+        for (index, step) in steps.iter().enumerate() {
+            if state.is_completed(*step) {
+                log.info(format!("Skipping finished step{}: {}", log.emoji(" ⏭️"), step.name()));
+                self.events.emit(Event::StepSkipped { module: module.name.clone(), step: step.name() });
+                run.report.skipped.push(*step);
+                continue;
+            }
+            log.info(format!("{}{}: {}", step.description(), log.emoji(step.emoji()), module.name));
+            self.events.emit(Event::StepStarted { module: module.name.clone(), step: step.name() });
+            let started = Instant::now();
+            let report = match self.run_step(&module, *step).and_then(|report| state.complete(*step).map(|_| report)) {
+                Ok(report) => report,
+                Err(e) => {
+                    run.pending = steps[index..].iter().filter(|step| !state.is_completed(**step)).copied().collect();
+                    run.error = Some(e);
+                    return run;
+                }
+            };
+            for path in &report.files_written {
+                self.events.emit(Event::FileWritten { path: path.clone() });
+            }
+            self.events.emit(Event::StepFinished { module: module.name.clone(), step: step.name(), duration_ms: millis(started.elapsed()) });
+            run.report.steps.push(report);
+        }
+
+        run.error = state.finish().err();
+        run
+    }
+
+    /// Run a single create step for `module`.
+    pub fn run_step(&self, module: &NewModule, step: CreateStep) -> Result<StepReport> {
+        let mut report = StepReport::new(step);
+        self.run_step_into(module, step, &mut report)
+            .with_context(|| format!("module {}: step {}", module.name, step.name()))?;
+        Ok(report)
+    }
+
+    fn run_step_into(&self, module: &NewModule, step: CreateStep, report: &mut StepReport) -> Result<()> {
+        match step {
+            CreateStep::InitModule => self.initialize_module(module, report)?,
+            CreateStep::InitExamples => self.initialize_examples(module, report)?,
+            CreateStep::InitTests => self.initialize_tests(module, report)?,
+            CreateStep::CopyReadmeAndLicense => self.copy_readme_and_license(module, report)?,
+            CreateStep::UpdateReadme => self.update_readme_content(module, report)?,
+            CreateStep::GenerateWorkflow => self.generate_github_actions_workflow(module, report)?,
+            CreateStep::GoFmt => {
+                let go = self.toolchain.go();
+                let mut dirs = vec![&module.path];
+                if self.components.examples {
+                    dirs.push(&module.examples_path);
+                }
+                if self.components.tests {
+                    dirs.push(&module.tests_path);
+                }
+                for dir in dirs {
+                    report.run(self.runner.as_ref(), dir, go, &["fmt", "./..."])?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn initialize_module(&self, module: &NewModule, report: &mut StepReport) -> Result<()> {
+        fs::create_dir_all(&module.path).at_path(&module.path)?;
+
+        let runner = self.runner.as_ref();
+        let (dagger, go) = (self.toolchain.dagger(), self.toolchain.go());
+        let go_module = format!("{}/{}", self.go_module_prefix, module.name);
+
+        if self.offline {
+            let config = Config::go(&module.name, self.engine_version.to_string(), self.module_excludes());
+            self.write_module_files(&module.path, &config, &go_module, report)?;
+            report.files_written.extend(self.templates.render_dir(&self.templates.module_dir(), &module.path, &module.name)?);
+            return Ok(());
+        }
+
+        // A resumed create may find the module initialized by the failed attempt.
+        if !dagger_json::path(&module.path).exists() {
+            report.run(runner, &module.path, dagger, &["init", "--sdk", "go", "--name", &module.name, "--source", "."])?;
+        }
+        report.files_written.extend(self.templates.render_dir(&self.templates.module_dir(), &module.path, &module.name)?);
+        report.files_written.push(dagger_json::set_excludes(&module.path, self.module_excludes())?);
+        report.run(runner, &module.path, go, &["mod", "edit", "-module", &go_module])?;
+        report.run(runner, &module.path, dagger, &["develop", "-m", &module.name])?;
+
+        Ok(())
+    }
+
+    /// The exclude list of the parent's dagger.json, without the modules that are not created.
+    fn module_excludes(&self) -> Value {
+        let mut excludes = dagger_json::module_excludes();
+        if let Value::Array(entries) = &mut excludes {
+            entries.retain(|entry| match entry.as_str() {
+                Some("tests") => self.components.tests,
+                Some("examples/go") => self.components.examples,
+                _ => true,
+            });
+        }
+        excludes
+    }
+
+    fn initialize_examples(&self, module: &NewModule, report: &mut StepReport) -> Result<()> {
+        let dir = &module.examples_path;
+        let templates_path = self.templates.examples_dir();
+        let runner = self.runner.as_ref();
+        let (dagger, go) = (self.toolchain.dagger(), self.toolchain.go());
+        let go_module = format!("{}/{}/examples/go", self.go_module_prefix, module.name);
+        fs::create_dir_all(dir).at_path(dir)?;
+
+        if self.offline {
+            let config = Config::go("go", self.engine_version.to_string(), dagger_json::examples_excludes())
+                .with_dependency(&module.name, "../..");
+            self.write_module_files(dir, &config, &go_module, report)?;
+            report.files_written.extend(self.templates.render_dir(&templates_path, dir, &module.name)?);
+            report.files_written.extend(template::copy_dir_all(templates_path.join("testdata/common"), dir.join("testdata/common"))?);
+            return Ok(());
+        }
+
+        if !dagger_json::path(dir).exists() {
+            report.run(runner, dir, dagger, &["init", "--sdk", "go", "--name", "go", "--source", "."])?;
+        }
+        report.files_written.extend(self.templates.render_dir(&templates_path, dir, &module.name)?);
+        report.files_written.push(dagger_json::set_excludes(dir, dagger_json::examples_excludes())?);
+        report.run(runner, dir, go, &["mod", "edit", "-module", &go_module])?;
+        report.files_written.extend(template::copy_dir_all(templates_path.join("testdata/common"), dir.join("testdata/common"))?);
+        report.run(runner, dir, dagger, &["install", "../../"])?;
+        report.run(runner, dir, dagger, &["develop", "-m", "go"])?;
+
+        Ok(())
+    }
+
+    fn initialize_tests(&self, module: &NewModule, report: &mut StepReport) -> Result<()> {
+        let dir = &module.tests_path;
+        let templates_path = self.templates.tests_dir();
+        let runner = self.runner.as_ref();
+        let (dagger, go) = (self.toolchain.dagger(), self.toolchain.go());
+        let go_module = format!("{}/{}/tests", self.go_module_prefix, module.name);
+        fs::create_dir_all(dir).at_path(dir)?;
+
+        if self.offline {
+            let config = Config::go("tests", self.engine_version.to_string(), dagger_json::tests_excludes())
+                .with_dependency(&module.name, "..");
+            self.write_module_files(dir, &config, &go_module, report)?;
+            report.files_written.extend(self.templates.render_dir(&templates_path, dir, &module.name)?);
+            report.files_written.extend(template::copy_dir_all(templates_path.join("testdata/common"), dir.join("testdata/common"))?);
+            return Ok(());
+        }
+
+        if !dagger_json::path(dir).exists() {
+            report.run(runner, dir, dagger, &["init", "--sdk", "go", "--name", "tests", "--source", "."])?;
+        }
+        report.files_written.extend(self.templates.render_dir(&templates_path, dir, &module.name)?);
+        report.files_written.push(dagger_json::set_excludes(dir, dagger_json::tests_excludes())?);
+        report.run(runner, dir, go, &["mod", "edit", "-module", &go_module])?;
+        report.files_written.extend(template::copy_dir_all(templates_path.join("testdata/common"), dir.join("testdata/common"))?);
+        report.run(runner, dir, dagger, &["install", "../"])?;
+        report.run(runner, dir, dagger, &["develop", "-m", "tests"])?;
+
+        Ok(())
+    }
+
+    /// What `dagger init` and `dagger install` leave in `dir` besides the sources: the
+    /// dagger.json, a go.mod for `go_module`, and git files that ignore the generated code.
+    fn write_module_files(&self, dir: &Path, config: &Config, go_module: &str, report: &mut StepReport) -> Result<()> {
+        report.files_written.push(dagger_json::write(dir, config)?);
+
+        let files = [
+            ("go.mod", format!("module {}\n\ngo {}\n", go_module, self.go_version.to_string().trim_start_matches('v'))),
+            (".gitignore", GENERATED_PATHS.map(|path| format!("{}\n", path)).concat()),
+            (
+                ".gitattributes",
+                GENERATED_PATHS
+                    .map(|path| {
+                        let pattern = if path.ends_with(".go") { path.to_string() } else { format!("{}/**", path) };
+                        format!("{} linguist-generated\n", pattern)
+                    })
+                    .concat(),
+            ),
+        ];
+        for (name, content) in files {
+            let path = dir.join(name);
+            fs::write(&path, content).at_path(&path)?;
+            report.files_written.push(path);
+        }
+        Ok(())
+    }
+
+    fn copy_readme_and_license(&self, module: &NewModule, report: &mut StepReport) -> Result<()> {
+        let readme_dest_path = module.path.join("README.md");
+        let license_dest_path = module.path.join("LICENSE");
+
+        fs::create_dir_all(&module.path).at_path(&module.path)?;
+
+        if self.components.readme {
+            // Replace placeholders in README.md if any
+            let readme_content = self.templates.read(&self.templates.readme())?;
+            fs::write(&readme_dest_path, readme_content.replace("[@MODULE_NAME]", &module.name)).at_path(&readme_dest_path)?;
+            report.files_written.push(readme_dest_path);
+        }
+
+        let license_content = self.templates.read(&self.templates.license())?;
+        fs::write(&license_dest_path, license_content).at_path(&license_dest_path)?;
+        report.files_written.push(license_dest_path);
+        Ok(())
+    }
+
+    fn update_readme_content(&self, module: &NewModule, report: &mut StepReport) -> Result<()> {
+        let readme_path = module.path.join("README.md");
+
+        if !readme_path.exists() {
+            return Err(Error::io(&readme_path, std::io::ErrorKind::NotFound.into()));
+        }
+
+        let readme_content = fs::read_to_string(&readme_path).at_path(&readme_path)?;
+        fs::write(&readme_path, template::replace_module_name_smart(&readme_content, &module.name)).at_path(&readme_path)?;
+
+        report.files_written.push(readme_path);
+        Ok(())
+    }
+
+    fn generate_github_actions_workflow(&self, module: &NewModule, report: &mut StepReport) -> Result<()> {
+        fs::create_dir_all(&module.github_actions_workflow_path).at_path(&module.github_actions_workflow_path)?;
+
+        let template_content = self.templates.read(&self.templates.workflow())?;
+        let new_content = template::process_template_content(&template_content, &module.name);
+        fs::write(&module.github_actions_workflow, new_content).at_path(&module.github_actions_workflow)?;
+
+        report.files_written.push(module.github_actions_workflow.clone());
+        Ok(())
+    }
+
+    /// Run `dagger develop` in a single module.
+    pub fn develop(&self, module: &Module) -> DevelopStatus {
+        if !dagger_json::path(&module.path).exists() {
+            return DevelopStatus::Skipped;
+        }
+
+        let cmd = Cmd::new(self.toolchain.dagger(), &module.path).arg("develop");
+        match self.runner.run_checked(&cmd).with_context(|| format!("module {}: dagger develop", module.name)) {
+            Ok(_) => DevelopStatus::Succeeded,
+            Err(e) => DevelopStatus::Failed(e),
+        }
+    }
+}
+
+/// Module names become directory names and Go import path segments, so only allow
+/// ASCII letters, digits, `-` and `_`, starting with a letter.
+fn validate_module_name(name: &str) -> Result<()> {
+    let reason = if name.is_empty() {
+        Some("module name cannot be empty")
+    } else if !name.starts_with(|c: char| c.is_ascii_alphabetic()) {
+        Some("module name must start with a letter")
+    } else if !name.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_') {
+        Some("module name may only contain ASCII letters, digits, '-' and '_'")
+    } else {
+        None
+    };
+
+    match reason {
+        Some(reason) => Err(Error::InvalidModuleName { name: name.to_string(), reason: reason.to_string() }),
+        None => Ok(()),
+    }
+}
@@ -0,0 +1,137 @@
+use std::collections::BTreeSet;
+use std::path::Path;
+
+use crate::command::CommandRunner;
+use crate::error::{Error, Result};
+use crate::repo::{Module, Repo};
+
+/// The role a module plays in the repository.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ModuleKind {
+    /// A published module, e.g. `gitlab-cicd-vars`.
+    Parent,
+    /// The tests module of a parent, e.g. `gitlab-cicd-vars/tests`.
+    Tests,
+    /// An examples module of a parent, e.g. `gitlab-cicd-vars/examples/go`.
+    Examples,
+}
+
+impl ModuleKind {
+    pub fn of(module: &Module) -> Self {
+        let parts: Vec<&str> = module.name.split('/').collect();
+        if parts[1..].contains(&"examples") {
+            ModuleKind::Examples
+        } else if parts.len() > 1 && parts.last() == Some(&"tests") {
+            ModuleKind::Tests
+        } else {
+            ModuleKind::Parent
+        }
+    }
+}
+
+/// Which of the repository's modules a multi-module task works on. Without any filter every
+/// module is selected.
+#[derive(Debug, Clone, Default)]
+pub struct Selection {
+    modules: Vec<String>,
+    excludes: Vec<String>,
+    kinds: Vec<ModuleKind>,
+    changed_since: Option<String>,
+}
+
+impl Selection {
+    /// Only these modules and the modules nested in them, such as their tests.
+    pub fn with_modules(mut self, modules: impl IntoIterator<Item = impl Into<String>>) -> Self {
+        self.modules = modules.into_iter().map(|name| name.into().trim_end_matches('/').to_string()).collect();
+        self
+    }
+
+    /// Leave out modules whose name matches any of these globs, see [`glob_match`].
+    pub fn with_excludes(mut self, excludes: impl IntoIterator<Item = impl Into<String>>) -> Self {
+        self.excludes = excludes.into_iter().map(Into::into).collect();
+        self
+    }
+
+    /// Only modules of these kinds; none means every kind.
+    pub fn with_kinds(mut self, kinds: impl IntoIterator<Item = ModuleKind>) -> Self {
+        self.kinds = kinds.into_iter().collect();
+        self
+    }
+
+    /// Only modules owning a file that changed since the git ref `since`.
+    pub fn with_changed_since(mut self, since: Option<String>) -> Self {
+        self.changed_since = since;
+        self
+    }
+
+    /// Whether the selection keeps every module.
+    pub fn is_everything(&self) -> bool {
+        self.modules.is_empty() && self.excludes.is_empty() && self.kinds.is_empty() && self.changed_since.is_none()
+    }
+
+    /// The selected modules among `modules`, in their order. Naming a module that does not
+    /// exist is a usage error rather than an empty selection.
+    pub fn select(&self, repo: &Repo, runner: &dyn CommandRunner, modules: Vec<Module>) -> Result<Vec<Module>> {
+        for name in &self.modules {
+            if !modules.iter().any(|module| is_within(&module.name, name)) {
+                return Err(Error::Usage(format!("no module named {:?}", name)));
+            }
+        }
+
+        let changed: Option<BTreeSet<String>> = match &self.changed_since {
+            Some(since) => {
+                let files = repo.changed_files(runner, since)?;
+                Some(files.iter().filter_map(|file| owning_module(&modules, file)).map(|module| module.name.clone()).collect())
+            }
+            None => None,
+        };
+
+        Ok(modules
+            .into_iter()
+            .filter(|module| self.modules.is_empty() || self.modules.iter().any(|name| is_within(&module.name, name)))
+            .filter(|module| !self.excludes.iter().any(|glob| glob_match(glob, &module.name)))
+            .filter(|module| self.kinds.is_empty() || self.kinds.contains(&ModuleKind::of(module)))
+            .filter(|module| changed.as_ref().is_none_or(|changed| changed.contains(&module.name)))
+            .collect())
+    }
+}
+
+/// Whether the module `name` is `parent` or nested in it.
+fn is_within(name: &str, parent: &str) -> bool {
+    name == parent || name.strip_prefix(parent).is_some_and(|rest| rest.starts_with('/'))
+}
+
+/// The innermost of `modules` containing `file`, a path relative to the repository root.
+pub fn owning_module<'a>(modules: &'a [Module], file: &Path) -> Option<&'a Module> {
+    modules
+        .iter()
+        .filter(|module| file.starts_with(&module.name))
+        .max_by_key(|module| module.name.len())
+}
+
+/// Match `text` against a glob where `*` matches within one path segment, `**` matches
+/// across segments and `?` matches a single character other than `/`.
+pub fn glob_match(pattern: &str, text: &str) -> bool {
+    let pattern: Vec<char> = pattern.chars().collect();
+    let text: Vec<char> = text.chars().collect();
+    matches_from(&pattern, &text)
+}
+
+fn matches_from(pattern: &[char], text: &[char]) -> bool {
+    match pattern {
+        [] => text.is_empty(),
+        ['*', '*', rest @ ..] => {
+            // `**/` may also stand for no directory at all, so `a/**/b` matches `a/b`.
+            if rest.first() == Some(&'/') && matches_from(&rest[1..], text) {
+                return true;
+            }
+            (0..=text.len()).any(|skip| matches_from(rest, &text[skip..]))
+        }
+        ['*', rest @ ..] => {
+            let segment = text.iter().position(|c| *c == '/').unwrap_or(text.len());
+            (0..=segment).any(|skip| matches_from(rest, &text[skip..]))
+        }
+        ['?', rest @ ..] => text.first().is_some_and(|c| *c != '/') && matches_from(rest, &text[1..]),
+        [c, rest @ ..] => text.first() == Some(c) && matches_from(rest, &text[1..]),
+    }
+}
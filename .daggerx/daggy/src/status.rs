@@ -0,0 +1,102 @@
+use serde::Serialize;
+
+use crate::command::CommandRunner;
+use crate::error::Result;
+use crate::bump::Bumper;
+use crate::repo::{Module, Repo};
+
+/// Where a module stands between its latest release and the working tree.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ModuleStatus {
+    pub module: String,
+    /// The newest `<module>/v*` tag, or `None` when the module was never released.
+    pub latest_tag: Option<String>,
+    /// Commits touching the module since `latest_tag`, or since the first commit.
+    pub commits_since: usize,
+    /// Files under the module with changes that are not committed yet.
+    pub uncommitted: usize,
+    /// The version `bump` would tag next, or `None` when there is nothing to release.
+    pub next_version: Option<String>,
+    /// `<module>/v*` tags that are not valid release versions.
+    pub malformed_tags: Vec<String>,
+}
+
+impl ModuleStatus {
+    /// The release state of `module`, which has `uncommitted` files with uncommitted changes,
+    /// with the next version `bumper` would tag.
+    pub fn read(bumper: &Bumper, module: &str, uncommitted: usize) -> Result<Self> {
+        let plan = bumper.plan(module)?;
+        Ok(Self {
+            module: module.to_string(),
+            latest_tag: plan.since,
+            commits_since: plan.commits,
+            uncommitted,
+            next_version: plan.next.map(|next| next.to_string()),
+            malformed_tags: plan.malformed,
+        })
+    }
+
+    /// What needs a look: a module that was never released or has malformed tags.
+    pub fn problems(&self) -> Vec<String> {
+        let mut problems = Vec::new();
+        if self.latest_tag.is_none() {
+            problems.push("no release tag".to_string());
+        }
+        problems.extend(self.malformed_tags.iter().map(|tag| format!("malformed tag {}", tag)));
+        problems
+    }
+}
+
+/// The release state of a list of modules.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct StatusReport {
+    pub statuses: Vec<ModuleStatus>,
+}
+
+impl StatusReport {
+    /// The release state of every module of `modules`, in order, counting uncommitted files
+    /// through `runner`.
+    pub fn read(repo: &Repo, runner: &dyn CommandRunner, bumper: &Bumper, modules: &[Module]) -> Result<Self> {
+        let mut statuses = Vec::new();
+        for module in modules {
+            let uncommitted = repo.uncommitted_files(runner, &module.name)?.len();
+            statuses.push(ModuleStatus::read(bumper, &module.name, uncommitted)?);
+        }
+        Ok(Self { statuses })
+    }
+
+    /// Modules with commits since their latest release.
+    pub fn unreleased(&self) -> usize {
+        self.statuses.iter().filter(|status| status.next_version.is_some()).count()
+    }
+
+    /// Modules with problems, see [`ModuleStatus::problems`].
+    pub fn flagged(&self) -> usize {
+        self.statuses.iter().filter(|status| !status.problems().is_empty()).count()
+    }
+}
+
+/// `statuses` as an aligned table with a header row.
+pub fn table(statuses: &[ModuleStatus]) -> String {
+    let header = ["module", "latest", "commits", "uncommitted", "next", "notes"];
+    let mut rows = vec![header.map(str::to_string)];
+    for status in statuses {
+        rows.push([
+            status.module.clone(),
+            status.latest_tag.as_deref().and_then(|tag| tag.rsplit_once('/')).map_or("-", |(_, version)| version).to_string(),
+            status.commits_since.to_string(),
+            status.uncommitted.to_string(),
+            status.next_version.clone().unwrap_or_else(|| "-".to_string()),
+            status.problems().join("; "),
+        ]);
+    }
+
+    let widths: Vec<usize> = (0..header.len()).map(|column| rows.iter().map(|row| row[column].len()).max().unwrap_or_default()).collect();
+    let mut table = String::new();
+    for row in &rows {
+        let cells: Vec<String> = row.iter().zip(&widths).map(|(cell, width)| format!("{:<width$}", cell, width = width)).collect();
+        table.push_str(cells.join("  ").trim_end());
+        table.push('\n');
+    }
+    table
+}
@@ -0,0 +1,196 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use regex::Regex;
+
+use crate::error::{Error, IoResultExt, Result};
+
+/// The template tree used to scaffold new modules, usually `.daggerx/templates`.
+///
+/// Templates may wrap lines in `{{if .name}}` ... `{{end}}` marker lines; the lines of a
+/// disabled section are dropped when the template is read or rendered.
+#[derive(Debug, Clone)]
+pub struct TemplateSet {
+    root: PathBuf,
+    disabled_sections: Vec<String>,
+}
+
+impl TemplateSet {
+    pub fn new(root: impl Into<PathBuf>) -> Self {
+        Self { root: root.into(), disabled_sections: Vec::new() }
+    }
+
+    /// Drop the `{{if .name}}` sections named in `sections`.
+    pub fn with_disabled_sections<S: Into<String>>(mut self, sections: impl IntoIterator<Item = S>) -> Self {
+        self.disabled_sections = sections.into_iter().map(Into::into).collect();
+        self
+    }
+
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    /// Templates for the parent module sources.
+    pub fn module_dir(&self) -> PathBuf {
+        self.root.join("module")
+    }
+
+    /// Templates for the `examples/go` module.
+    pub fn examples_dir(&self) -> PathBuf {
+        self.root.join("examples/go")
+    }
+
+    /// Templates for the `tests` module.
+    pub fn tests_dir(&self) -> PathBuf {
+        self.root.join("tests")
+    }
+
+    pub fn readme(&self) -> PathBuf {
+        self.root.join("README.md")
+    }
+
+    pub fn license(&self) -> PathBuf {
+        self.root.join("LICENSE")
+    }
+
+    pub fn workflow(&self) -> PathBuf {
+        self.root.join("github/workflows/mod-template-ci.yaml.tmpl")
+    }
+
+    /// Render every file below `template_dir` into `dest_dir`, dropping `.tmpl` extensions.
+    /// Returns the files written.
+    pub fn render_dir(&self, template_dir: &Path, dest_dir: &Path, module_name: &str) -> Result<Vec<PathBuf>> {
+        require(template_dir)?;
+        let mut written = Vec::new();
+        self.render_dir_into(template_dir, dest_dir, module_name, &mut written)?;
+        Ok(written)
+    }
+
+    /// Read a single template file without its disabled sections, failing with
+    /// [`Error::TemplateMissing`] when absent.
+    pub fn read(&self, template: &Path) -> Result<String> {
+        require(template)?;
+        let content = fs::read_to_string(template).at_path(template)?;
+        Ok(keep_sections(&content, |section| !self.disabled_sections.iter().any(|disabled| disabled == section)))
+    }
+
+    fn render_dir_into(&self, template_dir: &Path, dest_dir: &Path, module_name: &str, written: &mut Vec<PathBuf>) -> Result<()> {
+        for entry in fs::read_dir(template_dir).at_path(template_dir)? {
+            let entry = entry.at_path(template_dir)?;
+            let path = entry.path();
+
+            if path.is_dir() {
+                let new_dir = dest_dir.join(entry.file_name());
+                fs::create_dir_all(&new_dir).at_path(&new_dir)?;
+                self.render_dir_into(&path, &new_dir, module_name, written)?;
+            } else {
+                let new_content = process_template_content(&self.read(&path)?, module_name);
+
+                let dest_file_name = entry.file_name().to_string_lossy().replace(".tmpl", "");
+                let dest_path = dest_dir.join(dest_file_name);
+                fs::write(&dest_path, new_content).at_path(&dest_path)?;
+                written.push(dest_path);
+            }
+        }
+
+        Ok(())
+    }
+}
+
+fn require(template: &Path) -> Result<()> {
+    if template.exists() {
+        Ok(())
+    } else {
+        Err(Error::TemplateMissing { path: template.to_path_buf() })
+    }
+}
+
+/// Resolve the `{{if .name}}` ... `{{end}}` marker lines of a template: the lines of a
+/// section are kept when `enabled(name)` holds, and the marker lines are always dropped.
+/// Sections may nest.
+pub fn keep_sections(content: &str, enabled: impl Fn(&str) -> bool) -> String {
+    let start = Regex::new(r"^\s*\{\{\s*if\s+\.(\w+)\s*\}\}\s*$").unwrap();
+    let end = Regex::new(r"^\s*\{\{\s*end\s*\}\}\s*$").unwrap();
+
+    let mut kept = String::with_capacity(content.len());
+    let mut sections: Vec<bool> = Vec::new();
+    for line in content.split_inclusive('\n') {
+        if let Some(caps) = start.captures(line.trim_end_matches(['\r', '\n'])) {
+            sections.push(enabled(&caps[1]));
+        } else if end.is_match(line.trim_end_matches(['\r', '\n'])) && !sections.is_empty() {
+            sections.pop();
+        } else if sections.iter().all(|keep| *keep) {
+            kept.push_str(line);
+        }
+    }
+    kept
+}
+
+/// Replace the `{{.module_name*}}` placeholders of a template.
+pub fn process_template_content(content: &str, module_name: &str) -> String {
+    let pkg_name = module_name.to_lowercase().trim().replace(" ", "-");
+    let pascal_case_name = to_pascal_case(module_name);
+    let lowercase_name = module_name.to_lowercase();
+
+    let content = content.replace("{{.module_name_pkg}}", &pkg_name);
+    let content = content.replace("{{.module_name}}", &pascal_case_name);
+    content.replace("{{.module_name_lowercase}}", &lowercase_name)
+}
+
+/// Replace `{{.module_name}}` with the lowercase name inside Markdown code spans and blocks,
+/// and with the PascalCase name everywhere else.
+pub fn replace_module_name_smart(content: &str, module_name: &str) -> String {
+    let pascal_case_name = to_pascal_case(module_name);
+    let lowercase_name = module_name.to_lowercase();
+
+    let re = Regex::new(r"```[\s\S]*?```|`[^`\n]+`|\{\{\s*\.\s*module_name\s*\}\}").unwrap();
+
+    re.replace_all(content, |caps: &regex::Captures| {
+        let matched = caps.get(0).unwrap().as_str();
+        if matched.starts_with("```") || matched.starts_with("`") {
+            // Inside code blocks, use lowercase with hyphens
+            matched.replace("{{.module_name}}", &lowercase_name)
+        } else {
+            // Outside code blocks, use PascalCase without hyphens
+            matched.replace("{{.module_name}}", &pascal_case_name)
+        }
+    }).to_string()
+}
+
+fn capitalize_module_name(module_name: &str) -> String {
+    let mut chars = module_name.chars();
+    match chars.next() {
+        None => String::new(),
+        Some(first) => first.to_uppercase().collect::<String>() + chars.as_str(),
+    }
+}
+
+pub fn to_pascal_case(s: &str) -> String {
+    s.split('-')
+        .map(capitalize_module_name)
+        .collect()
+}
+
+/// Copy a directory recursively without rendering its contents.
+pub fn copy_dir_all(src: impl AsRef<Path>, dst: impl AsRef<Path>) -> Result<Vec<PathBuf>> {
+    require(src.as_ref())?;
+    let mut written = Vec::new();
+    copy_dir_into(src.as_ref(), dst.as_ref(), &mut written)?;
+    Ok(written)
+}
+
+fn copy_dir_into(src: &Path, dst: &Path, written: &mut Vec<PathBuf>) -> Result<()> {
+    fs::create_dir_all(dst).at_path(dst)?;
+    for entry in fs::read_dir(src).at_path(src)? {
+        let entry = entry.at_path(src)?;
+        let ty = entry.file_type().at_path(entry.path())?;
+        let dest_path = dst.join(entry.file_name());
+        if ty.is_dir() {
+            copy_dir_into(&entry.path(), &dest_path, written)?;
+        } else {
+            fs::copy(entry.path(), &dest_path).at_path(&dest_path)?;
+            written.push(dest_path);
+        }
+    }
+    Ok(())
+}
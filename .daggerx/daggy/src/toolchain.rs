@@ -0,0 +1,143 @@
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::Path;
+
+use serde::Deserialize;
+
+use crate::error::{Error, Result};
+
+/// Per-repository binaries, relative to the repository root.
+pub const TOOLCHAIN_CONFIG: &str = ".daggerx/toolchain.json";
+
+/// The external tools daggy relies on.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Tool {
+    Git,
+    Dagger,
+    Go,
+}
+
+impl Tool {
+    /// The name of the tool, which is also its default binary.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Tool::Git => "git",
+            Tool::Dagger => "dagger",
+            Tool::Go => "go",
+        }
+    }
+}
+
+impl fmt::Display for Tool {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+/// The binaries used for each tool: a name looked up on PATH or a path to an executable.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Toolchain {
+    git: String,
+    dagger: String,
+    go: String,
+}
+
+impl Default for Toolchain {
+    fn default() -> Self {
+        Self { git: Tool::Git.name().to_string(), dagger: Tool::Dagger.name().to_string(), go: Tool::Go.name().to_string() }
+    }
+}
+
+impl Toolchain {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Run dagger from `bin`, e.g. `~/bin/dagger-0.12.4`.
+    pub fn with_dagger(mut self, bin: impl Into<String>) -> Self {
+        self.dagger = bin.into();
+        self
+    }
+
+    pub fn with_go(mut self, bin: impl Into<String>) -> Self {
+        self.go = bin.into();
+        self
+    }
+
+    pub fn with_git(mut self, bin: impl Into<String>) -> Self {
+        self.git = bin.into();
+        self
+    }
+
+    /// The binary to run for `tool`.
+    pub fn program(&self, tool: Tool) -> &str {
+        match tool {
+            Tool::Git => &self.git,
+            Tool::Dagger => &self.dagger,
+            Tool::Go => &self.go,
+        }
+    }
+
+    pub fn dagger(&self) -> &str {
+        &self.dagger
+    }
+
+    pub fn go(&self) -> &str {
+        &self.go
+    }
+
+    pub fn git(&self) -> &str {
+        &self.git
+    }
+}
+
+/// The binaries a repository pins in its [`TOOLCHAIN_CONFIG`], e.g.
+/// `{ "dagger": "~/bin/dagger-0.12.4", "go": "go1.22.4" }`. Flags and environment
+/// variables take precedence over it.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ToolchainConfig {
+    pub dagger: Option<String>,
+    pub go: Option<String>,
+    pub git: Option<String>,
+}
+
+impl ToolchainConfig {
+    /// Load the configuration of the repository containing `dir`. It is looked up in `dir` and
+    /// its parents up to the repository root rather than asked from git, which it may
+    /// configure. Without one the defaults apply.
+    pub fn find(dir: &Path) -> Result<Self> {
+        for ancestor in dir.ancestors() {
+            let path = ancestor.join(TOOLCHAIN_CONFIG);
+            let content = match fs::read_to_string(&path) {
+                Ok(content) => content,
+                Err(e) if e.kind() == io::ErrorKind::NotFound => {
+                    if ancestor.join(".git").exists() {
+                        break;
+                    }
+                    continue;
+                }
+                Err(e) => return Err(Error::io(&path, e)),
+            };
+            let config: Self =
+                serde_json::from_str(&content).map_err(|e| Error::io(&path, io::Error::new(io::ErrorKind::InvalidData, e)))?;
+            return Ok(config.relative_to(ancestor));
+        }
+        Ok(Self::default())
+    }
+
+    /// Resolve relative paths such as `bin/dagger` against `root`; bare names are still
+    /// looked up on PATH.
+    fn relative_to(self, root: &Path) -> Self {
+        let resolve = |bin: Option<String>| {
+            bin.map(|bin| match Path::new(&bin) {
+                path if path.is_relative() && bin.contains('/') && !bin.starts_with("~/") => {
+                    root.join(path).to_string_lossy().into_owned()
+                }
+                _ => bin,
+            })
+        };
+        Self { dagger: resolve(self.dagger), go: resolve(self.go), git: resolve(self.git) }
+    }
+}
@@ -0,0 +1,48 @@
+use std::fmt;
+use std::str::FromStr;
+use std::sync::LazyLock;
+
+use regex::Regex;
+
+static VERSION: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"v?(\d+)\.(\d+)(?:\.(\d+))?").unwrap());
+
+/// A `major.minor.patch` version, as printed by tools and used for `engineVersion`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Version {
+    pub major: u64,
+    pub minor: u64,
+    pub patch: u64,
+}
+
+impl Version {
+    pub const fn new(major: u64, minor: u64, patch: u64) -> Self {
+        Self { major, minor, patch }
+    }
+
+    /// The first version number appearing in `text`, e.g. in `go version go1.22.4 linux/amd64`.
+    pub fn find_in(text: &str) -> Option<Self> {
+        let captures = VERSION.captures(text)?;
+        let number = |i: usize| captures.get(i).map_or(Some(0), |m| m.as_str().parse().ok());
+        Some(Self::new(number(1)?, number(2)?, number(3)?))
+    }
+}
+
+impl FromStr for Version {
+    type Err = String;
+
+    /// Parse `v0.12.4`, `0.12.4` or `0.12`.
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        let value = value.trim();
+        match VERSION.find(value) {
+            Some(m) if m.start() == 0 && m.end() == value.len() => Self::find_in(value),
+            _ => None,
+        }
+        .ok_or_else(|| format!("invalid version {:?}, expected e.g. v0.12.4", value))
+    }
+}
+
+impl fmt::Display for Version {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "v{}.{}.{}", self.major, self.minor, self.patch)
+    }
+}
@@ -0,0 +1,169 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::thread;
+use std::time::{Duration, Instant, SystemTime};
+
+use crate::cancel::Cancellation;
+use crate::error::{Error, IoResultExt, Result};
+use crate::repo::IGNORED_DIRS;
+use crate::scaffold::GENERATED_PATHS;
+
+/// How often watched files are checked for changes.
+pub const DEFAULT_POLL_INTERVAL: Duration = Duration::from_millis(200);
+
+/// How long files must stay unchanged before a change is reported.
+pub const DEFAULT_DEBOUNCE: Duration = Duration::from_millis(500);
+
+/// Whether `path` is generated by `dagger develop`, in any module below the watched one.
+fn is_generated(path: &Path) -> bool {
+    GENERATED_PATHS.iter().any(|generated| path.ends_with(generated.trim_start_matches('/')))
+}
+
+/// The Go sources and dagger.json files below `dir`, which includes child modules such as
+/// `tests` and `examples/go`.
+fn is_watched(path: &Path) -> bool {
+    path.file_name().is_some_and(|name| name == "dagger.json") || path.extension().is_some_and(|ext| ext == "go")
+}
+
+/// Modification time and size of every watched file below a directory.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Snapshot(BTreeMap<PathBuf, (Option<SystemTime>, u64)>);
+
+impl Snapshot {
+    pub fn take(dir: &Path) -> Result<Self> {
+        let mut snapshot = Self::default();
+        snapshot.collect(dir)?;
+        Ok(snapshot)
+    }
+
+    fn collect(&mut self, dir: &Path) -> Result<()> {
+        for entry in fs::read_dir(dir).at_path(dir)? {
+            let entry = entry.at_path(dir)?;
+            let path = entry.path();
+            if is_generated(&path) {
+                continue;
+            }
+            let file_type = entry.file_type().at_path(&path)?;
+            if file_type.is_dir() {
+                if !IGNORED_DIRS.iter().any(|ignored| entry.file_name() == *ignored) {
+                    self.collect(&path)?;
+                }
+            } else if is_watched(&path) {
+                // Files may vanish while an editor saves them; the next poll sees them again.
+                if let Ok(metadata) = entry.metadata() {
+                    self.0.insert(path, (metadata.modified().ok(), metadata.len()));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    pub fn files(&self) -> impl Iterator<Item = &Path> {
+        self.0.keys().map(PathBuf::as_path)
+    }
+
+    /// Files added, modified or removed since `earlier`, sorted.
+    pub fn changes_since(&self, earlier: &Snapshot) -> Vec<PathBuf> {
+        let mut changed: Vec<PathBuf> =
+            self.0.iter().filter(|(path, stamp)| earlier.0.get(*path) != Some(stamp)).map(|(path, _)| path.clone()).collect();
+        changed.extend(earlier.0.keys().filter(|path| !self.0.contains_key(*path)).cloned());
+        changed.sort();
+        changed
+    }
+}
+
+/// Polls the watched files of a module directory and reports changes once they settle.
+#[derive(Debug)]
+pub struct Watcher {
+    dir: PathBuf,
+    snapshot: Snapshot,
+    poll_interval: Duration,
+    debounce: Duration,
+    cancellation: Cancellation,
+}
+
+impl Watcher {
+    /// Start watching `dir` from its current state.
+    pub fn new(dir: impl Into<PathBuf>) -> Result<Self> {
+        let dir = dir.into();
+        let snapshot = Snapshot::take(&dir)?;
+        Ok(Self {
+            dir,
+            snapshot,
+            poll_interval: DEFAULT_POLL_INTERVAL,
+            debounce: DEFAULT_DEBOUNCE,
+            cancellation: Cancellation::new(),
+        })
+    }
+
+    pub fn with_poll_interval(mut self, interval: Duration) -> Self {
+        self.poll_interval = interval;
+        self
+    }
+
+    /// Wait until files stayed unchanged for `debounce`, so a burst of saves is one change.
+    pub fn with_debounce(mut self, debounce: Duration) -> Self {
+        self.debounce = debounce;
+        self
+    }
+
+    /// Stop waiting once `cancellation` is cancelled.
+    pub fn with_cancellation(mut self, cancellation: Cancellation) -> Self {
+        self.cancellation = cancellation;
+        self
+    }
+
+    pub fn snapshot(&self) -> &Snapshot {
+        &self.snapshot
+    }
+
+    /// Accept the current state of `paths` without reporting it, e.g. after `dagger develop`
+    /// itself rewrote dagger.json. Changes to every other file, such as those saved while
+    /// develop ran, are still reported by the next [`Watcher::wait`].
+    pub fn accept(&mut self, paths: &[PathBuf]) -> Result<()> {
+        let current = Snapshot::take(&self.dir)?;
+        for path in paths {
+            match current.0.get(path) {
+                Some(stamp) => self.snapshot.0.insert(path.clone(), *stamp),
+                None => self.snapshot.0.remove(path),
+            };
+        }
+        Ok(())
+    }
+
+    /// Block until watched files change and then stay unchanged for the debounce period.
+    /// Returns the changed files, or `None` once cancelled.
+    pub fn wait(&mut self) -> Result<Option<Vec<PathBuf>>> {
+        let mut current = self.snapshot.clone();
+        let mut last_change: Option<Instant> = None;
+        loop {
+            if self.cancellation.is_cancelled() {
+                return Ok(None);
+            }
+            thread::sleep(self.poll_interval);
+
+            let next = match Snapshot::take(&self.dir) {
+                Ok(next) => next,
+                // A directory removed while it was read, e.g. mid-save; the next poll sees
+                // where it settled.
+                Err(Error::Io { source, .. }) if source.kind() == io::ErrorKind::NotFound && self.dir.is_dir() => continue,
+                Err(e) => return Err(e),
+            };
+            if next != current {
+                current = next;
+                last_change = Some(Instant::now());
+            }
+            if last_change.is_some_and(|at| at.elapsed() >= self.debounce) {
+                let changed = current.changes_since(&self.snapshot);
+                self.snapshot = current.clone();
+                if !changed.is_empty() {
+                    return Ok(Some(changed));
+                }
+                // Changed and changed back, e.g. an editor's temporary rewrite.
+                last_change = None;
+            }
+        }
+    }
+}
@@ -0,0 +1,318 @@
+mod common;
+
+use std::sync::Arc;
+
+use common::{git, stderr, stdout, Sandbox};
+use daggy::bump::{BumpOutcome, BumpStatus, Bumper};
+use daggy::command::SystemRunner;
+use daggy::release::{Bump, Commit, ReleaseConfig, ReleaseVersion, Releases};
+use daggy::Repo;
+
+/// `alpha` and `beta`, committed, with `alpha` released as `alpha/v1.2.0`.
+fn released_sandbox() -> Sandbox {
+    let sandbox = Sandbox::new();
+    sandbox.add_module("alpha");
+    sandbox.add_module("beta");
+    commit(&sandbox, "alpha/main.go", "feat: initial alpha");
+    sandbox.git(&["tag", "-a", "alpha/v1.2.0", "-m", "Bump alpha to v1.2.0"]);
+    sandbox
+}
+
+/// Commit `file`, with new content, with the message `message`.
+fn commit(sandbox: &Sandbox, file: &str, message: &str) {
+    let previous = std::fs::read_to_string(sandbox.root.join(file)).unwrap_or_default();
+    sandbox.write(file, &format!("{}// {}\n", previous, message));
+    sandbox.git(&["add", "-A"]);
+    sandbox.git(&["commit", "-qm", message]);
+}
+
+fn version(value: &str) -> ReleaseVersion {
+    value.parse().unwrap()
+}
+
+#[test]
+fn bump_tags_the_next_version() {
+    let sandbox = released_sandbox();
+
+    let output = sandbox.run(&["--task", "bump", "--module", "alpha", "--bump", "minor"]);
+
+    assert!(output.status.success(), "bump failed: {}", stderr(&output));
+    assert_eq!(stdout(&output), "alpha/v1.3.0\n");
+    assert_eq!(sandbox.git(&["cat-file", "-t", "alpha/v1.3.0"]), "tag", "the tag is annotated");
+    assert_eq!(sandbox.git(&["tag", "-l", "--format=%(contents:subject)", "alpha/v1.3.0"]), "Bump alpha to v1.3.0");
+    assert!(stderr(&output).contains("Tagged alpha/v1.3.0 (v1.2.0 → v1.3.0)"));
+    assert!(stderr(&output).contains("Created 1 tags locally; push them with --push."));
+}
+
+#[test]
+fn the_first_bump_starts_from_zero() {
+    let sandbox = released_sandbox();
+
+    let output = sandbox.run(&["--task", "bump", "--module", "beta", "--bump", "minor"]);
+
+    assert!(output.status.success(), "bump failed: {}", stderr(&output));
+    assert_eq!(stdout(&output), "beta/v0.1.0\n");
+}
+
+#[test]
+fn the_level_is_inferred_from_conventional_commits_touching_the_module() {
+    let sandbox = released_sandbox();
+    commit(&sandbox, "beta/main.go", "feat!: rework beta");
+    commit(&sandbox, "alpha/main.go", "fix(alpha): handle empty input");
+
+    let output = sandbox.run(&["--task", "bump", "--module", "alpha"]);
+    assert!(output.status.success(), "bump failed: {}", stderr(&output));
+    assert_eq!(stdout(&output), "alpha/v1.2.1\n");
+    assert!(stderr(&output).contains("alpha: 1 commits since alpha/v1.2.0 call for a patch bump"), "stderr: {}", stderr(&output));
+
+    commit(&sandbox, "alpha/main.go", "feat: add a flag");
+    let output = sandbox.run(&["--task", "bump", "--module", "alpha"]);
+    assert_eq!(stdout(&output), "alpha/v1.3.0\n");
+
+    commit(&sandbox, "alpha/main.go", "refactor: rename the flag\n\nBREAKING CHANGE: the flag is now --name");
+    let output = sandbox.run(&["--task", "bump", "--module", "alpha"]);
+    assert_eq!(stdout(&output), "alpha/v2.0.0\n");
+}
+
+#[test]
+fn modules_without_new_commits_are_skipped() {
+    let sandbox = released_sandbox();
+    commit(&sandbox, "beta/main.go", "fix: beta only");
+
+    let output = sandbox.run(&["--task", "bump", "--module", "alpha", "--module", "beta"]);
+
+    assert!(output.status.success(), "bump failed: {}", stderr(&output));
+    // Untagged, beta counts every commit, including the feat that added it.
+    assert_eq!(stdout(&output), "beta/v0.1.0\n");
+    assert!(stderr(&output).contains("Skipped alpha: no commits since alpha/v1.2.0"));
+}
+
+#[test]
+fn changed_since_bumps_the_changed_modules() {
+    let sandbox = released_sandbox();
+    commit(&sandbox, "beta/main.go", "feat: beta");
+
+    let output = sandbox.run(&["--task", "bump", "--changed-since", "HEAD~1", "--bump", "patch"]);
+
+    assert!(output.status.success(), "bump failed: {}", stderr(&output));
+    assert_eq!(stdout(&output), "beta/v0.0.1\n");
+}
+
+#[test]
+fn a_change_to_the_tests_of_a_module_bumps_the_module() {
+    let sandbox = released_sandbox();
+    sandbox.add_module("alpha/tests");
+    commit(&sandbox, "alpha/tests/main.go", "test: cover alpha");
+
+    let output = sandbox.run(&["--task", "bump", "--changed-since", "HEAD~1", "--bump", "patch"]);
+
+    assert!(output.status.success(), "bump failed: {}", stderr(&output));
+    assert_eq!(stdout(&output), "alpha/v1.2.1\n");
+}
+
+#[test]
+fn release_please_rules_apply_before_v1() {
+    let sandbox = Sandbox::new();
+    sandbox.add_module("alpha");
+    sandbox.write("release-please-config.json", r#"{ "packages": { ".": { "bump-minor-pre-major": true, "bump-patch-for-minor-pre-major": true } } }"#);
+    commit(&sandbox, "alpha/main.go", "feat: initial alpha");
+    sandbox.git(&["tag", "-a", "alpha/v0.3.0", "-m", "v0.3.0"]);
+
+    commit(&sandbox, "alpha/main.go", "feat: new function");
+    assert_eq!(stdout(&sandbox.run(&["--task", "bump", "-m", "alpha"])), "alpha/v0.3.1\n");
+
+    commit(&sandbox, "alpha/main.go", "feat!: drop the old function");
+    assert_eq!(stdout(&sandbox.run(&["--task", "bump", "-m", "alpha"])), "alpha/v0.4.0\n");
+}
+
+#[test]
+fn prereleases_count_up_and_lead_to_the_release() {
+    let sandbox = released_sandbox();
+    let bump = |level: &str| stdout(&sandbox.run(&["--task", "bump", "-m", "alpha", "--bump", level]));
+
+    assert_eq!(bump("prerelease"), "alpha/v1.2.1-rc.1\n");
+    assert_eq!(bump("prerelease"), "alpha/v1.2.1-rc.2\n");
+    assert_eq!(bump("patch"), "alpha/v1.2.1\n");
+}
+
+#[test]
+fn an_existing_tag_is_not_moved() {
+    let sandbox = released_sandbox();
+    let releases = Releases::new(Repo::at(&sandbox.root), Arc::new(SystemRunner::new()));
+    let before = sandbox.git(&["rev-parse", "alpha/v1.2.0"]);
+    commit(&sandbox, "alpha/main.go", "fix: later");
+
+    let error = releases.create_tag("alpha", "alpha/v1.2.0", "Bump alpha to v1.2.0").unwrap_err();
+
+    assert_eq!(error.to_string(), "cannot release alpha: tag alpha/v1.2.0 already exists");
+    assert_eq!(error.exit_code(), 13);
+    assert_eq!(sandbox.git(&["rev-parse", "alpha/v1.2.0"]), before);
+}
+
+#[test]
+fn malformed_tags_are_ignored() {
+    let sandbox = released_sandbox();
+    sandbox.git(&["tag", "alpha/vnext"]);
+
+    let output = sandbox.run(&["--task", "bump", "-m", "alpha", "--bump", "patch"]);
+
+    assert!(output.status.success(), "bump failed: {}", stderr(&output));
+    assert_eq!(stdout(&output), "alpha/v1.2.1\n");
+    assert!(stderr(&output).contains("Ignoring malformed tag alpha/vnext"));
+}
+
+#[test]
+fn tags_are_pushed_only_with_push() {
+    let sandbox = released_sandbox();
+    let remote = sandbox.root.parent().unwrap().join("remote.git");
+    git(sandbox.root.parent().unwrap(), &["init", "--quiet", "--bare", remote.to_str().unwrap()]);
+    sandbox.git(&["remote", "add", "origin", remote.to_str().unwrap()]);
+    let remote_tags = || git(&remote, &["tag", "--list"]);
+
+    assert!(sandbox.run(&["--task", "bump", "-m", "alpha", "--bump", "patch"]).status.success());
+    assert_eq!(remote_tags(), "");
+
+    let output = sandbox.run(&["--task", "bump", "-m", "alpha", "--bump", "patch", "--push"]);
+
+    assert!(output.status.success(), "bump failed: {}", stderr(&output));
+    assert_eq!(remote_tags(), "alpha/v1.2.2");
+    assert!(stderr(&output).contains("Pushed alpha/v1.2.2 to origin"));
+}
+
+#[test]
+fn a_failed_tag_deletes_the_tags_created_before_it() {
+    let sandbox = released_sandbox();
+    // A tag named like the module keeps git from creating tags under beta/.
+    sandbox.git(&["tag", "beta"]);
+
+    let output = sandbox.run(&["--task", "bump", "-m", "alpha", "-m", "beta", "--bump", "minor"]);
+
+    assert_eq!(output.status.code(), Some(7), "stderr: {}", stderr(&output));
+    assert_eq!(sandbox.git(&["tag", "--list", "alpha/*"]), "alpha/v1.2.0");
+    assert!(stderr(&output).contains("Deleted the tags created before the failure: alpha/v1.3.0"), "stderr: {}", stderr(&output));
+    assert_eq!(stdout(&output), "");
+}
+
+#[test]
+fn all_tags_are_pushed_at_once_or_not_at_all() {
+    let sandbox = released_sandbox();
+
+    let output = sandbox.run(&["--task", "bump", "-m", "alpha", "-m", "beta", "--bump", "minor", "--push", "--remote", "nowhere"]);
+
+    assert_eq!(output.status.code(), Some(7), "stderr: {}", stderr(&output));
+    assert!(stderr(&output).contains("pushing alpha/v1.3.0, beta/v0.1.0 to nowhere"), "stderr: {}", stderr(&output));
+    assert_eq!(sandbox.git(&["tag", "--list"]), "alpha/v1.2.0", "the tags are deleted so a re-run starts over");
+
+    let remote = sandbox.root.parent().unwrap().join("remote.git");
+    git(sandbox.root.parent().unwrap(), &["init", "--quiet", "--bare", remote.to_str().unwrap()]);
+    sandbox.git(&["remote", "add", "nowhere", remote.to_str().unwrap()]);
+    let output = sandbox.run(&["--task", "bump", "-m", "alpha", "-m", "beta", "--bump", "minor", "--push", "--remote", "nowhere"]);
+
+    assert!(output.status.success(), "bump failed: {}", stderr(&output));
+    assert_eq!(git(&remote, &["tag", "--list"]), "alpha/v1.3.0\nbeta/v0.1.0");
+    assert!(stderr(&output).contains("Pushed alpha/v1.3.0, beta/v0.1.0 to nowhere"));
+}
+
+#[test]
+fn bump_needs_a_selection() {
+    let sandbox = released_sandbox();
+
+    let output = sandbox.run(&["--task", "bump"]);
+
+    assert_eq!(output.status.code(), Some(2));
+    assert!(stderr(&output).contains("select the modules to bump with --module or --changed-since"));
+}
+
+#[test]
+fn versions_order_by_semver_precedence() {
+    let mut versions: Vec<ReleaseVersion> =
+        ["v1.0.0", "v1.0.0-rc.10", "v0.9.9", "v1.0.0-rc.2", "v1.0.0-beta", "v1.0.0-rc"].iter().map(|v| version(v)).collect();
+    versions.sort();
+
+    let sorted: Vec<String> = versions.iter().map(ToString::to_string).collect();
+    assert_eq!(sorted, ["v0.9.9", "v1.0.0-beta", "v1.0.0-rc", "v1.0.0-rc.2", "v1.0.0-rc.10", "v1.0.0"]);
+    assert!("1.0.0".parse::<ReleaseVersion>().is_err(), "tags carry the v");
+}
+
+#[test]
+fn bumps_follow_semver() {
+    let bump = |from: &str, level: Bump| version(from).bump(level, "rc").unwrap().to_string();
+
+    assert_eq!(bump("v1.2.3", Bump::Major), "v2.0.0");
+    assert_eq!(bump("v1.2.3", Bump::Minor), "v1.3.0");
+    assert_eq!(bump("v1.2.3", Bump::Patch), "v1.2.4");
+    assert_eq!(bump("v1.2.3", Bump::Prerelease), "v1.2.4-rc.1");
+    assert_eq!(bump("v2.0.0-rc.3", Bump::Major), "v2.0.0");
+    assert_eq!(bump("v1.3.0-rc.3", Bump::Major), "v2.0.0");
+    assert_eq!(bump("v1.3.0-rc.3", Bump::Minor), "v1.3.0");
+    assert_eq!(bump("v1.2.4-beta.1", Bump::Prerelease), "v1.2.4-rc.1");
+}
+
+#[test]
+fn prerelease_bumps_never_go_backwards() {
+    let bump = |from: &str, preid: &str| version(from).bump(Bump::Prerelease, preid);
+
+    assert_eq!(bump("v1.0.0-beta.2", "rc"), Ok(version("v1.0.0-rc.1")));
+    assert_eq!(bump("v1.0.0-rc.2", "beta"), Err("a prerelease bump of v1.0.0-rc.2 to v1.0.0-beta.1 would not increase the version".to_string()));
+}
+
+#[test]
+fn a_bump_to_a_lower_prerelease_is_refused() {
+    let sandbox = released_sandbox();
+    sandbox.git(&["tag", "-a", "alpha/v1.2.1-rc.2", "-m", "alpha/v1.2.1-rc.2"]);
+
+    let output = sandbox.run(&["--task", "bump", "-m", "alpha", "--bump", "prerelease", "--preid", "beta"]);
+
+    assert_eq!(output.status.code(), Some(13), "stderr: {}", stderr(&output));
+    assert!(stderr(&output).contains("v1.2.1-beta.1 would not increase the version"), "stderr: {}", stderr(&output));
+    assert_eq!(sandbox.git(&["tag", "--list", "alpha/*"]), "alpha/v1.2.0\nalpha/v1.2.1-rc.2");
+}
+
+#[test]
+fn breaking_changes_are_marked_in_the_subject_or_a_footer() {
+    let commit = |subject: &str, body: &str| Commit { hash: String::new(), subject: subject.to_string(), body: body.to_string() };
+    let config = ReleaseConfig::default();
+    let current = version("v1.0.0");
+
+    assert_eq!(config.infer_bump(&current, &[commit("feat(api)!: remove v1", "")]), Bump::Major);
+    assert_eq!(config.infer_bump(&current, &[commit("fix: x", "BREAKING CHANGE: y")]), Bump::Major);
+    assert_eq!(config.infer_bump(&current, &[commit("docs: x", ""), commit("feat: y", "")]), Bump::Minor);
+    assert_eq!(config.infer_bump(&current, &[commit("Update README", "")]), Bump::Patch);
+    assert_eq!(commit("feat(api)!: remove v1", "").conventional().unwrap().scope, Some("api"));
+}
+
+#[test]
+fn json_output_reports_new_tags_as_events() {
+    let sandbox = released_sandbox();
+
+    let output = sandbox.run(&["--task", "bump", "-m", "alpha", "-m", "beta", "--bump", "patch", "--output", "json"]);
+
+    assert!(output.status.success(), "bump failed: {}", stderr(&output));
+    let events: Vec<serde_json::Value> = stdout(&output).lines().map(|line| serde_json::from_str(line).unwrap()).collect();
+    let tags: Vec<&serde_json::Value> = events.iter().filter(|e| e["event"] == "tag_created").collect();
+    assert_eq!(tags.len(), 2);
+    assert_eq!((tags[0]["tag"].as_str(), tags[0]["previous"].as_str(), tags[0]["pushed"].as_bool()), (Some("alpha/v1.2.1"), Some("v1.2.0"), Some(false)));
+    assert_eq!((tags[1]["tag"].as_str(), tags[1].get("previous")), (Some("beta/v0.0.1"), None));
+    assert_eq!(events.last().unwrap()["succeeded"], 2);
+}
+
+#[test]
+fn bumper_reports_the_tagged_and_the_unchanged_modules() {
+    let sandbox = released_sandbox();
+    commit(&sandbox, "alpha/main.go", "fix: handle empty input");
+    let repo = Repo::at(&sandbox.root);
+    let modules = vec![repo.module("alpha").unwrap(), repo.module("beta").unwrap()];
+    sandbox.git(&["tag", "-a", "beta/v0.1.0", "-m", "Bump beta to v0.1.0"]);
+
+    let bumper = Bumper::new(Releases::new(repo, Arc::new(SystemRunner::new())), ReleaseConfig::default());
+    let report = bumper.bump(&modules).unwrap();
+
+    let tagged = BumpStatus::Tagged { tag: "alpha/v1.2.1".to_string(), version: version("v1.2.1"), previous: Some(version("v1.2.0")) };
+    let unchanged = BumpStatus::Unchanged { since: Some("beta/v0.1.0".to_string()) };
+    assert_eq!(
+        report.outcomes,
+        vec![BumpOutcome { module: "alpha".to_string(), status: tagged }, BumpOutcome { module: "beta".to_string(), status: unchanged }]
+    );
+    assert_eq!(report.tags(), vec!["alpha/v1.2.1"]);
+    assert_eq!(sandbox.git(&["tag", "--list", "alpha/*"]), "alpha/v1.2.0\nalpha/v1.2.1");
+}
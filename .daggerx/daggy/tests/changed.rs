@@ -0,0 +1,111 @@
+mod common;
+
+use common::{stderr, stdout, Sandbox};
+
+fn depends_on(name: &str, dependency: &str) -> String {
+    format!(
+        "{{\n  \"name\": \"{}\",\n  \"sdk\": \"go\",\n  \"source\": \".\",\n  \"dependencies\": [{{ \"name\": \"{}\", \"source\": \"../{}\" }}],\n  \"engineVersion\": \"v0.12.4\"\n}}\n",
+        name, dependency, dependency
+    )
+}
+
+/// `gamma` depends on `beta`, which depends on `alpha`; `delta` stands alone. The change
+/// from `HEAD~1` touches the tests of `alpha`.
+fn sandbox() -> Sandbox {
+    let sandbox = Sandbox::new();
+    for module in ["alpha", "alpha/tests", "alpha/examples/go", "delta"] {
+        sandbox.add_module(module);
+    }
+    sandbox.write("beta/dagger.json", &depends_on("beta", "alpha"));
+    sandbox.write("gamma/dagger.json", &depends_on("gamma", "beta"));
+    sandbox.git(&["add", "-A"]);
+    sandbox.git(&["commit", "-qm", "modules"]);
+    sandbox.write("alpha/tests/main.go", "package main\n");
+    sandbox.write("docs/index.md", "outside of any module\n");
+    sandbox.git(&["add", "-A"]);
+    sandbox.git(&["commit", "-qm", "change"]);
+    sandbox
+}
+
+#[test]
+fn changed_lists_the_parent_modules_and_their_dependents() {
+    let sandbox = sandbox();
+
+    let output = sandbox.run(&["--task", "changed", "--base", "HEAD~1"]);
+
+    assert!(output.status.success(), "changed failed: {}", stderr(&output));
+    assert_eq!(stdout(&output), "alpha\nbeta\ngamma\n");
+    let err = stderr(&output);
+    assert!(err.contains("2 files changed from HEAD~1 to HEAD"));
+    assert!(err.contains("Module affected: alpha (changed)"));
+    assert!(err.contains("Module affected: gamma (depends on alpha)"));
+    assert!(sandbox.invocations().is_empty());
+}
+
+#[test]
+fn changed_prints_a_json_array() {
+    let sandbox = sandbox();
+
+    let output = sandbox.run(&["--task", "changed", "--base", "HEAD~1", "--head", "HEAD", "--format", "json", "--exclude", "gamma"]);
+
+    assert!(output.status.success(), "changed failed: {}", stderr(&output));
+    assert_eq!(stdout(&output), "[\"alpha\",\"beta\"]\n");
+}
+
+#[test]
+fn changed_prints_nothing_without_changes() {
+    let sandbox = sandbox();
+
+    let output = sandbox.run(&["--task", "changed", "--base", "HEAD", "--format", "json"]);
+
+    assert!(output.status.success(), "changed failed: {}", stderr(&output));
+    assert_eq!(stdout(&output), "[]\n");
+}
+
+#[test]
+fn changed_needs_a_base() {
+    let sandbox = sandbox();
+
+    let output = sandbox.run(&["--task", "changed"]);
+
+    assert_eq!(output.status.code(), Some(2));
+    assert!(stderr(&output).contains("--base is required for 'changed' task"));
+}
+
+#[test]
+fn changes_that_only_landed_on_the_base_are_not_counted() {
+    let sandbox = sandbox();
+    sandbox.git(&["branch", "main"]);
+    sandbox.git(&["checkout", "-q", "-b", "feature"]);
+    sandbox.write("delta/main.go", "package main\n");
+    sandbox.git(&["add", "-A"]);
+    sandbox.git(&["commit", "-qm", "feature"]);
+    sandbox.git(&["checkout", "-q", "main"]);
+    sandbox.write("gamma/main.go", "package main\n");
+    sandbox.git(&["add", "-A"]);
+    sandbox.git(&["commit", "-qm", "moved on"]);
+    sandbox.git(&["checkout", "-q", "feature"]);
+
+    let output = sandbox.run(&["--task", "changed", "--base", "main"]);
+
+    assert!(output.status.success(), "changed failed: {}", stderr(&output));
+    assert_eq!(stdout(&output), "delta\n");
+}
+
+#[test]
+fn json_output_reports_affected_modules_as_events() {
+    let sandbox = sandbox();
+
+    let output = sandbox.run(&["--task", "changed", "--base", "HEAD~1", "--output", "json", "--format", "json"]);
+
+    assert!(output.status.success(), "changed failed: {}", stderr(&output));
+    let events: Vec<serde_json::Value> = stdout(&output).lines().map(|line| serde_json::from_str(line).unwrap()).collect();
+    let affected: Vec<(&str, &str, Option<&str>)> = events
+        .iter()
+        .filter(|e| e["event"] == "module_affected")
+        .map(|e| (e["module"].as_str().unwrap(), e["reason"].as_str().unwrap(), e["dependency"].as_str()))
+        .collect();
+    assert_eq!(affected, [("alpha", "changed", None), ("beta", "depends_on", Some("alpha")), ("gamma", "depends_on", Some("alpha"))]);
+    let summary = events.last().unwrap();
+    assert_eq!((summary["event"].as_str(), summary["total"].as_u64(), summary["succeeded"].as_u64()), (Some("summary"), Some(3), Some(3)));
+}
@@ -0,0 +1,181 @@
+mod common;
+
+use std::sync::Arc;
+
+use common::{stderr, stdout, Sandbox};
+use daggy::changelog::{prepend, render_entry, ChangelogSection, ChangelogUpdate, ReleaseEntry};
+use daggy::command::SystemRunner;
+use daggy::release::{Commit, ReleaseConfig, ReleaseVersion, Releases};
+use daggy::Repo;
+
+const CONFIG: &str = r#"{
+  "packages": {
+    ".": {
+      "changelog-sections": [
+        { "type": "feat", "section": "Features" },
+        { "type": "fix", "section": "Bug Fixes" },
+        { "type": "docs", "section": "Docs" },
+        { "type": "ci", "hidden": true, "section": "CI" }
+      ]
+    }
+  }
+}"#;
+
+fn commit(sandbox: &Sandbox, file: &str, message: &str) -> String {
+    let previous = std::fs::read_to_string(sandbox.root.join(file)).unwrap_or_default();
+    sandbox.write(file, &format!("{}// {}\n", previous, message));
+    sandbox.git(&["add", "-A"]);
+    sandbox.git(&["commit", "-qm", message]);
+    sandbox.git(&["rev-parse", "--short=7", "HEAD"])
+}
+
+fn tag(sandbox: &Sandbox, tag: &str) {
+    sandbox.git(&["tag", "-a", tag, "-m", tag]);
+}
+
+/// `alpha`, released as v1.0.0, then as v1.1.0 after a few more commits.
+fn released_sandbox() -> (Sandbox, Vec<String>) {
+    let sandbox = Sandbox::new();
+    sandbox.add_module("alpha");
+    sandbox.write("release-please-config.json", CONFIG);
+    commit(&sandbox, "alpha/main.go", "feat: initial alpha");
+    tag(&sandbox, "alpha/v1.0.0");
+    let hashes = vec![
+        commit(&sandbox, "alpha/main.go", "feat(api): add a flag"),
+        commit(&sandbox, "alpha/README.md", "docs: describe the flag"),
+        commit(&sandbox, "alpha/main.go", "ci: lint"),
+        commit(&sandbox, "beta/main.go", "fix: unrelated module"),
+        commit(&sandbox, "alpha/main.go", "fix: handle empty input"),
+    ];
+    tag(&sandbox, "alpha/v1.1.0");
+    (sandbox, hashes)
+}
+
+#[test]
+fn changelog_prepends_the_latest_release_to_the_module_changelog() {
+    let (sandbox, hashes) = released_sandbox();
+    sandbox.write("alpha/CHANGELOG.md", "# Changelog\n\n## v1.0.0 (2024-01-01)\n\n\n### Features\n\n* initial alpha (0000000)\n");
+
+    let output = sandbox.run(&["--task", "changelog", "--module", "alpha"]);
+
+    assert!(output.status.success(), "changelog failed: {}", stderr(&output));
+    let date = sandbox.git(&["for-each-ref", "--format=%(creatordate:short)", "refs/tags/alpha/v1.1.0"]);
+    assert_eq!(sandbox.read("alpha/CHANGELOG.md"), format!(
+        "# Changelog\n\n## v1.1.0 ({})\n\n\n### Features\n\n* **api:** add a flag ({})\n\n\n### Bug Fixes\n\n* handle empty input ({})\n\n\n### Docs\n\n* describe the flag ({})\n\n## v1.0.0 (2024-01-01)\n\n\n### Features\n\n* initial alpha (0000000)\n",
+        date, hashes[0], hashes[4], hashes[1]
+    ));
+    assert!(stderr(&output).contains("alpha: 4 commits from alpha/v1.0.0 to alpha/v1.1.0"), "stderr: {}", stderr(&output));
+    assert!(stderr(&output).contains("Added v1.1.0 to alpha/CHANGELOG.md"));
+
+    let again = sandbox.run(&["--task", "changelog", "--module", "alpha"]);
+    assert!(again.status.success());
+    assert!(stderr(&again).contains("alpha/CHANGELOG.md already has an entry for v1.1.0."));
+}
+
+#[test]
+fn changelog_creates_the_file_for_an_older_tag() {
+    let (sandbox, _) = released_sandbox();
+
+    let output = sandbox.run(&["--task", "changelog", "--tag", "alpha/v1.0.0"]);
+
+    assert!(output.status.success(), "changelog failed: {}", stderr(&output));
+    let changelog = sandbox.read("alpha/CHANGELOG.md");
+    assert!(changelog.starts_with("# Changelog\n\n## v1.0.0 ("), "changelog: {}", changelog);
+    assert!(changelog.contains("### Features\n\n* initial alpha ("));
+    assert!(!changelog.contains("add a flag"));
+}
+
+#[test]
+fn notes_print_the_release_notes_of_a_tag() {
+    let (sandbox, hashes) = released_sandbox();
+
+    let output = sandbox.run(&["--task", "changelog", "--tag", "alpha/v1.1.0", "--notes"]);
+
+    assert!(output.status.success(), "changelog failed: {}", stderr(&output));
+    assert!(stdout(&output).starts_with(&format!("### Features\n\n* **api:** add a flag ({})\n", hashes[0])), "stdout: {}", stdout(&output));
+    assert!(!sandbox.root.join("alpha/CHANGELOG.md").exists());
+}
+
+#[test]
+fn a_release_covers_its_prereleases() {
+    let (sandbox, _) = released_sandbox();
+    sandbox.git(&["tag", "-d", "alpha/v1.1.0"]);
+    sandbox.git(&["tag", "-a", "alpha/v1.1.0-rc.1", "-m", "rc", "HEAD~2"]);
+    tag(&sandbox, "alpha/v1.1.0");
+
+    let notes = stdout(&sandbox.run(&["--task", "changelog", "-m", "alpha", "--notes"]));
+    assert!(notes.contains("add a flag") && notes.contains("handle empty input"), "notes: {}", notes);
+
+    let notes = stdout(&sandbox.run(&["--task", "changelog", "--tag", "alpha/v1.1.0-rc.1", "--notes"]));
+    assert!(notes.contains("add a flag") && !notes.contains("handle empty input"), "notes: {}", notes);
+}
+
+#[test]
+fn changelog_needs_a_release_tag() {
+    let sandbox = Sandbox::new();
+    sandbox.add_module("alpha");
+    commit(&sandbox, "alpha/main.go", "feat: initial alpha");
+
+    let output = sandbox.run(&["--task", "changelog", "--module", "alpha"]);
+    assert_eq!(output.status.code(), Some(13));
+    assert!(stderr(&output).contains("cannot release alpha: no release tags yet, create one with --task bump"));
+
+    let output = sandbox.run(&["--task", "changelog", "--tag", "alpha/v9.9.9"]);
+    assert_eq!(output.status.code(), Some(13));
+    assert!(stderr(&output).contains("no release tag alpha/v9.9.9"));
+}
+
+#[test]
+fn entries_list_breaking_changes_first_and_skip_unknown_types() {
+    let commit = |hash: &str, subject: &str, body: &str| Commit { hash: hash.to_string(), subject: subject.to_string(), body: body.to_string() };
+    let commits = [
+        commit("1111111aaaa", "feat!: drop v1", ""),
+        commit("2222222bbbb", "chore: bump deps", ""),
+        commit("3333333cccc", "Merge branch main", ""),
+    ];
+
+    let entry = render_entry(&ReleaseVersion::new(2, 0, 0), "2024-08-07", &commits, &ChangelogSection::defaults());
+
+    assert_eq!(entry, "## v2.0.0 (2024-08-07)\n\n\n### ⚠ BREAKING CHANGES\n\n* drop v1 (1111111)\n\n\n### Features\n\n* drop v1 (1111111)\n");
+    assert_eq!(
+        render_entry(&ReleaseVersion::new(2, 0, 1), "2024-08-08", &commits[1..], &ChangelogSection::defaults()),
+        "## v2.0.1 (2024-08-08)\n\nNo notable changes.\n"
+    );
+}
+
+#[test]
+fn prepend_keeps_a_single_title() {
+    assert_eq!(prepend(None, "## v1.0.0 (d)\n"), "# Changelog\n\n## v1.0.0 (d)\n");
+    assert_eq!(prepend(Some("## v0.1.0 (d)\n"), "## v1.0.0 (d)\n"), "# Changelog\n\n## v1.0.0 (d)\n\n## v0.1.0 (d)\n");
+}
+
+#[test]
+fn json_output_reports_the_notes_as_an_event() {
+    let (sandbox, hashes) = released_sandbox();
+
+    let output = sandbox.run(&["--task", "changelog", "--tag", "alpha/v1.1.0", "--notes", "--output", "json"]);
+
+    assert!(output.status.success(), "changelog failed: {}", stderr(&output));
+    let events: Vec<serde_json::Value> = stdout(&output).lines().map(|line| serde_json::from_str(line).unwrap()).collect();
+    let notes = events.iter().find(|e| e["event"] == "release_notes").expect("a release_notes event");
+    assert_eq!((notes["module"].as_str(), notes["tag"].as_str(), notes["version"].as_str()), (Some("alpha"), Some("alpha/v1.1.0"), Some("v1.1.0")));
+    assert!(notes["notes"].as_str().unwrap().starts_with(&format!("### Features\n\n* **api:** add a flag ({})\n", hashes[0])));
+    assert_eq!(events.last().unwrap()["succeeded"], 1);
+}
+
+#[test]
+fn release_entries_are_written_once() {
+    let (sandbox, _) = released_sandbox();
+    let repo = Repo::at(&sandbox.root);
+    let releases = Releases::new(repo.clone(), Arc::new(SystemRunner::new()));
+    let config = ReleaseConfig::load(&repo).unwrap();
+
+    let entry = ReleaseEntry::read(&releases, &config, "alpha", None).unwrap();
+
+    assert_eq!((entry.tag.as_str(), entry.previous.as_deref(), entry.commits), ("alpha/v1.1.0", Some("alpha/v1.0.0"), 4));
+    assert!(entry.notes().starts_with("### Features\n\n"), "notes: {}", entry.notes());
+    let path = sandbox.root.join("alpha/CHANGELOG.md");
+    assert_eq!(entry.write(&sandbox.root.join("alpha")).unwrap(), ChangelogUpdate::Added(path.clone()));
+    assert_eq!(entry.write(&sandbox.root.join("alpha")).unwrap(), ChangelogUpdate::Present(path));
+    assert_eq!(sandbox.read("alpha/CHANGELOG.md").matches("## v1.1.0").count(), 1);
+}
@@ -0,0 +1,115 @@
+mod common;
+
+use common::{stderr, stdout, Sandbox};
+use serde_json::Value;
+
+fn sandbox() -> Sandbox {
+    let sandbox = Sandbox::new();
+    sandbox.add_module("alpha");
+    sandbox.add_module("beta");
+    sandbox.write("beta/dagger.gen.go", "// stale\npackage main\n");
+    sandbox
+}
+
+#[test]
+fn check_passes_when_develop_changes_nothing() {
+    let sandbox = sandbox();
+
+    let output = sandbox.run(&["--task", "develop", "--check"]);
+
+    assert!(output.status.success(), "check failed: {}", stderr(&output));
+    assert!(stderr(&output).contains("Generated code of all 2 modules is up to date"));
+}
+
+#[test]
+fn check_lists_the_files_develop_changed_and_fails() {
+    let sandbox = sandbox();
+
+    let output = sandbox.run_with_env(&["--task", "develop", "--check", "--output", "json"], &[("DAGGY_FAKE_GENERATE", "beta")]);
+
+    assert_eq!(output.status.code(), Some(12));
+    let err = stderr(&output);
+    assert!(err.contains("dagger develop changed 2 files of beta:\n  modified  dagger.gen.go\n  added     internal/dagger/dagger.gen.go\n"), "stderr: {}", err);
+    assert!(err.contains("Generated code is out of date in: beta (2 files)"));
+    assert!(err.contains("dagger develop changed 2 files in 1 modules"));
+    assert!(sandbox.read("beta/dagger.gen.go").starts_with("// Code generated by dagger"), "files are kept without --restore");
+
+    let events: Vec<Value> = stdout(&output).lines().map(|line| serde_json::from_str(line).unwrap()).collect();
+    let drift = events.iter().find(|e| e["event"] == "develop_drift").unwrap();
+    assert_eq!(drift["module"], "beta");
+    assert_eq!(drift["files"], serde_json::json!([
+        { "path": "dagger.gen.go", "kind": "modified" },
+        { "path": "internal/dagger/dagger.gen.go", "kind": "added" },
+    ]));
+    assert_eq!(drift["restored"], false);
+}
+
+#[test]
+fn restore_puts_the_changed_files_back() {
+    let sandbox = sandbox();
+
+    let output = sandbox.run_with_env(&["--task", "develop", "--check", "--restore"], &[("DAGGY_FAKE_GENERATE", "beta")]);
+
+    assert_eq!(output.status.code(), Some(12));
+    assert!(stderr(&output).contains("The changed files were restored."));
+    assert_eq!(sandbox.read("beta/dagger.gen.go"), "// stale\npackage main\n");
+    assert!(!sandbox.root.join("beta/internal").exists(), "added files and their directories are removed");
+}
+
+#[test]
+fn check_develops_unchanged_modules_too() {
+    let sandbox = sandbox();
+    assert!(sandbox.run(&["--task", "develop"]).status.success());
+    let before = sandbox.invocations().len();
+
+    let output = sandbox.run(&["--task", "develop", "--check", "--module", "alpha"]);
+
+    assert!(output.status.success(), "check failed: {}", stderr(&output));
+    assert_eq!(sandbox.invocations().split_off(before).len(), 1);
+}
+
+#[test]
+fn check_annotates_the_changed_files_on_github() {
+    let sandbox = sandbox();
+
+    let output = sandbox.run_with_env(&["--task", "develop", "--check"], &[("DAGGY_FAKE_GENERATE", "beta"), ("GITHUB_ACTIONS", "true")]);
+
+    assert_eq!(output.status.code(), Some(12));
+    assert!(stderr(&output).contains("::error file=beta/dagger.gen.go,title=generated code is out of date::modified by dagger develop"), "stderr: {}", stderr(&output));
+}
+
+#[test]
+fn restore_requires_check() {
+    let sandbox = sandbox();
+
+    let output = sandbox.run(&["--task", "develop", "--restore"]);
+
+    assert_eq!(output.status.code(), Some(2));
+    assert!(sandbox.invocations().is_empty());
+}
+
+#[test]
+fn drifted_modules_fail_in_the_step_summary_and_the_junit_report() {
+    let sandbox = sandbox();
+    let summary = sandbox.root.join("summary.md");
+
+    let output = sandbox.run_with_env(
+        &["--task", "develop", "--check", "--junit", "develop.xml"],
+        &[("DAGGY_FAKE_GENERATE", "beta"), ("GITHUB_ACTIONS", "true"), ("GITHUB_STEP_SUMMARY", summary.to_str().unwrap())],
+    );
+
+    assert_eq!(output.status.code(), Some(12));
+    assert!(
+        stderr(&output).contains("::error file=beta/dagger.json,title=generated code of beta is out of date::dagger develop changed dagger.gen.go (modified), internal/dagger/dagger.gen.go (added)"),
+        "stderr: {}",
+        stderr(&output)
+    );
+    let markdown = std::fs::read_to_string(&summary).unwrap();
+    assert!(markdown.contains("| `alpha` | ✅ succeeded |"), "summary: {}", markdown);
+    assert!(markdown.contains("| `beta` | ❌ out of date |"));
+    assert!(markdown.contains("develop changed `dagger.gen.go` modified, `internal/dagger/dagger.gen.go` added |"));
+    assert!(markdown.contains("**1 succeeded, 1 failed, 0 skipped** of 2 modules."));
+    let xml = sandbox.read("develop.xml");
+    assert!(xml.contains("tests=\"2\" failures=\"1\""), "xml: {}", xml);
+    assert!(xml.contains("<failure type=\"drift\" message=\"dagger develop changed 2 files, the generated code is out of date\">modified  dagger.gen.go\nadded     internal/dagger/dagger.gen.go</failure>"));
+}
@@ -0,0 +1,55 @@
+mod common;
+
+use std::sync::Arc;
+
+use common::recording::RecordingRunner;
+use daggy::command::{Cmd, CommandRunner, SystemRunner};
+use daggy::publish::{PublishEntry, Publisher};
+use daggy::Repo;
+
+const HOSTILE: &str = "mod; rm -rf ~ $(touch pwned) `id` | cat > x 'q' \"d\"";
+
+#[test]
+fn system_runner_passes_the_argument_vector_without_a_shell() {
+    let dir = tempfile::tempdir().unwrap();
+    let cmd = Cmd::new("printf", dir.path()).args(["%s\\n", HOSTILE, "*", "$HOME"]).capture_output();
+
+    let output = SystemRunner::new().run(&cmd).unwrap();
+
+    assert!(output.success());
+    assert_eq!(String::from_utf8_lossy(&output.stdout), format!("{}\n*\n$HOME\n", HOSTILE));
+    assert!(!dir.path().join("pwned").exists() && !dir.path().join("x").exists(), "nothing was interpreted by a shell");
+}
+
+#[test]
+fn recording_runner_keeps_the_argument_vector_of_module_names() {
+    let recorder = Arc::new(RecordingRunner::new());
+    let publisher = Publisher::new(Repo::at("/repo"), recorder.clone()).with_address("example.com/mods");
+    let entry = PublishEntry { module: HOSTILE.to_string(), version: "v1.0.0".to_string(), tag: "t/v1.0.0".to_string(), commit: "abc".to_string() };
+
+    publisher.publish(&entry).unwrap();
+
+    let calls = recorder.calls();
+    let publish = calls.iter().find(|cmd| cmd.program == "dagger").expect("dagger publish was run");
+    assert_eq!(publish.args, ["publish", "-m", HOSTILE, &format!("example.com/mods/{}@v1.0.0", HOSTILE)]);
+    assert_eq!(publish.cwd.to_str(), Some("/repo/.daggerx/cache/publish/t/v1.0.0"));
+    assert_eq!(calls.iter().map(|cmd| cmd.program.as_str()).collect::<Vec<_>>(), ["git", "dagger", "git"]);
+}
+
+#[test]
+fn command_lines_quote_arguments_like_a_shell_would_need() {
+    let cmd = Cmd::new("dagger", "/").args(["call", "it's", "a b", "plain-arg"]);
+
+    assert_eq!(cmd.to_string(), r"dagger call 'it'\''s' 'a b' plain-arg");
+}
+
+#[test]
+fn stubbed_outputs_answer_matching_commands_only() {
+    let recorder = RecordingRunner::new();
+    recorder.fail_on("git", Some("push"), 128);
+
+    assert!(recorder.run(&Cmd::new("git", "/").arg("status")).unwrap().success());
+    assert_eq!(recorder.run(&Cmd::new("git", "/").arg("push")).unwrap().code, Some(128));
+    assert!(recorder.run_checked(&Cmd::new("git", "/").arg("push")).is_err());
+    assert_eq!(recorder.command_lines(), ["git status", "git push", "git push"]);
+}
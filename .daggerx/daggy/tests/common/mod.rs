@@ -0,0 +1,205 @@
+//! Hermetic sandbox for the daggy integration tests.
+//!
+//! Every sandbox is a fresh git repository holding a copy of `.daggerx/templates`, with
+//! stand-in `dagger` and `go` executables (see `fixtures/fake-tool.sh`) first on PATH.
+//! The stand-ins record their invocations so tests can assert the exact command sequence.
+
+#![allow(dead_code)]
+
+pub mod recording;
+
+use std::env;
+use std::fs;
+use std::os::unix::fs::PermissionsExt;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Output};
+
+use tempfile::TempDir;
+
+pub const FAKE_TOOLS: &[&str] = &["dagger", "go"];
+
+pub struct Sandbox {
+    _dir: TempDir,
+    pub root: PathBuf,
+    pub bin: PathBuf,
+    pub log: PathBuf,
+}
+
+impl Sandbox {
+    /// A git repository with the templates and fake tools in place.
+    pub fn new() -> Self {
+        let sandbox = Self::bare();
+        git(&sandbox.root, &["init", "--quiet"]);
+        // daggy creates tags with the repository's own identity.
+        for (key, value) in [("user.name", "daggy"), ("user.email", "daggy@example.com"), ("tag.gpgsign", "false")] {
+            git(&sandbox.root, &["config", key, value]);
+        }
+        copy_dir(&manifest_dir().join("../templates"), &sandbox.root.join(".daggerx/templates"));
+        sandbox
+    }
+
+    /// A directory that is not a git repository, with the fake tools in place.
+    pub fn bare() -> Self {
+        let dir = tempfile::tempdir().expect("create temp dir");
+        let base = dir.path().canonicalize().expect("canonicalize temp dir");
+        let root = base.join("repo");
+        let bin = base.join("bin");
+        let log = base.join("invocations.log");
+        fs::create_dir_all(&root).unwrap();
+        fs::create_dir_all(&bin).unwrap();
+        fs::write(&log, "").unwrap();
+
+        for tool in FAKE_TOOLS {
+            install_fake_tool(&bin, tool);
+        }
+
+        Self { _dir: dir, root, bin, log }
+    }
+
+    /// A `daggy` command running in the sandbox root with the fake tools on PATH.
+    pub fn daggy(&self) -> Command {
+        let path = env::var_os("PATH").unwrap_or_default();
+        let mut paths = vec![self.bin.clone()];
+        paths.extend(env::split_paths(&path));
+
+        let mut cmd = Command::new(env!("CARGO_BIN_EXE_daggy"));
+        cmd.current_dir(&self.root)
+            .env("PATH", env::join_paths(paths).unwrap())
+            .env("DAGGY_FAKE_LOG", &self.log)
+            .env_remove("DAGGY_FAKE_FAIL_AT")
+            .env_remove("DAGGY_FAKE_FAIL_DIR")
+            .env_remove("DAGGY_FAKE_FAIL_BIN")
+            .env_remove("DAGGY_FAKE_TRANSIENT")
+            .env_remove("DAGGY_FAKE_SLEEP")
+            .env_remove("DAGGY_FAKE_GENERATE")
+            .env_remove("DAGGY_FAKE_DAGGER_VERSION")
+            .env_remove("DAGGY_FAKE_GO_VERSION")
+            .env_remove("GITHUB_ACTIONS")
+            .env_remove("GITHUB_STEP_SUMMARY")
+            .env_remove("DAGGY_DAGGER_BIN")
+            .env_remove("DAGGY_GO_BIN")
+            .env_remove("DAGGY_GIT_BIN");
+        cmd
+    }
+
+    pub fn run(&self, args: &[&str]) -> Output {
+        self.daggy().args(args).output().expect("run daggy")
+    }
+
+    pub fn run_with_env(&self, args: &[&str], envs: &[(&str, &str)]) -> Output {
+        self.daggy().args(args).envs(envs.iter().copied()).output().expect("run daggy")
+    }
+
+    /// Recorded fake tool invocations as `(cwd relative to the root, command line)`.
+    pub fn invocations(&self) -> Vec<(String, String)> {
+        fs::read_to_string(&self.log)
+            .unwrap()
+            .lines()
+            .map(|line| {
+                let (cwd, command) = line.split_once('\t').expect("malformed invocation log line");
+                let cwd = Path::new(cwd).strip_prefix(&self.root).unwrap_or(Path::new(cwd));
+                (cwd.to_string_lossy().into_owned(), command.to_string())
+            })
+            .collect()
+    }
+
+    /// Every file below `rel`, relative to it and sorted.
+    pub fn tree(&self, rel: &str) -> Vec<String> {
+        let base = self.root.join(rel);
+        let mut files = Vec::new();
+        collect_files(&base, &base, &mut files);
+        files.sort();
+        files
+    }
+
+    pub fn read(&self, rel: &str) -> String {
+        fs::read_to_string(self.root.join(rel)).unwrap_or_else(|e| panic!("read {}: {}", rel, e))
+    }
+
+    pub fn write(&self, rel: &str, content: &str) {
+        let path = self.root.join(rel);
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(path, content).unwrap();
+    }
+
+    /// Add a minimal dagger module at `rel`.
+    pub fn add_module(&self, rel: &str) {
+        let name = Path::new(rel).file_name().unwrap().to_string_lossy();
+        self.write(&format!("{}/dagger.json", rel), &format!(
+            "{{\n  \"name\": \"{}\",\n  \"sdk\": \"go\",\n  \"source\": \".\",\n  \"engineVersion\": \"v0.12.4\"\n}}\n",
+            name
+        ));
+    }
+
+    pub fn git(&self, args: &[&str]) -> String {
+        git(&self.root, args)
+    }
+
+    /// A PATH holding only the fake tools, git and the utilities the fakes use, so missing
+    /// tools stay missing even when the real ones are installed.
+    pub fn isolated_path(&self) -> String {
+        let path = env::var_os("PATH").unwrap_or_default();
+        for tool in ["git", "basename", "wc", "tr", "sed", "mv", "sleep", "touch"] {
+            let link = self.bin.join(tool);
+            if link.exists() {
+                continue;
+            }
+            let target = env::split_paths(&path).map(|dir| dir.join(tool)).find(|p| p.is_file());
+            std::os::unix::fs::symlink(target.unwrap_or_else(|| panic!("{} on PATH", tool)), link).unwrap();
+        }
+        self.bin.to_string_lossy().into_owned()
+    }
+}
+
+pub fn manifest_dir() -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+}
+
+pub fn install_fake_tool(bin: &Path, tool: &str) {
+    let dest = bin.join(tool);
+    fs::copy(manifest_dir().join("tests/fixtures/fake-tool.sh"), &dest).unwrap();
+    fs::set_permissions(&dest, fs::Permissions::from_mode(0o755)).unwrap();
+}
+
+pub fn git(dir: &Path, args: &[&str]) -> String {
+    let output = Command::new("git")
+        .args(["-c", "user.name=daggy", "-c", "user.email=daggy@example.com", "-c", "commit.gpgsign=false", "-c", "tag.gpgsign=false"])
+        .args(args)
+        .current_dir(dir)
+        .output()
+        .expect("run git");
+    assert!(output.status.success(), "git {:?} failed: {}", args, String::from_utf8_lossy(&output.stderr));
+    String::from_utf8_lossy(&output.stdout).trim().to_string()
+}
+
+pub fn stdout(output: &Output) -> String {
+    String::from_utf8_lossy(&output.stdout).into_owned()
+}
+
+pub fn stderr(output: &Output) -> String {
+    String::from_utf8_lossy(&output.stderr).into_owned()
+}
+
+fn copy_dir(src: &Path, dst: &Path) {
+    fs::create_dir_all(dst).unwrap();
+    for entry in fs::read_dir(src).unwrap() {
+        let entry = entry.unwrap();
+        let dest = dst.join(entry.file_name());
+        if entry.file_type().unwrap().is_dir() {
+            copy_dir(&entry.path(), &dest);
+        } else {
+            fs::copy(entry.path(), dest).unwrap();
+        }
+    }
+}
+
+fn collect_files(base: &Path, dir: &Path, files: &mut Vec<String>) {
+    for entry in fs::read_dir(dir).unwrap() {
+        let entry = entry.unwrap();
+        if entry.file_type().unwrap().is_dir() {
+            collect_files(base, &entry.path(), files);
+        } else {
+            files.push(entry.path().strip_prefix(base).unwrap().to_string_lossy().into_owned());
+        }
+    }
+}
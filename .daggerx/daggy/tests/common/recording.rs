@@ -0,0 +1,58 @@
+//! A [`CommandRunner`] for tests that records commands instead of running them.
+
+use std::sync::Mutex;
+
+use daggy::command::{Cmd, CommandOutput, CommandRunner};
+use daggy::Result;
+
+/// A fake runner that records every command instead of running it.
+///
+/// Commands succeed with empty output unless a different output was registered for their
+/// program and first argument with [`RecordingRunner::stub`] or [`RecordingRunner::fail_on`].
+#[derive(Debug, Default)]
+pub struct RecordingRunner {
+    calls: Mutex<Vec<Cmd>>,
+    stubs: Mutex<Vec<(String, Option<String>, CommandOutput)>>,
+}
+
+impl RecordingRunner {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Answer `program` (optionally only with `first_arg`) with `output`.
+    pub fn stub(&self, program: &str, first_arg: Option<&str>, output: CommandOutput) {
+        self.stubs.lock().unwrap().push((program.to_string(), first_arg.map(str::to_string), output));
+    }
+
+    /// Make `program` (optionally only with `first_arg`) exit with `code`.
+    pub fn fail_on(&self, program: &str, first_arg: Option<&str>, code: i32) {
+        self.stub(program, first_arg, CommandOutput { code: Some(code), ..Default::default() });
+    }
+
+    /// Every command run so far, in order.
+    pub fn calls(&self) -> Vec<Cmd> {
+        self.calls.lock().unwrap().clone()
+    }
+
+    /// Every command run so far, rendered as command lines.
+    pub fn command_lines(&self) -> Vec<String> {
+        self.calls().iter().map(Cmd::to_string).collect()
+    }
+}
+
+impl CommandRunner for RecordingRunner {
+    fn run(&self, cmd: &Cmd) -> Result<CommandOutput> {
+        self.calls.lock().unwrap().push(cmd.clone());
+
+        let stubs = self.stubs.lock().unwrap();
+        let stub = stubs.iter().find(|(program, first_arg, _)| {
+            *program == cmd.program && first_arg.as_ref().is_none_or(|arg| cmd.args.first() == Some(arg))
+        });
+
+        Ok(match stub {
+            Some((_, _, output)) => output.clone(),
+            None => CommandOutput { code: Some(0), ..Default::default() },
+        })
+    }
+}
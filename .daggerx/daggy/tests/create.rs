@@ -0,0 +1,305 @@
+mod common;
+
+use std::fs;
+
+use common::{stderr, Sandbox};
+use serde_json::Value;
+
+const MODULE: &str = "foo-bar";
+
+fn create_args() -> [&'static str; 4] {
+    ["--task", "create", "--module", MODULE]
+}
+
+/// The command sequence of a successful `create`, as `(cwd, command line)`.
+fn expected_create_commands() -> Vec<(&'static str, &'static str)> {
+    vec![
+        ("foo-bar", "dagger init --sdk go --name foo-bar --source ."),
+        ("foo-bar", "go mod edit -module github.com/Excoriate/daggerverse/foo-bar"),
+        ("foo-bar", "dagger develop -m foo-bar"),
+        ("foo-bar/examples/go", "dagger init --sdk go --name go --source ."),
+        ("foo-bar/examples/go", "go mod edit -module github.com/Excoriate/daggerverse/foo-bar/examples/go"),
+        ("foo-bar/examples/go", "dagger install ../../"),
+        ("foo-bar/examples/go", "dagger develop -m go"),
+        ("foo-bar/tests", "dagger init --sdk go --name tests --source ."),
+        ("foo-bar/tests", "go mod edit -module github.com/Excoriate/daggerverse/foo-bar/tests"),
+        ("foo-bar/tests", "dagger install ../"),
+        ("foo-bar/tests", "dagger develop -m tests"),
+        ("foo-bar", "go fmt ./..."),
+        ("foo-bar/examples/go", "go fmt ./..."),
+        ("foo-bar/tests", "go fmt ./..."),
+    ]
+}
+
+fn to_owned(commands: Vec<(&str, &str)>) -> Vec<(String, String)> {
+    commands.into_iter().map(|(cwd, cmd)| (cwd.to_string(), cmd.to_string())).collect()
+}
+
+#[test]
+fn create_scaffolds_the_full_module_tree() {
+    let sandbox = Sandbox::new();
+
+    let output = sandbox.run(&create_args());
+    assert!(output.status.success(), "create failed: {}", stderr(&output));
+
+    assert_eq!(sandbox.tree(MODULE), vec![
+        "LICENSE",
+        "README.md",
+        "apis.go",
+        "cloud.go",
+        "commands.go",
+        "common.go",
+        "config.go",
+        "dagger.json",
+        "examples/go/dagger.json",
+        "examples/go/go.mod",
+        "examples/go/main.go",
+        "examples/go/testdata/common/README.md",
+        "examples/go/testdata/common/test-file.yml",
+        "go.mod",
+        "main.go",
+        "tests/dagger.json",
+        "tests/go.mod",
+        "tests/main.go",
+        "tests/testdata/common/README.md",
+        "tests/testdata/common/test-file.yml",
+    ]);
+    assert_eq!(sandbox.tree(".github"), vec!["workflows/ci-mod-foo-bar.yaml"]);
+}
+
+#[test]
+fn create_renders_templates_for_the_module() {
+    let sandbox = Sandbox::new();
+    assert!(sandbox.run(&create_args()).status.success());
+
+    let main_go = sandbox.read("foo-bar/main.go");
+    assert!(main_go.contains("type FooBar struct"));
+    assert!(main_go.contains("\"github.com/Excoriate/daggerverse/foo-bar/internal/dagger\""));
+    assert!(!main_go.contains("{{."));
+
+    assert!(sandbox.read("foo-bar/tests/main.go").contains("github.com/Excoriate/daggerverse/foo-bar/tests/internal/dagger"));
+    assert!(sandbox.read("foo-bar/go.mod").starts_with("module github.com/Excoriate/daggerverse/foo-bar\n"));
+    assert!(sandbox.read("foo-bar/examples/go/go.mod").starts_with("module github.com/Excoriate/daggerverse/foo-bar/examples/go\n"));
+
+    let readme = sandbox.read("foo-bar/README.md");
+    assert!(readme.contains("# Module FooBar for Dagger"));
+    assert!(readme.contains("cd foo-bar"));
+    assert!(!readme.contains("{{.module_name}}"));
+
+    assert!(!sandbox.read(".github/workflows/ci-mod-foo-bar.yaml").contains("{{."));
+}
+
+#[test]
+fn create_writes_dagger_json_excludes() {
+    let sandbox = Sandbox::new();
+    assert!(sandbox.run(&create_args()).status.success());
+
+    let excludes = |rel: &str| -> Value {
+        let json: Value = serde_json::from_str(&sandbox.read(rel)).unwrap();
+        json["exclude"].clone()
+    };
+
+    assert_eq!(excludes("foo-bar/dagger.json"), serde_json::json!([
+        "../.direnv", "../.devenv", "../go.work", "../go.work.sum", "tests", "examples/go"
+    ]));
+    assert_eq!(excludes("foo-bar/tests/dagger.json"), serde_json::json!([
+        "../../.direnv", "../../.devenv", "../../go.work", "../../go.work.sum"
+    ]));
+    assert_eq!(excludes("foo-bar/examples/go/dagger.json"), serde_json::json!([
+        "../../../.direnv", "../../../.devenv", "../../../go.work", "../../../go.work.sum"
+    ]));
+}
+
+#[test]
+fn create_runs_commands_in_order() {
+    let sandbox = Sandbox::new();
+    assert!(sandbox.run(&create_args()).status.success());
+
+    assert_eq!(sandbox.invocations(), to_owned(expected_create_commands()));
+}
+
+#[test]
+fn create_stops_at_each_failing_command() {
+    let expected = expected_create_commands();
+    let step_of = |cwd: &str, cmd: &str| match (cwd, cmd) {
+        (_, "go fmt ./...") => "go-fmt",
+        ("foo-bar", _) => "init-module",
+        ("foo-bar/examples/go", _) => "init-examples",
+        ("foo-bar/tests", _) => "init-tests",
+        _ => unreachable!(),
+    };
+
+    for (index, (cwd, cmd)) in expected.iter().enumerate() {
+        let sandbox = Sandbox::new();
+        let fail_at = (index + 1).to_string();
+
+        let output = sandbox.run_with_env(&create_args(), &[("DAGGY_FAKE_FAIL_AT", &fail_at)]);
+
+        assert_eq!(output.status.code(), Some(7), "failing {:?} should exit with 7", cmd);
+        assert_eq!(sandbox.invocations(), to_owned(expected[..=index].to_vec()), "nothing may run after {:?}", cmd);
+        let err = stderr(&output);
+        assert!(err.contains(&format!("while: module foo-bar: step {}", step_of(cwd, cmd))), "unexpected stderr: {}", err);
+        assert!(err.contains(cmd), "stderr should name the failed command: {}", err);
+        assert!(err.contains(&format!("| fake {}: injected failure at invocation {}", &cmd[..cmd.find(' ').unwrap()], fail_at)), "stderr tail missing: {}", err);
+    }
+}
+
+#[test]
+fn create_rejects_existing_module() {
+    let sandbox = Sandbox::new();
+    sandbox.add_module(MODULE);
+
+    let output = sandbox.run(&create_args());
+
+    assert_eq!(output.status.code(), Some(4));
+    assert!(sandbox.invocations().is_empty());
+}
+
+#[test]
+fn create_rejects_module_names_with_shell_metacharacters() {
+    let sandbox = Sandbox::new();
+
+    let output = sandbox.run(&["--task", "create", "--module", "foo;touch pwned"]);
+
+    assert_eq!(output.status.code(), Some(4));
+    assert!(sandbox.invocations().is_empty());
+    assert!(!sandbox.root.join("pwned").exists());
+}
+
+#[test]
+fn create_requires_a_module_name() {
+    let sandbox = Sandbox::new();
+
+    let output = sandbox.run(&["--task", "create"]);
+
+    assert_eq!(output.status.code(), Some(2));
+}
+
+#[test]
+fn create_without_an_engine_writes_the_dagger_files_itself() {
+    let sandbox = Sandbox::new();
+    let path = sandbox.isolated_path();
+    fs::remove_file(sandbox.bin.join("dagger")).unwrap();
+
+    let output = sandbox.run_with_env(&["--task", "create", "--module", MODULE, "--no-engine"], &[("PATH", &path)]);
+
+    assert!(output.status.success(), "create failed: {}", stderr(&output));
+    assert_eq!(sandbox.invocations(), to_owned(vec![
+        ("foo-bar", "go fmt ./..."),
+        ("foo-bar/examples/go", "go fmt ./..."),
+        ("foo-bar/tests", "go fmt ./..."),
+    ]));
+    assert!(stderr(&output).contains("run daggy --task develop once an engine is available"));
+
+    assert_eq!(sandbox.read("foo-bar/tests/dagger.json"), r#"{
+  "name": "tests",
+  "sdk": "go",
+  "exclude": [
+    "../../.direnv",
+    "../../.devenv",
+    "../../go.work",
+    "../../go.work.sum"
+  ],
+  "dependencies": [
+    {
+      "name": "foo-bar",
+      "source": ".."
+    }
+  ],
+  "source": ".",
+  "engineVersion": "v0.12.4"
+}"#);
+    let examples: Value = serde_json::from_str(&sandbox.read("foo-bar/examples/go/dagger.json")).unwrap();
+    assert_eq!(examples["dependencies"], serde_json::json!([{ "name": "foo-bar", "source": "../.." }]));
+    let parent: Value = serde_json::from_str(&sandbox.read("foo-bar/dagger.json")).unwrap();
+    assert_eq!(parent["name"], "foo-bar");
+    assert!(parent.get("dependencies").is_none());
+
+    assert_eq!(sandbox.read("foo-bar/go.mod"), "module github.com/Excoriate/daggerverse/foo-bar\n\ngo 1.22.4\n");
+    assert_eq!(sandbox.read("foo-bar/examples/go/go.mod"), "module github.com/Excoriate/daggerverse/foo-bar/examples/go\n\ngo 1.22.4\n");
+    assert!(sandbox.read("foo-bar/tests/.gitignore").contains("/internal/dagger\n"));
+    assert!(sandbox.read("foo-bar/.gitattributes").contains("/dagger.gen.go linguist-generated\n"));
+    assert!(sandbox.read("foo-bar/main.go").contains("type FooBar struct"));
+}
+
+#[test]
+fn create_without_an_engine_writes_the_go_version_of_the_toolchain() {
+    let sandbox = Sandbox::new();
+    let output = sandbox.run_with_env(&["--task", "create", "--module", "detected", "--no-engine"], &[("DAGGY_FAKE_GO_VERSION", "go1.23.1")]);
+    assert!(output.status.success(), "create failed: {}", stderr(&output));
+    assert!(sandbox.read("detected/go.mod").ends_with("\ngo 1.23.1\n"));
+
+    common::install_fake_tool(&sandbox.bin, "go-1.21.6");
+    let output = sandbox.run(&["--task", "create", "--module", "named", "--no-engine", "--skip-preflight", "--go-bin", "go-1.21.6"]);
+    assert!(output.status.success(), "create failed: {}", stderr(&output));
+    assert!(sandbox.read("named/go.mod").ends_with("\ngo 1.21.6\n"), "without preflight the binary name tells the version");
+
+    let output = sandbox.run(&["--task", "create", "--module", "unknown", "--no-engine", "--skip-preflight"]);
+    assert!(output.status.success(), "create failed: {}", stderr(&output));
+    assert!(sandbox.read("unknown/go.mod").ends_with("\ngo 1.22.5\n"));
+}
+
+#[test]
+fn create_without_an_engine_pins_the_newest_engine_version_of_the_repository() {
+    let sandbox = Sandbox::new();
+    sandbox.write("other/dagger.json", r#"{"name": "other", "sdk": "go", "source": ".", "engineVersion": "v0.13.1"}"#);
+
+    let output = sandbox.run(&["--task", "create", "--module", MODULE, "--no-engine"]);
+
+    assert!(output.status.success(), "create failed: {}", stderr(&output));
+    for rel in ["foo-bar/dagger.json", "foo-bar/tests/dagger.json", "foo-bar/examples/go/dagger.json"] {
+        let json: Value = serde_json::from_str(&sandbox.read(rel)).unwrap();
+        assert_eq!(json["engineVersion"], "v0.13.1", "{}", rel);
+    }
+}
+
+#[test]
+fn create_leaves_out_omitted_components() {
+    let sandbox = Sandbox::new();
+
+    let output = sandbox.run(&["--task", "create", "--module", MODULE, "--no-examples", "--no-workflow"]);
+
+    assert!(output.status.success(), "create failed: {}", stderr(&output));
+    assert!(sandbox.invocations().iter().all(|(cwd, _)| !cwd.contains("examples")), "ran: {:?}", sandbox.invocations());
+    assert!(sandbox.tree(MODULE).iter().all(|file| !file.starts_with("examples/")));
+    assert!(sandbox.tree(MODULE).contains(&"tests/main.go".to_string()));
+    assert!(!sandbox.root.join(".github").exists());
+
+    let parent: Value = serde_json::from_str(&sandbox.read("foo-bar/dagger.json")).unwrap();
+    assert_eq!(parent["exclude"], serde_json::json!(["../.direnv", "../.devenv", "../go.work", "../go.work.sum", "tests"]));
+
+    let readme = sandbox.read("foo-bar/README.md");
+    assert!(readme.contains("## Testing"));
+    assert!(!readme.contains("examples/"), "readme: {}", readme);
+    assert!(!readme.contains("{{if") && !readme.contains("{{end"));
+}
+
+#[test]
+fn create_adjusts_the_workflow_and_readme_to_the_components() {
+    let sandbox = Sandbox::new();
+
+    let output = sandbox.run(&["--task", "create", "--module", MODULE, "--no-tests"]);
+
+    assert!(output.status.success(), "create failed: {}", stderr(&output));
+    let workflow = sandbox.read(".github/workflows/ci-mod-foo-bar.yaml");
+    assert!(!workflow.contains("foo-bar/tests"), "workflow: {}", workflow);
+    assert!(!workflow.contains("module-test:"));
+    assert!(workflow.contains("foo-bar-recipes-go:"));
+    assert!(!workflow.contains("{{if") && !workflow.contains("{{end"));
+
+    let readme = sandbox.read("foo-bar/README.md");
+    assert!(!readme.contains("## Testing"));
+    assert!(!readme.contains("just test"));
+    assert!(readme.contains("## Developer Experience"));
+}
+
+#[test]
+fn create_without_a_readme_still_copies_the_license() {
+    let sandbox = Sandbox::new();
+
+    let output = sandbox.run(&["--task", "create", "--module", MODULE, "--no-readme"]);
+
+    assert!(output.status.success(), "create failed: {}", stderr(&output));
+    assert!(sandbox.root.join("foo-bar/LICENSE").exists());
+    assert!(!sandbox.root.join("foo-bar/README.md").exists());
+}
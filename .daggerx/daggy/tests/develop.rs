@@ -0,0 +1,131 @@
+mod common;
+
+use std::sync::Arc;
+
+use common::recording::RecordingRunner;
+use common::{stderr, Sandbox};
+use daggy::develop::{engine_matrix, Developer, Engine};
+use daggy::log::{Logger, Verbosity};
+use daggy::toolchain::Toolchain;
+use daggy::{DevelopStatus, Repo, Scaffolder};
+
+fn develop_sandbox() -> Sandbox {
+    let sandbox = Sandbox::new();
+    sandbox.add_module("alpha");
+    sandbox.add_module("alpha/tests");
+    sandbox.add_module("alpha/examples/go");
+    sandbox.add_module("beta");
+    sandbox
+}
+
+fn develop_everywhere(modules: &[&str]) -> Vec<(String, String)> {
+    modules.iter().map(|m| (m.to_string(), "dagger develop".to_string())).collect()
+}
+
+#[test]
+fn develop_runs_in_every_module() {
+    let sandbox = develop_sandbox();
+
+    let output = sandbox.run(&["--task", "develop"]);
+
+    assert!(output.status.success(), "develop failed: {}", stderr(&output));
+    assert_eq!(sandbox.invocations(), develop_everywhere(&["alpha", "alpha/examples/go", "alpha/tests", "beta"]));
+    assert!(stderr(&output).contains("Dagger develop completed for all 4 modules successfully!"));
+}
+
+#[test]
+fn develop_continues_past_failing_modules() {
+    let sandbox = develop_sandbox();
+
+    let output = sandbox.run_with_env(&["--task", "develop"], &[("DAGGY_FAKE_FAIL_DIR", "alpha/tests")]);
+
+    assert_eq!(output.status.code(), Some(8));
+    assert_eq!(sandbox.invocations(), develop_everywhere(&["alpha", "alpha/examples/go", "alpha/tests", "beta"]));
+    // stderr is not a terminal here, so the output carries no emoji.
+    let err = stderr(&output);
+    assert!(err.contains("Failed to develop module: alpha/tests"));
+    assert!(err.contains("3 successes and 1 failures."));
+}
+
+#[test]
+fn develop_ignores_the_git_directory() {
+    let sandbox = develop_sandbox();
+    sandbox.add_module(".git/hooks");
+
+    assert!(sandbox.run(&["--task", "develop"]).status.success());
+    assert!(sandbox.invocations().iter().all(|(cwd, _)| !cwd.starts_with(".git")));
+}
+
+#[test]
+fn develop_requires_a_git_repository() {
+    let sandbox = Sandbox::bare();
+
+    let output = sandbox.run(&["--task", "develop", "--error-format", "json"]);
+
+    assert_eq!(output.status.code(), Some(3));
+    assert!(stderr(&output).contains("\"error\":\"not_in_git_repo\""));
+}
+
+#[test]
+fn develop_writes_per_command_logs() {
+    let sandbox = develop_sandbox();
+
+    let output = sandbox.run_with_env(&["--task", "develop", "--command-logs"], &[("DAGGY_FAKE_FAIL_DIR", "beta")]);
+
+    assert_eq!(output.status.code(), Some(8));
+    let logs = sandbox.tree(".daggerx/logs");
+    assert_eq!(logs.len(), 6, "one log per command: {:?}", logs);
+    assert!(logs[0].ends_with("-001-git---version.log"), "preflight comes first: {:?}", logs);
+    assert!(logs[1].ends_with("-002-dagger-version.log"), "preflight comes first: {:?}", logs);
+    assert!(logs[2..].iter().all(|log| log.ends_with("-dagger-develop.log")));
+
+    let failed = sandbox.read(&format!(".daggerx/logs/{}", logs[5]));
+    assert!(failed.starts_with("$ dagger develop\n"));
+    assert!(failed.contains("fake dagger: injected failure in"));
+    assert!(failed.contains("# exit code: Some(1)"));
+}
+
+fn engine(label: &str, recorder: &Arc<RecordingRunner>, sandbox: &Sandbox) -> Engine {
+    let toolchain = Toolchain::new().with_dagger(label);
+    let scaffolder = Scaffolder::new(Repo::at(&sandbox.root)).with_runner(recorder.clone()).with_toolchain(toolchain);
+    Engine { label: label.to_string(), scaffolder }
+}
+
+#[test]
+fn a_matrix_develops_every_module_with_every_engine() {
+    let sandbox = develop_sandbox();
+    let recorder = Arc::new(RecordingRunner::new());
+    recorder.fail_on("dagger-b", Some("develop"), 1);
+    let modules = Repo::at(&sandbox.root).find_dagger_modules().unwrap();
+    let developer = Developer::new(Repo::at(&sandbox.root))
+        .with_engine(engine("dagger-a", &recorder, &sandbox))
+        .with_engine(engine("dagger-b", &recorder, &sandbox));
+
+    let run = developer.develop(&modules[..2]).unwrap();
+
+    assert!(run.interrupted.is_none() && run.pending.is_empty());
+    assert_eq!((run.report.succeeded(), run.report.failed()), (2, 2));
+    assert_eq!(recorder.command_lines(), ["dagger-a develop", "dagger-b develop", "dagger-a develop", "dagger-b develop"]);
+    assert_eq!(
+        engine_matrix(&run.report, &modules[..2], &["dagger-a", "dagger-b"]),
+        "Module × engine matrix:\n  module             dagger-a  dagger-b\n  alpha              ok        FAILED\n  alpha/examples/go  ok        FAILED"
+    );
+}
+
+#[test]
+fn develop_until_failure_leaves_the_modules_after_a_failure_pending() {
+    let sandbox = develop_sandbox();
+    let recorder = Arc::new(RecordingRunner::new());
+    recorder.fail_on("dagger", Some("develop"), 1);
+    let modules = Repo::at(&sandbox.root).find_dagger_modules().unwrap();
+    let developer = Developer::new(Repo::at(&sandbox.root)).with_engine(engine("dagger", &recorder, &sandbox));
+
+    let run = developer.develop_until_failure(&modules);
+
+    assert_eq!(run.report.outcomes.len(), 1);
+    assert!(matches!(run.report.outcomes[0].status, DevelopStatus::Failed(_)));
+    let pending: Vec<&str> = run.pending.iter().map(|m| m.name.as_str()).collect();
+    assert_eq!(pending, ["alpha/examples/go", "alpha/tests", "beta"]);
+    assert_eq!(run.finished(&Logger::new(Verbosity::Normal, false, true)), ["alpha ❌"]);
+    assert_eq!(run.finished(&Logger::new(Verbosity::Normal, false, false)), ["alpha"]);
+}
@@ -0,0 +1,107 @@
+mod common;
+
+use std::process::Output;
+
+use common::{stderr, stdout, Sandbox};
+use serde_json::Value;
+
+fn events(output: &Output) -> Vec<Value> {
+    stdout(output)
+        .lines()
+        .map(|line| serde_json::from_str(line).unwrap_or_else(|e| panic!("not a JSON event: {:?}: {}", line, e)))
+        .collect()
+}
+
+fn kinds(events: &[Value]) -> Vec<&str> {
+    events.iter().map(|event| event["event"].as_str().unwrap()).collect()
+}
+
+#[test]
+fn develop_emits_one_event_per_step() {
+    let sandbox = Sandbox::new();
+    sandbox.add_module("alpha");
+    sandbox.add_module("beta");
+
+    let output = sandbox.run_with_env(&["--task", "develop", "--output", "json"], &[("DAGGY_FAKE_FAIL_DIR", "beta")]);
+
+    assert_eq!(output.status.code(), Some(8), "stderr: {}", stderr(&output));
+    let events = events(&output);
+    assert_eq!(kinds(&events), [
+        "module_discovered",
+        "module_discovered",
+        // Preflight: git --version, dagger version.
+        "command_started",
+        "command_finished",
+        "command_started",
+        "command_finished",
+        "command_started",
+        "command_finished",
+        "develop_succeeded",
+        "command_started",
+        "command_finished",
+        "develop_failed",
+        "error",
+        "summary",
+    ]);
+
+    assert_eq!(events[0]["module"], "alpha");
+    assert_eq!(events[6]["command"], "dagger develop");
+    assert_eq!(events[6]["cwd"], sandbox.root.join("alpha").to_str().unwrap());
+    assert_eq!(events[7]["exit_code"], 0);
+    assert!(events[7]["duration_ms"].is_u64());
+    assert_eq!(events[10]["exit_code"], 1);
+    assert_eq!(events[11]["module"], "beta");
+    assert_eq!(events[11]["error"]["error"], "command_failed");
+    assert_eq!(events[11]["error"]["context"][0], "module beta: dagger develop");
+    assert_eq!(events[12]["error"], "develop_failed");
+    assert_eq!(events[12]["exit_code"], 8);
+
+    let summary = &events[13];
+    assert_eq!(summary["task"], "develop");
+    assert_eq!(summary["status"], "failed");
+    assert_eq!((summary["total"].as_u64(), summary["succeeded"].as_u64(), summary["failed"].as_u64()), (Some(2), Some(1), Some(1)));
+    assert!(events.iter().all(|event| event["timestamp_ms"].is_u64()));
+}
+
+#[test]
+fn create_reports_steps_commands_and_written_files() {
+    let sandbox = Sandbox::new();
+
+    let output = sandbox.run(&["--task", "create", "--module", "foo", "--output", "json"]);
+
+    assert!(output.status.success(), "create failed: {}", stderr(&output));
+    let events = events(&output);
+
+    let steps: Vec<&str> = events.iter().filter(|e| e["event"] == "step_finished").map(|e| e["step"].as_str().unwrap()).collect();
+    assert_eq!(steps, ["init-module", "init-examples", "init-tests", "copy-readme-and-license", "update-readme", "generate-workflow", "go-fmt"]);
+
+    let started = events.iter().filter(|e| e["event"] == "command_started").count();
+    let finished = events.iter().filter(|e| e["event"] == "command_finished").count();
+    // The fake tools do not record the version queries of the preflight checks.
+    assert_eq!(started, sandbox.invocations().len() + 3);
+    assert_eq!(finished, started);
+
+    let written: Vec<&str> = events.iter().filter(|e| e["event"] == "file_written").map(|e| e["path"].as_str().unwrap()).collect();
+    assert!(written.contains(&sandbox.root.join("foo/main.go").to_str().unwrap()), "written: {:?}", written);
+    assert!(written.contains(&sandbox.root.join(".github/workflows/ci-mod-foo.yaml").to_str().unwrap()), "written: {:?}", written);
+
+    let summary = events.last().unwrap();
+    assert_eq!(summary["event"], "summary");
+    assert_eq!(summary["task"], "create");
+    assert_eq!(summary["status"], "succeeded");
+    assert_eq!(summary["succeeded"], 7);
+}
+
+#[test]
+fn errors_before_any_step_still_end_with_a_summary() {
+    let sandbox = Sandbox::bare();
+
+    let output = sandbox.run(&["--task", "develop", "--output", "json"]);
+
+    assert_eq!(output.status.code(), Some(3));
+    let events = events(&output);
+    assert_eq!(kinds(&events), ["error", "summary"]);
+    assert_eq!(events[0]["error"], "not_in_git_repo");
+    assert_eq!(events[1]["status"], "failed");
+    assert_eq!(events[1]["total"], 0);
+}
@@ -0,0 +1,115 @@
+#!/bin/sh
+# Stand-in for the `dagger` and `go` CLIs used by the integration tests.
+#
+# Every invocation is appended to $DAGGY_FAKE_LOG as "<cwd><TAB><program> <args>".
+# Failures can be injected with:
+#   DAGGY_FAKE_FAIL_AT=<n>     fail the n-th recorded invocation
+#   DAGGY_FAKE_FAIL_DIR=<dir>  fail every invocation whose cwd ends with <dir>
+#   DAGGY_FAKE_FAIL_BIN=<name> fail every invocation of the binary called <name>
+#   DAGGY_FAKE_STDERR=<text>   write <text> to stderr with the failures of DAGGY_FAKE_FAIL_DIR
+#   DAGGY_FAKE_TRANSIENT=<n>   fail the first n invocations with an engine connection error
+#   DAGGY_FAKE_SLEEP=<secs>    hang for <secs> first, then touch "$DAGGY_FAKE_LOG.slept"
+#   DAGGY_FAKE_IGNORE_TERM=1   ignore SIGTERM, so only SIGKILL stops the invocation
+#   DAGGY_FAKE_GENERATE=<dir>  let `dagger develop` in a cwd ending with <dir> rewrite
+#                              dagger.gen.go and internal/dagger/dagger.gen.go
+#
+# Version queries answer with $DAGGY_FAKE_DAGGER_VERSION / $DAGGY_FAKE_GO_VERSION and are
+# not recorded, so preflight checks leave the invocation log and failure counting alone.
+# Copies named `dagger-<version>` or `go-<anything>` behave like dagger and go; the dagger
+# copies report v<version>.
+set -eu
+
+bin=$(basename "$0")
+prog=$bin
+version=${DAGGY_FAKE_DAGGER_VERSION:-v0.12.4}
+case "$bin" in
+    dagger-*)
+        prog=dagger
+        version="v${bin#dagger-}"
+        ;;
+    go-*)
+        prog=go
+        ;;
+esac
+case "$prog $*" in
+    "dagger version")
+        echo "dagger $version (registry.dagger.io/engine) linux/amd64"
+        exit 0
+        ;;
+    "go version")
+        echo "go version ${DAGGY_FAKE_GO_VERSION:-go1.22.4} linux/amd64"
+        exit 0
+        ;;
+esac
+
+cwd=$(pwd)
+printf '%s\t%s\n' "$cwd" "$bin $*" >> "$DAGGY_FAKE_LOG"
+count=$(wc -l < "$DAGGY_FAKE_LOG" | tr -d ' ')
+
+if [ -n "${DAGGY_FAKE_IGNORE_TERM:-}" ]; then
+    trap '' TERM
+fi
+if [ -n "${DAGGY_FAKE_SLEEP:-}" ]; then
+    sleep "$DAGGY_FAKE_SLEEP"
+    touch "$DAGGY_FAKE_LOG.slept"
+fi
+if [ "$count" -le "${DAGGY_FAKE_TRANSIENT:-0}" ]; then
+    echo "Error: failed to connect to engine: connection refused" >&2
+    exit 1
+fi
+if [ "${DAGGY_FAKE_FAIL_AT:-}" = "$count" ]; then
+    echo "fake $prog: injected failure at invocation $count" >&2
+    exit 1
+fi
+if [ "${DAGGY_FAKE_FAIL_BIN:-}" = "$bin" ]; then
+    echo "fake $bin: injected failure of $bin" >&2
+    exit 1
+fi
+if [ -n "${DAGGY_FAKE_FAIL_DIR:-}" ]; then
+    case "$cwd" in
+        *"/$DAGGY_FAKE_FAIL_DIR")
+            echo "fake $prog: working in $cwd"
+            echo "fake $prog: injected failure in $cwd" >&2
+            if [ -n "${DAGGY_FAKE_STDERR:-}" ]; then
+                printf '%s\n' "$DAGGY_FAKE_STDERR" >&2
+            fi
+            exit 1
+            ;;
+    esac
+fi
+
+case "$prog $*" in
+    "dagger init "*)
+        name=""
+        while [ $# -gt 0 ]; do
+            case "$1" in
+                --name) name=$2; shift ;;
+            esac
+            shift
+        done
+        cat > dagger.json <<JSON
+{
+  "name": "$name",
+  "sdk": "go",
+  "source": ".",
+  "engineVersion": "v0.12.4"
+}
+JSON
+        printf 'module dagger/%s\n\ngo 1.22.4\n' "$name" > go.mod
+        ;;
+    "dagger develop"*)
+        if [ -n "${DAGGY_FAKE_GENERATE:-}" ]; then
+            case "$cwd" in
+                *"/$DAGGY_FAKE_GENERATE")
+                    mkdir -p internal/dagger
+                    printf '// Code generated by dagger. DO NOT EDIT.\npackage main\n' > dagger.gen.go
+                    printf '// Code generated by dagger. DO NOT EDIT.\npackage dagger\n' > internal/dagger/dagger.gen.go
+                    ;;
+            esac
+        fi
+        ;;
+    "go mod edit -module "*)
+        sed "1s|.*|module $4|" go.mod > go.mod.tmp
+        mv go.mod.tmp go.mod
+        ;;
+esac
@@ -0,0 +1,72 @@
+mod common;
+
+use common::{stderr, Sandbox};
+
+fn github_sandbox() -> Sandbox {
+    let sandbox = Sandbox::new();
+    sandbox.add_module("alpha");
+    sandbox.add_module("beta");
+    sandbox
+}
+
+#[test]
+fn develop_groups_module_output_and_annotates_failures() {
+    let sandbox = github_sandbox();
+
+    let output = sandbox.run_with_env(&["--task", "develop"], &[("GITHUB_ACTIONS", "true"), ("DAGGY_FAKE_FAIL_DIR", "beta")]);
+
+    assert_eq!(output.status.code(), Some(8));
+    let err = stderr(&output);
+    let commands: Vec<&str> = err.lines().filter(|line| line.starts_with("::")).collect();
+    assert_eq!(commands.len(), 5, "stderr: {}", err);
+    assert_eq!(commands[0], "::group::dagger develop: alpha");
+    assert_eq!(commands[1], "::endgroup::");
+    assert_eq!(commands[2], "::group::dagger develop: beta");
+    assert_eq!(commands[3], "::endgroup::");
+    assert!(
+        commands[4].starts_with("::error file=beta/dagger.json,title=dagger develop failed in beta::command `dagger develop`"),
+        "annotation: {}",
+        commands[4]
+    );
+    assert!(commands[4].contains("%0A    | fake dagger: injected failure"), "multi-line messages are escaped: {}", commands[4]);
+
+    // The module output sits inside its group.
+    let group = err.find("::group::dagger develop: beta").unwrap();
+    let failure = err.find("fake dagger: injected failure").unwrap();
+    let end = err[group..].find("::endgroup::").unwrap() + group;
+    assert!(group < failure && failure < end);
+}
+
+#[test]
+fn develop_appends_a_results_table_to_the_step_summary() {
+    let sandbox = github_sandbox();
+    let summary = sandbox.root.join("../step-summary.md");
+    std::fs::write(&summary, "## Earlier step\n\n").unwrap();
+
+    let output = sandbox.run_with_env(&["--task", "develop"], &[
+        ("GITHUB_ACTIONS", "true"),
+        ("GITHUB_STEP_SUMMARY", summary.to_str().unwrap()),
+        ("DAGGY_FAKE_FAIL_DIR", "beta"),
+    ]);
+
+    assert_eq!(output.status.code(), Some(8));
+    let markdown = std::fs::read_to_string(&summary).unwrap();
+    assert!(markdown.starts_with("## Earlier step\n\n### daggy develop\n"), "summary: {}", markdown);
+    assert!(markdown.contains("| Module | Result | Duration | Details |"));
+    assert!(markdown.contains("| `alpha` | ✅ succeeded |"));
+    assert!(markdown.contains("| `beta` | ❌ failed |"));
+    assert!(markdown.contains("command `dagger develop` failed in"));
+    assert!(markdown.contains("**1 succeeded, 1 failed, 0 skipped** of 2 modules."));
+}
+
+#[test]
+fn nothing_github_specific_happens_outside_actions() {
+    let sandbox = github_sandbox();
+    let summary = sandbox.root.join("../step-summary.md");
+
+    let output = sandbox.run_with_env(&["--task", "develop"], &[("GITHUB_STEP_SUMMARY", summary.to_str().unwrap())]);
+
+    assert!(output.status.success(), "develop failed: {}", stderr(&output));
+    assert!(!stderr(&output).lines().any(|line| line.starts_with("::")));
+    assert!(!summary.exists());
+}
@@ -0,0 +1,144 @@
+mod common;
+
+use common::{stderr, stdout, Sandbox};
+use serde_json::Value;
+
+fn developed(sandbox: &Sandbox, from: usize) -> Vec<String> {
+    sandbox.invocations().split_off(from).into_iter().map(|(cwd, _)| cwd).collect()
+}
+
+/// `lib` and `app`, which depends on `lib`, both developed once.
+fn developed_sandbox() -> Sandbox {
+    let sandbox = Sandbox::new();
+    sandbox.add_module("lib");
+    sandbox.write("lib/main.go", "package main\n");
+    sandbox.write("app/dagger.json", concat!(
+        "{\n  \"name\": \"app\",\n  \"sdk\": \"go\",\n  \"source\": \".\",\n",
+        "  \"dependencies\": [{ \"name\": \"lib\", \"source\": \"../lib\" }],\n",
+        "  \"engineVersion\": \"v0.12.4\"\n}\n",
+    ));
+    sandbox.write("app/main.go", "package main\n");
+    let output = sandbox.run(&["--task", "develop"]);
+    assert!(output.status.success(), "develop failed: {}", stderr(&output));
+    sandbox
+}
+
+#[test]
+fn a_second_develop_skips_unchanged_modules() {
+    let sandbox = developed_sandbox();
+    let before = sandbox.invocations().len();
+
+    let output = sandbox.run(&["--task", "develop", "--output", "json"]);
+
+    assert!(output.status.success(), "develop failed: {}", stderr(&output));
+    assert!(developed(&sandbox, before).is_empty());
+    let err = stderr(&output);
+    assert!(err.contains("Skipped lib: unchanged since the last successful develop"), "stderr: {}", err);
+    assert!(err.contains("Skipped 2 unchanged modules: app, lib (use --force to develop them anyway)"));
+    assert!(err.contains("Dagger develop completed: 0 developed, 2 unchanged."));
+
+    let events: Vec<Value> = stdout(&output).lines().map(|line| serde_json::from_str(line).unwrap()).collect();
+    let skipped: Vec<(&str, &str)> = events
+        .iter()
+        .filter(|e| e["event"] == "develop_skipped")
+        .map(|e| (e["module"].as_str().unwrap(), e["reason"].as_str().unwrap()))
+        .collect();
+    assert_eq!(skipped, [("app", "unchanged"), ("lib", "unchanged")]);
+    assert_eq!(events.last().unwrap()["skipped"], 2);
+}
+
+#[test]
+fn a_changed_source_develops_the_module_again() {
+    let sandbox = developed_sandbox();
+    let before = sandbox.invocations().len();
+    sandbox.write("app/main.go", "package main\n\nfunc main() {}\n");
+
+    let output = sandbox.run(&["--task", "develop"]);
+
+    assert!(output.status.success(), "develop failed: {}", stderr(&output));
+    assert_eq!(developed(&sandbox, before), ["app"]);
+    assert!(stderr(&output).contains("Reason: inputs changed"));
+}
+
+#[test]
+fn a_changed_dependency_develops_its_dependents() {
+    let sandbox = developed_sandbox();
+    let before = sandbox.invocations().len();
+    sandbox.write("lib/go.mod", "module lib\n\ngo 1.22.5\n");
+
+    let output = sandbox.run(&["--task", "develop"]);
+
+    assert!(output.status.success(), "develop failed: {}", stderr(&output));
+    assert_eq!(developed(&sandbox, before), ["app", "lib"]);
+}
+
+#[test]
+fn deleted_or_edited_generated_code_develops_the_module_again() {
+    let sandbox = Sandbox::new();
+    sandbox.add_module("alpha");
+    let generate = [("DAGGY_FAKE_GENERATE", "alpha")];
+    assert!(sandbox.run_with_env(&["--task", "develop"], &generate).status.success());
+    let before = sandbox.invocations().len();
+    assert!(sandbox.run_with_env(&["--task", "develop"], &generate).status.success());
+    assert!(developed(&sandbox, before).is_empty(), "regenerated code is unchanged");
+
+    sandbox.write("alpha/dagger.gen.go", "package main\n\n// edited by hand\n");
+    let output = sandbox.run_with_env(&["--task", "develop"], &generate);
+    assert!(output.status.success(), "develop failed: {}", stderr(&output));
+    assert_eq!(developed(&sandbox, before), ["alpha"]);
+    assert!(stderr(&output).contains("Reason: inputs changed"));
+
+    let before = sandbox.invocations().len();
+    std::fs::remove_dir_all(sandbox.root.join("alpha/internal/dagger")).unwrap();
+    assert!(sandbox.run_with_env(&["--task", "develop"], &generate).status.success());
+    assert_eq!(developed(&sandbox, before), ["alpha"]);
+    assert!(sandbox.root.join("alpha/internal/dagger/dagger.gen.go").is_file());
+}
+
+#[test]
+fn force_develops_unchanged_modules() {
+    let sandbox = developed_sandbox();
+    let before = sandbox.invocations().len();
+
+    let output = sandbox.run(&["--task", "develop", "--force"]);
+
+    assert!(output.status.success(), "develop failed: {}", stderr(&output));
+    assert_eq!(developed(&sandbox, before), ["app", "lib"]);
+    assert!(stderr(&output).contains("Dagger develop completed for all 2 modules successfully!"));
+}
+
+#[test]
+fn failed_modules_are_developed_again() {
+    let sandbox = Sandbox::new();
+    sandbox.add_module("alpha");
+    assert_eq!(sandbox.run_with_env(&["--task", "develop"], &[("DAGGY_FAKE_FAIL_DIR", "alpha")]).status.code(), Some(8));
+    let before = sandbox.invocations().len();
+
+    let output = sandbox.run(&["--task", "develop"]);
+
+    assert!(output.status.success(), "develop failed: {}", stderr(&output));
+    assert_eq!(developed(&sandbox, before), ["alpha"]);
+    assert!(stderr(&output).contains("Reason: no previous develop"));
+}
+
+#[test]
+fn modules_with_a_source_directory_fingerprint_it() {
+    let sandbox = Sandbox::new();
+    sandbox.write(
+        "infra/dagger.json",
+        "{\n  \"name\": \"infra\",\n  \"sdk\": \"go\",\n  \"source\": \"dagger\",\n  \"engineVersion\": \"v0.12.4\"\n}\n",
+    );
+    sandbox.write("infra/dagger/main.go", "package main\n");
+    sandbox.write("infra/dagger/go.mod", "module dagger/infra\n\ngo 1.22.4\n");
+    assert!(sandbox.run(&["--task", "develop"]).status.success());
+
+    let before = sandbox.invocations().len();
+    sandbox.write("infra/scripts/main.go", "package main\n");
+    assert!(sandbox.run(&["--task", "develop"]).status.success());
+    assert!(developed(&sandbox, before).is_empty(), "sources outside the source directory are not inputs");
+
+    sandbox.write("infra/dagger/go.mod", "module dagger/infra\n\ngo 1.22.5\n");
+    let output = sandbox.run(&["--task", "develop"]);
+    assert!(output.status.success(), "develop failed: {}", stderr(&output));
+    assert_eq!(developed(&sandbox, before), ["infra"]);
+}
@@ -0,0 +1,146 @@
+mod common;
+
+use std::process::Stdio;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use common::{stderr, Sandbox};
+
+fn sandbox_with(modules: &[&str]) -> Sandbox {
+    let sandbox = Sandbox::new();
+    for module in modules {
+        sandbox.add_module(module);
+    }
+    sandbox
+}
+
+#[test]
+fn transient_engine_errors_are_retried() {
+    let sandbox = sandbox_with(&["alpha", "beta"]);
+
+    let output = sandbox.run_with_env(
+        &["--task", "develop", "--retry-backoff", "10ms"],
+        &[("DAGGY_FAKE_TRANSIENT", "2")],
+    );
+
+    assert!(output.status.success(), "develop failed: {}", stderr(&output));
+    let invocations: Vec<String> = sandbox.invocations().into_iter().map(|(cwd, _)| cwd).collect();
+    assert_eq!(invocations, vec!["alpha", "alpha", "alpha", "beta"]);
+    assert!(stderr(&output).contains("retrying in 10ms (1/2)"));
+}
+
+#[test]
+fn transient_errors_fail_once_retries_are_exhausted() {
+    let sandbox = sandbox_with(&["alpha", "beta"]);
+
+    let output = sandbox.run_with_env(
+        &["--task", "develop", "--retries", "1", "--retry-backoff", "10ms"],
+        &[("DAGGY_FAKE_TRANSIENT", "2")],
+    );
+
+    assert_eq!(output.status.code(), Some(8));
+    let invocations: Vec<String> = sandbox.invocations().into_iter().map(|(cwd, _)| cwd).collect();
+    assert_eq!(invocations, vec!["alpha", "alpha", "beta"]);
+    assert!(stderr(&output).contains("failed to connect to engine"));
+}
+
+#[test]
+fn permanent_failures_are_not_retried() {
+    let sandbox = sandbox_with(&["alpha"]);
+
+    let output = sandbox.run_with_env(&["--task", "develop", "--retry-backoff", "10ms"], &[("DAGGY_FAKE_FAIL_AT", "1")]);
+
+    assert_eq!(output.status.code(), Some(8));
+    assert_eq!(sandbox.invocations().len(), 1);
+}
+
+#[test]
+fn hanging_commands_time_out() {
+    let sandbox = sandbox_with(&["alpha"]);
+    let started = Instant::now();
+
+    let output = sandbox.run_with_env(
+        &["--task", "create", "--module", "slow", "--timeout", "300ms", "--retries", "0"],
+        &[("DAGGY_FAKE_SLEEP", "10")],
+    );
+
+    assert_eq!(output.status.code(), Some(10), "stderr: {}", stderr(&output));
+    assert!(started.elapsed() < Duration::from_secs(5), "timeout was not enforced");
+    assert!(stderr(&output).contains("command `dagger init --sdk go --name slow --source .`"));
+    assert!(stderr(&output).contains("timed out after 300ms"));
+}
+
+#[test]
+fn timed_out_commands_are_not_retried() {
+    let sandbox = sandbox_with(&["alpha"]);
+
+    let output = sandbox.run_with_env(
+        &["--task", "develop", "--timeout", "300ms", "--retry-backoff", "10ms"],
+        &[("DAGGY_FAKE_SLEEP", "10")],
+    );
+
+    assert_eq!(output.status.code(), Some(8), "stderr: {}", stderr(&output));
+    assert_eq!(sandbox.invocations().len(), 1);
+    assert!(!stderr(&output).contains("retrying"));
+}
+
+#[test]
+fn sigint_kills_running_commands_and_reports_progress() {
+    let sandbox = sandbox_with(&["alpha", "beta", "gamma"]);
+
+    let child = sandbox
+        .daggy()
+        .args(["--task", "develop"])
+        .env("DAGGY_FAKE_SLEEP", "3")
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .expect("spawn daggy");
+
+    thread::sleep(Duration::from_millis(700));
+    // SAFETY: plain kill(2) on the child we spawned.
+    unsafe { libc::kill(child.id() as libc::pid_t, libc::SIGINT) };
+    let output = child.wait_with_output().expect("wait for daggy");
+
+    assert_eq!(output.status.code(), Some(130), "stderr: {}", stderr(&output));
+    let err = stderr(&output);
+    assert!(err.contains("Interrupted after 0 of 3 modules."), "stderr: {}", err);
+    assert!(err.contains("Finished modules: none"));
+    assert!(err.contains("Unfinished modules: alpha, beta, gamma"));
+    assert_eq!(sandbox.invocations().len(), 1, "no module may start after the interrupt");
+
+    // The stand-in would touch the marker once its sleep finished; it must have been killed.
+    thread::sleep(Duration::from_secs(3));
+    assert!(!sandbox.log.with_extension("log.slept").exists());
+}
+
+#[test]
+fn a_second_sigint_kills_running_commands_before_exiting() {
+    let sandbox = sandbox_with(&["alpha"]);
+
+    let child = sandbox
+        .daggy()
+        .args(["--task", "develop"])
+        .env("DAGGY_FAKE_SLEEP", "3")
+        .env("DAGGY_FAKE_IGNORE_TERM", "1")
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .expect("spawn daggy");
+
+    thread::sleep(Duration::from_millis(700));
+    // SAFETY: plain kill(2) on the child we spawned.
+    unsafe { libc::kill(child.id() as libc::pid_t, libc::SIGINT) };
+    thread::sleep(Duration::from_millis(300));
+    let second = Instant::now();
+    // SAFETY: as above.
+    unsafe { libc::kill(child.id() as libc::pid_t, libc::SIGINT) };
+    let output = child.wait_with_output().expect("wait for daggy");
+
+    assert_eq!(output.status.code(), Some(130), "stderr: {}", stderr(&output));
+    assert!(second.elapsed() < Duration::from_secs(1), "daggy waited for the grace period");
+
+    // The stand-in ignores SIGTERM; only the SIGKILL sent on the second signal stops it.
+    thread::sleep(Duration::from_secs(3));
+    assert!(!sandbox.log.with_extension("log.slept").exists());
+}
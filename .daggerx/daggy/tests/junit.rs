@@ -0,0 +1,58 @@
+mod common;
+
+use std::path::PathBuf;
+use std::time::Duration;
+
+use common::{stderr, Sandbox};
+use daggy::junit::TestSuite;
+use daggy::{DevelopOutcome, DevelopReport, DevelopStatus, Module};
+
+#[test]
+fn develop_writes_one_test_case_per_module() {
+    let sandbox = Sandbox::new();
+    sandbox.add_module("alpha");
+    sandbox.add_module("beta");
+
+    let output = sandbox.run_with_env(&["--task", "develop", "--junit", "reports/develop.xml"], &[("DAGGY_FAKE_FAIL_DIR", "beta")]);
+
+    assert_eq!(output.status.code(), Some(8), "stderr: {}", stderr(&output));
+    let xml = sandbox.read("reports/develop.xml");
+    assert!(xml.starts_with("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n<testsuites name=\"daggy\" tests=\"2\" failures=\"1\" errors=\"0\" skipped=\"0\""), "xml: {}", xml);
+    assert!(xml.contains("<testsuite name=\"daggy develop\" tests=\"2\" failures=\"1\""));
+    assert!(xml.contains("<testcase name=\"alpha\" classname=\"daggy.develop\" time=\""));
+    assert!(xml.contains("<failure type=\"command_failed\" message=\"command `dagger develop` failed in "));
+    assert!(xml.contains("  while: module beta: dagger develop</failure>"), "xml: {}", xml);
+    assert!(xml.contains(&format!("<system-out>fake dagger: working in {}</system-out>", sandbox.root.join("beta").display())));
+    assert!(xml.contains(&format!("<system-err>fake dagger: injected failure in {}</system-err>", sandbox.root.join("beta").display())));
+}
+
+#[test]
+fn develop_writes_the_report_when_everything_passes() {
+    let sandbox = Sandbox::new();
+    sandbox.add_module("alpha");
+
+    let output = sandbox.run(&["--task", "develop", "--junit", "develop.xml"]);
+
+    assert!(output.status.success(), "develop failed: {}", stderr(&output));
+    let xml = sandbox.read("develop.xml");
+    assert!(xml.contains("tests=\"1\" failures=\"0\" errors=\"0\" skipped=\"0\""));
+    assert!(!xml.contains("<failure"));
+}
+
+#[test]
+fn skipped_and_unreached_modules_are_skipped_test_cases() {
+    let module = |name: &str| Module { name: name.to_string(), path: PathBuf::from("/repo").join(name) };
+    let report = DevelopReport {
+        outcomes: vec![
+            DevelopOutcome { module: module("alpha"), status: DevelopStatus::Succeeded, elapsed: Duration::from_millis(1500), engine: None },
+            DevelopOutcome { module: module("draft"), status: DevelopStatus::Skipped, elapsed: Duration::ZERO, engine: None },
+        ],
+    };
+
+    let xml = TestSuite::from_develop(&report, &[module("gamma")]).to_xml();
+
+    assert!(xml.contains("tests=\"3\" failures=\"0\" errors=\"0\" skipped=\"2\" time=\"1.500\""), "xml: {}", xml);
+    assert!(xml.contains("<testcase name=\"alpha\" classname=\"daggy.develop\" time=\"1.500\"/>"));
+    assert!(xml.contains("<testcase name=\"draft\" classname=\"daggy.develop\" time=\"0.000\">\n      <skipped message=\"no dagger.json found\"/>"));
+    assert!(xml.contains("<testcase name=\"gamma\" classname=\"daggy.develop\" time=\"0.000\">\n      <skipped message=\"interrupted before it ran\"/>"));
+}
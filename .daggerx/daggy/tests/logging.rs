@@ -0,0 +1,93 @@
+mod common;
+
+use common::{stderr, stdout, Sandbox};
+
+fn one_module_sandbox() -> Sandbox {
+    let sandbox = Sandbox::new();
+    sandbox.add_module("alpha");
+    sandbox
+}
+
+#[test]
+fn progress_goes_to_stderr_and_stdout_stays_empty() {
+    let sandbox = one_module_sandbox();
+
+    let output = sandbox.run(&["--task", "develop"]);
+
+    assert!(output.status.success(), "develop failed: {}", stderr(&output));
+    assert_eq!(stdout(&output), "");
+    let err = stderr(&output);
+    assert!(err.contains("Developing module: alpha..."));
+    assert!(err.contains("Successfully developed module: alpha"));
+    assert!(!err.contains("Running command:"), "commands are only echoed with --verbose");
+}
+
+#[test]
+fn quiet_only_prints_the_summary() {
+    let sandbox = one_module_sandbox();
+
+    let output = sandbox.run(&["--task", "develop", "--quiet"]);
+
+    assert!(output.status.success(), "develop failed: {}", stderr(&output));
+    assert_eq!(stderr(&output), "Dagger develop completed for all 1 modules successfully!\n");
+}
+
+#[test]
+fn quiet_still_reports_errors() {
+    let sandbox = one_module_sandbox();
+
+    let output = sandbox.run_with_env(&["--task", "develop", "-q"], &[("DAGGY_FAKE_FAIL_DIR", "alpha")]);
+
+    assert_eq!(output.status.code(), Some(8));
+    let err = stderr(&output);
+    assert!(err.contains("Failed to develop module: alpha"));
+    assert!(err.contains("Error: 1 of 1 modules failed to develop"), "stderr: {}", err);
+    assert!(!err.contains("Developing module"));
+}
+
+#[test]
+fn verbose_echoes_commands_and_directories() {
+    let sandbox = one_module_sandbox();
+
+    let output = sandbox.run(&["--task", "develop", "--verbose"]);
+
+    assert!(output.status.success(), "develop failed: {}", stderr(&output));
+    let err = stderr(&output);
+    assert!(err.contains("Running command: dagger develop"));
+    assert!(err.contains(&format!("Running command in directory: {}", sandbox.root.join("alpha").display())));
+}
+
+#[test]
+fn verbose_and_quiet_conflict() {
+    let sandbox = one_module_sandbox();
+
+    let output = sandbox.run(&["--task", "develop", "--verbose", "--quiet"]);
+
+    assert_eq!(output.status.code(), Some(2));
+}
+
+#[test]
+fn output_has_no_emoji_or_color_without_a_terminal() {
+    let sandbox = one_module_sandbox();
+
+    let output = sandbox.run(&["--task", "develop"]);
+
+    let err = stderr(&output);
+    assert!(!err.contains('\u{1b}'), "no ANSI escapes: {:?}", err);
+    assert!(!err.contains('✅') && !err.contains('🎉'), "no emoji: {}", err);
+}
+
+#[test]
+fn turning_emoji_off_leaves_the_output_of_commands_alone() {
+    let sandbox = one_module_sandbox();
+
+    let output = sandbox.run_with_env(
+        &["--task", "develop"],
+        &[("DAGGY_FAKE_FAIL_DIR", "alpha"), ("DAGGY_FAKE_STDERR", "✗ TestAlpha ⚠ ⌘ ★ failed")],
+    );
+
+    assert_eq!(output.status.code(), Some(8));
+    let err = stderr(&output);
+    assert!(err.contains("✗ TestAlpha ⚠ ⌘ ★ failed"), "stderr: {}", err);
+    assert!(err.contains("Failed to develop module: alpha") && !err.contains('❌'), "stderr: {}", err);
+}
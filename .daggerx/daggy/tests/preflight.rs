@@ -0,0 +1,90 @@
+mod common;
+
+use std::fs;
+
+use common::{stderr, Sandbox};
+
+#[test]
+fn create_fails_fast_when_go_is_missing() {
+    let sandbox = Sandbox::new();
+    let path = sandbox.isolated_path();
+    fs::remove_file(sandbox.bin.join("go")).unwrap();
+
+    let output = sandbox.run_with_env(&["--task", "create", "--module", "foo"], &[("PATH", &path)]);
+
+    assert_eq!(output.status.code(), Some(5), "stderr: {}", stderr(&output));
+    let err = stderr(&output);
+    assert!(err.contains("Error: go is not installed or not on PATH"), "stderr: {}", err);
+    assert!(err.contains("while: preflight checks"));
+    assert!(!sandbox.root.join("foo").exists(), "nothing may be written before the preflight passes");
+    assert!(sandbox.invocations().is_empty());
+}
+
+#[test]
+fn develop_rejects_a_dagger_cli_older_than_an_engine_version() {
+    let sandbox = Sandbox::new();
+    sandbox.add_module("alpha");
+
+    let output = sandbox.run_with_env(&["--task", "develop"], &[("DAGGY_FAKE_DAGGER_VERSION", "v0.12.1")]);
+
+    assert_eq!(output.status.code(), Some(11), "stderr: {}", stderr(&output));
+    assert!(stderr(&output).contains(
+        "Error: dagger: v0.12.1 is older than the engineVersion v0.12.4 of alpha/dagger.json; upgrade the dagger CLI to v0.12.4 or newer"
+    ));
+    assert!(sandbox.invocations().is_empty());
+}
+
+#[test]
+fn create_enforces_the_minimum_dagger_version() {
+    let sandbox = Sandbox::new();
+
+    let output = sandbox.run(&["--task", "create", "--module", "foo", "--min-dagger-version", "v0.13.0"]);
+
+    assert_eq!(output.status.code(), Some(11));
+    assert!(stderr(&output).contains("dagger: v0.12.4 is older than the required minimum v0.13.0"));
+    assert!(!sandbox.root.join("foo").exists());
+}
+
+#[test]
+fn unparseable_versions_are_reported() {
+    let sandbox = Sandbox::new();
+
+    let output = sandbox.run_with_env(&["--task", "create", "--module", "foo"], &[("DAGGY_FAKE_GO_VERSION", "devel")]);
+
+    assert_eq!(output.status.code(), Some(11));
+    assert!(stderr(&output).contains("go: could not find a version in the output of `go version`: \"go version devel linux/amd64\""));
+}
+
+#[test]
+fn malformed_engine_versions_are_dagger_json_errors() {
+    let sandbox = Sandbox::new();
+    sandbox.write("alpha/dagger.json", "{\"name\": \"alpha\", \"engineVersion\": \"latest\"}\n");
+
+    let output = sandbox.run(&["--task", "develop"]);
+
+    assert_eq!(output.status.code(), Some(9));
+    assert!(stderr(&output).contains("alpha/dagger.json: engineVersion \"latest\" is not a version like \"v0.12.4\""));
+}
+
+#[test]
+fn preflight_can_be_skipped() {
+    let sandbox = Sandbox::new();
+    sandbox.add_module("alpha");
+
+    let output = sandbox.run_with_env(&["--task", "develop", "--skip-preflight"], &[("DAGGY_FAKE_DAGGER_VERSION", "v0.11.0")]);
+
+    assert!(output.status.success(), "develop failed: {}", stderr(&output));
+    assert_eq!(sandbox.invocations().len(), 1);
+}
+
+#[test]
+fn passing_checks_report_the_versions_found() {
+    let sandbox = Sandbox::new();
+    sandbox.add_module("alpha");
+
+    let output = sandbox.run(&["--task", "develop"]);
+
+    assert!(output.status.success());
+    assert!(stderr(&output).contains("Preflight checks passed: git v"), "stderr: {}", stderr(&output));
+    assert!(stderr(&output).contains(", dagger v0.12.4"));
+}
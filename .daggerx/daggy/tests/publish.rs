@@ -0,0 +1,191 @@
+mod common;
+
+use std::sync::Arc;
+
+use common::{stderr, stdout, Sandbox};
+use daggy::command::SystemRunner;
+use daggy::publish::{parse_tag, PublishEntry, Publisher};
+use daggy::Repo;
+
+/// `alpha` released as v1.0.0 and v1.1.0, with a v1.2.0-rc.1 on top; `beta` never released.
+fn released_sandbox() -> Sandbox {
+    let sandbox = Sandbox::new();
+    sandbox.add_module("alpha");
+    sandbox.add_module("alpha/tests");
+    sandbox.add_module("beta");
+    for (message, tag) in [("feat: alpha", "alpha/v1.0.0"), ("feat: flag", "alpha/v1.1.0"), ("feat: next", "alpha/v1.2.0-rc.1")] {
+        sandbox.write("alpha/main.go", &format!("// {}\n", message));
+        sandbox.git(&["add", "-A"]);
+        sandbox.git(&["commit", "-qm", message]);
+        sandbox.git(&["tag", "-a", tag, "-m", tag]);
+    }
+    sandbox
+}
+
+fn commit_of(sandbox: &Sandbox, tag: &str) -> String {
+    sandbox.git(&["rev-parse", &format!("{}^{{commit}}", tag)])
+}
+
+#[test]
+fn plan_lists_the_latest_release_of_every_module() {
+    let sandbox = released_sandbox();
+
+    let output = sandbox.run(&["--task", "publish", "--plan"]);
+
+    assert!(output.status.success(), "publish failed: {}", stderr(&output));
+    assert_eq!(stdout(&output), format!("alpha v1.1.0 {}\n", commit_of(&sandbox, "alpha/v1.1.0")));
+    assert!(stderr(&output).contains("Skipped beta: not released yet"), "stderr: {}", stderr(&output));
+    assert!(stderr(&output).contains("1 modules to publish."));
+    assert!(sandbox.invocations().is_empty());
+}
+
+#[test]
+fn plan_of_a_tag_prints_json() {
+    let sandbox = released_sandbox();
+
+    let output = sandbox.run(&["--task", "publish", "--plan", "--tag", "alpha/v1.0.0", "--format", "json"]);
+
+    assert!(output.status.success(), "publish failed: {}", stderr(&output));
+    let plan: serde_json::Value = serde_json::from_str(&stdout(&output)).unwrap();
+    assert_eq!(
+        plan,
+        serde_json::json!([{ "module": "alpha", "version": "v1.0.0", "tag": "alpha/v1.0.0", "commit": commit_of(&sandbox, "alpha/v1.0.0") }])
+    );
+}
+
+#[test]
+fn publish_runs_dagger_publish_in_a_checkout_of_the_tag() {
+    let sandbox = released_sandbox();
+    sandbox.write("alpha/main.go", "// uncommitted\n");
+
+    let output = sandbox.run(&["--task", "publish", "--tag", "alpha/v1.0.0"]);
+
+    assert!(output.status.success(), "publish failed: {}", stderr(&output));
+    assert_eq!(
+        sandbox.invocations(),
+        [(".daggerx/cache/publish/alpha/v1.0.0".to_string(), "dagger publish -m alpha github.com/Excoriate/daggerverse/alpha@v1.0.0".to_string())]
+    );
+    assert!(stderr(&output).contains("Published github.com/Excoriate/daggerverse/alpha@v1.0.0"));
+    assert!(!sandbox.root.join(".daggerx/cache/publish/alpha/v1.0.0").exists(), "the checkout is removed");
+    assert_eq!(sandbox.git(&["worktree", "list"]).lines().count(), 1);
+    assert_eq!(sandbox.read("alpha/main.go"), "// uncommitted\n", "the working tree is left alone");
+}
+
+#[test]
+fn a_failed_publish_fails_the_task_and_cleans_up() {
+    let sandbox = released_sandbox();
+
+    let output = sandbox.run_with_env(
+        &["--task", "publish", "--address", "example.com/mods", "--retries", "0"],
+        &[("DAGGY_FAKE_FAIL_BIN", "dagger")],
+    );
+
+    assert_eq!(output.status.code(), Some(14));
+    assert!(stderr(&output).contains("module alpha: dagger publish"), "stderr: {}", stderr(&output));
+    assert_eq!(sandbox.invocations()[0].1, "dagger publish -m alpha example.com/mods/alpha@v1.1.0");
+    assert_eq!(sandbox.git(&["worktree", "list"]).lines().count(), 1);
+}
+
+#[test]
+fn the_modules_after_a_failed_publish_are_still_published() {
+    let sandbox = released_sandbox();
+    sandbox.git(&["tag", "-a", "beta/v0.1.0", "-m", "beta/v0.1.0"]);
+
+    let output = sandbox.run_with_env(&["--task", "publish", "--retries", "0"], &[("DAGGY_FAKE_FAIL_DIR", "alpha/v1.1.0")]);
+
+    assert_eq!(output.status.code(), Some(14), "stderr: {}", stderr(&output));
+    let published: Vec<String> = sandbox.invocations().into_iter().map(|(_, invocation)| invocation).collect();
+    assert_eq!(
+        published,
+        [
+            "dagger publish -m alpha github.com/Excoriate/daggerverse/alpha@v1.1.0",
+            "dagger publish -m beta github.com/Excoriate/daggerverse/beta@v0.1.0",
+        ]
+    );
+    let err = stderr(&output);
+    assert!(err.contains("Published github.com/Excoriate/daggerverse/beta@v0.1.0"), "stderr: {}", err);
+    assert!(err.contains("Published 1 modules, 1 failed: alpha v1.1.0"), "stderr: {}", err);
+    assert!(err.contains("1 of 2 modules failed to publish"), "stderr: {}", err);
+}
+
+#[test]
+fn unreleased_modules_are_listed_as_skipped() {
+    let sandbox = released_sandbox();
+
+    let output = sandbox.run(&["--task", "publish", "--output", "json"]);
+
+    assert!(output.status.success(), "publish failed: {}", stderr(&output));
+    assert!(stderr(&output).contains("Skipped 1 unreleased modules: beta"), "stderr: {}", stderr(&output));
+    let summary: serde_json::Value = serde_json::from_str(stdout(&output).lines().last().unwrap()).unwrap();
+    assert_eq!((summary["total"].as_u64(), summary["succeeded"].as_u64(), summary["skipped"].as_u64()), (Some(2), Some(1), Some(1)));
+}
+
+#[test]
+fn the_tag_must_release_a_module_of_the_same_name() {
+    let sandbox = released_sandbox();
+    sandbox.write("gamma/dagger.json", "{ \"name\": \"delta\", \"sdk\": \"go\" }\n");
+    sandbox.git(&["add", "-A"]);
+    sandbox.git(&["commit", "-qm", "gamma"]);
+    sandbox.git(&["tag", "gamma/v1.0.0"]);
+    sandbox.git(&["tag", "docs/v1.0.0"]);
+
+    let output = sandbox.run(&["--task", "publish", "--plan", "--tag", "gamma/v1.0.0"]);
+    assert_eq!(output.status.code(), Some(13));
+    assert!(stderr(&output).contains("cannot release gamma: the dagger.json at gamma/v1.0.0 names the module \"delta\", not \"gamma\""));
+
+    let output = sandbox.run(&["--task", "publish", "--plan", "--tag", "docs/v1.0.0"]);
+    assert_eq!(output.status.code(), Some(13));
+    assert!(stderr(&output).contains("docs/v1.0.0 has no module directory docs with a dagger.json"));
+
+    let output = sandbox.run(&["--task", "publish", "--plan", "--tag", "alpha/v9.0.0"]);
+    assert_eq!(output.status.code(), Some(13));
+    assert!(stderr(&output).contains("no release tag alpha/v9.0.0"));
+
+    let output = sandbox.run(&["--task", "publish", "--plan", "--tag", "alpha/latest"]);
+    assert_eq!(output.status.code(), Some(2));
+}
+
+#[test]
+fn tags_split_into_module_and_version() {
+    let (module, version) = parse_tag("alpha/v1.2.3-rc.1").unwrap();
+    assert_eq!((module.as_str(), version.to_string().as_str()), ("alpha", "v1.2.3-rc.1"));
+    assert!(parse_tag("v1.2.3").is_err());
+    assert!(parse_tag("/v1.2.3").is_err());
+    assert!(parse_tag("alpha/vnext").is_err());
+}
+
+#[test]
+fn json_output_reports_the_plan_and_publishes_as_events() {
+    let sandbox = released_sandbox();
+
+    let output = sandbox.run(&["--task", "publish", "--output", "json"]);
+
+    assert!(output.status.success(), "publish failed: {}", stderr(&output));
+    let events: Vec<serde_json::Value> = stdout(&output).lines().map(|line| serde_json::from_str(line).unwrap()).collect();
+    let planned = events.iter().find(|e| e["event"] == "publish_planned").expect("a publish_planned event");
+    assert_eq!((planned["module"].as_str(), planned["tag"].as_str()), (Some("alpha"), Some("alpha/v1.1.0")));
+    assert_eq!(planned["commit"].as_str(), Some(commit_of(&sandbox, "alpha/v1.1.0").as_str()));
+    assert!(events.iter().any(|e| e["event"] == "publish_skipped" && e["module"] == "beta"));
+    let published = events.iter().find(|e| e["event"] == "module_published").expect("a module_published event");
+    assert_eq!(published["reference"], "github.com/Excoriate/daggerverse/alpha@v1.1.0");
+    let summary = events.last().unwrap();
+    assert_eq!((summary["total"].as_u64(), summary["succeeded"].as_u64(), summary["skipped"].as_u64()), (Some(2), Some(1), Some(1)));
+}
+
+#[test]
+fn the_plan_holds_the_latest_releases_and_the_unreleased_modules() {
+    let sandbox = released_sandbox();
+    let repo = Repo::at(&sandbox.root);
+    let modules = vec![repo.module("alpha").unwrap(), repo.module("beta").unwrap()];
+
+    let plan = Publisher::new(repo, Arc::new(SystemRunner::new())).plan(&modules).unwrap();
+
+    let alpha = PublishEntry {
+        module: "alpha".to_string(),
+        version: "v1.1.0".to_string(),
+        tag: "alpha/v1.1.0".to_string(),
+        commit: commit_of(&sandbox, "alpha/v1.1.0"),
+    };
+    assert_eq!(plan.entries, vec![alpha]);
+    assert_eq!(plan.unreleased, vec!["beta"]);
+}
@@ -0,0 +1,113 @@
+mod common;
+
+use common::{stderr, stdout, Sandbox};
+use serde_json::Value;
+
+const MODULE: &str = "foo-bar";
+
+fn create_args() -> [&'static str; 4] {
+    ["--task", "create", "--module", MODULE]
+}
+
+fn state(sandbox: &Sandbox) -> Value {
+    serde_json::from_str(&sandbox.read("foo-bar/.daggy-create.json")).unwrap()
+}
+
+#[test]
+fn a_failed_create_records_its_finished_steps() {
+    let sandbox = Sandbox::new();
+
+    // The 6th invocation is `dagger install ../../` of the examples module.
+    let output = sandbox.run_with_env(&create_args(), &[("DAGGY_FAKE_FAIL_AT", "6")]);
+
+    assert_eq!(output.status.code(), Some(7));
+    assert_eq!(state(&sandbox), serde_json::json!({
+        "module": "foo-bar",
+        "offline": false,
+        "components": { "examples": true, "tests": true, "workflow": true, "readme": true },
+        "completed": ["init-module"],
+    }));
+    assert!(stderr(&output).contains("Continue with: daggy --task create --resume foo-bar"));
+}
+
+#[test]
+fn resume_reruns_the_failed_step_and_continues() {
+    let sandbox = Sandbox::new();
+    assert_eq!(sandbox.run_with_env(&create_args(), &[("DAGGY_FAKE_FAIL_AT", "6")]).status.code(), Some(7));
+    let failed_run = sandbox.invocations().len();
+
+    let output = sandbox.run(&["--task", "create", "--resume", MODULE, "--output", "json"]);
+
+    assert!(output.status.success(), "resume failed: {}", stderr(&output));
+    let resumed: Vec<(String, String)> = sandbox.invocations().split_off(failed_run);
+    // `dagger init` already ran for the examples before the failure.
+    assert_eq!(resumed, [
+        ("foo-bar/examples/go", "go mod edit -module github.com/Excoriate/daggerverse/foo-bar/examples/go"),
+        ("foo-bar/examples/go", "dagger install ../../"),
+        ("foo-bar/examples/go", "dagger develop -m go"),
+        ("foo-bar/tests", "dagger init --sdk go --name tests --source ."),
+        ("foo-bar/tests", "go mod edit -module github.com/Excoriate/daggerverse/foo-bar/tests"),
+        ("foo-bar/tests", "dagger install ../"),
+        ("foo-bar/tests", "dagger develop -m tests"),
+        ("foo-bar", "go fmt ./..."),
+        ("foo-bar/examples/go", "go fmt ./..."),
+        ("foo-bar/tests", "go fmt ./..."),
+    ].map(|(cwd, cmd)| (cwd.to_string(), cmd.to_string())));
+    assert!(!sandbox.root.join("foo-bar/.daggy-create.json").exists(), "the state file is removed on success");
+    assert!(sandbox.tree(MODULE).contains(&"examples/go/main.go".to_string()));
+
+    let events: Vec<Value> = stdout(&output).lines().map(|line| serde_json::from_str(line).unwrap()).collect();
+    let skipped: Vec<&str> = events.iter().filter(|e| e["event"] == "step_skipped").map(|e| e["step"].as_str().unwrap()).collect();
+    assert_eq!(skipped, ["init-module"]);
+    let summary = events.last().unwrap();
+    assert_eq!((summary["succeeded"].as_u64(), summary["skipped"].as_u64()), (Some(6), Some(1)));
+}
+
+#[test]
+fn resume_keeps_an_offline_create_offline() {
+    let sandbox = Sandbox::new();
+    let output = sandbox.run_with_env(&["--task", "create", "--module", MODULE, "--no-engine"], &[("DAGGY_FAKE_FAIL_AT", "1")]);
+    assert_eq!(output.status.code(), Some(7));
+    assert_eq!(state(&sandbox)["offline"], true);
+
+    let output = sandbox.run(&["--task", "create", "--resume", MODULE]);
+
+    assert!(output.status.success(), "resume failed: {}", stderr(&output));
+    assert!(sandbox.invocations().iter().all(|(_, cmd)| cmd == "go fmt ./..."), "ran: {:?}", sandbox.invocations());
+}
+
+#[test]
+fn create_points_at_resume_when_an_unfinished_module_exists() {
+    let sandbox = Sandbox::new();
+    sandbox.run_with_env(&create_args(), &[("DAGGY_FAKE_FAIL_AT", "2")]);
+
+    let output = sandbox.run(&create_args());
+
+    assert_eq!(output.status.code(), Some(4));
+    assert!(stderr(&output).contains("continue it with --resume foo-bar"));
+}
+
+#[test]
+fn resume_needs_a_state_file() {
+    let sandbox = Sandbox::new();
+    sandbox.add_module(MODULE);
+
+    let output = sandbox.run(&["--task", "create", "--resume", MODULE]);
+
+    assert_eq!(output.status.code(), Some(2));
+    assert!(stderr(&output).contains("nothing to resume for module \"foo-bar\""));
+    assert!(sandbox.invocations().is_empty());
+}
+
+#[test]
+fn resume_creates_the_components_of_the_first_attempt() {
+    let sandbox = Sandbox::new();
+    let output = sandbox.run_with_env(&["--task", "create", "--module", MODULE, "--no-tests"], &[("DAGGY_FAKE_FAIL_AT", "5")]);
+    assert_eq!(output.status.code(), Some(7));
+
+    let output = sandbox.run(&["--task", "create", "--resume", MODULE]);
+
+    assert!(output.status.success(), "resume failed: {}", stderr(&output));
+    assert!(!sandbox.root.join("foo-bar/tests").exists());
+    assert!(sandbox.invocations().iter().all(|(cwd, _)| !cwd.ends_with("tests")));
+}
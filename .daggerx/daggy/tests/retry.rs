@@ -0,0 +1,81 @@
+mod common;
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use common::recording::RecordingRunner;
+use daggy::command::{Cmd, CommandOutput, CommandRunner, Interruption};
+use daggy::retry::{is_transient, RetryPolicy, RetryingRunner};
+
+fn connection_refused() -> CommandOutput {
+    CommandOutput { code: Some(1), stderr: b"Error: failed to connect to engine: connection refused\n".to_vec(), ..Default::default() }
+}
+
+fn retrying(recorder: &Arc<RecordingRunner>) -> RetryingRunner {
+    let policy = RetryPolicy { max_retries: 2, initial_backoff: Duration::from_millis(1), ..RetryPolicy::default() };
+    RetryingRunner::new(recorder.clone(), policy).with_programs(["dagger", "/opt/dagger-0.13.0"])
+}
+
+#[test]
+fn engine_connection_errors_of_dagger_are_retried() {
+    let recorder = Arc::new(RecordingRunner::new());
+    recorder.stub("/opt/dagger-0.13.0", Some("develop"), connection_refused());
+
+    let output = retrying(&recorder).run(&Cmd::new("/opt/dagger-0.13.0", "/").arg("develop")).unwrap();
+
+    assert!(!output.success());
+    assert_eq!(recorder.calls().len(), 3);
+}
+
+#[test]
+fn other_programs_and_publish_run_once() {
+    let recorder = Arc::new(RecordingRunner::new());
+    recorder.stub("git", None, connection_refused());
+    recorder.stub("dagger", Some("publish"), connection_refused());
+    let runner = retrying(&recorder);
+
+    runner.run(&Cmd::new("git", "/").args(["push", "origin", "refs/tags/alpha/v1.0.0"])).unwrap();
+    runner.run(&Cmd::new("dagger", "/").args(["publish", "-m", "alpha"])).unwrap();
+
+    assert_eq!(recorder.command_lines(), ["git push origin refs/tags/alpha/v1.0.0", "dagger publish -m alpha"]);
+}
+
+#[test]
+fn timeouts_are_not_transient() {
+    let timed_out = CommandOutput { interrupted: Some(Interruption::TimedOut(Duration::from_secs(1))), ..connection_refused() };
+    let cancelled = CommandOutput { interrupted: Some(Interruption::Cancelled), ..connection_refused() };
+
+    assert!(is_transient(&connection_refused()));
+    assert!(!is_transient(&timed_out));
+    assert!(!is_transient(&cancelled));
+    assert!(!is_transient(&CommandOutput { code: Some(1), stderr: b"go: module not found\n".to_vec(), ..Default::default() }));
+}
+
+#[test]
+fn module_errors_mentioning_connection_problems_are_not_transient() {
+    let failed = |stderr: &str| CommandOutput { code: Some(1), stderr: stderr.as_bytes().to_vec(), ..Default::default() };
+    let module_errors = [
+        "Error: response from query has errors: process \"go test ./...\" did not complete successfully: context deadline exceeded\n",
+        "Error: input: alpha.test resolve: call function \"Test\": failed to get engine session: session closed\n",
+        "dial tcp 10.0.0.1:443: connect: connection refused\nError: response from query has errors: exit code: 1\n",
+        "panic: failed to connect to engine\nError: process \"go run .\" did not complete successfully: exit code: 2\n",
+    ];
+
+    for stderr in module_errors {
+        assert!(!is_transient(&failed(stderr)), "retried: {}", stderr);
+    }
+    assert!(is_transient(&failed("Error: start engine: failed to pull the engine image: i/o timeout\n")));
+    assert!(is_transient(&failed("Error: rpc error: code = Unavailable desc = connection reset by peer\n")));
+}
+
+#[test]
+fn a_failed_module_runs_once() {
+    let recorder = Arc::new(RecordingRunner::new());
+    let failed = b"Error: response from query has errors: call function \"Test\": context deadline exceeded\n";
+    recorder.stub("dagger", Some("call"), CommandOutput { code: Some(1), stderr: failed.to_vec(), ..Default::default() });
+
+    let output = retrying(&recorder).run(&Cmd::new("dagger", "/").args(["call", "test"])).unwrap();
+
+    assert!(!output.success());
+    assert_eq!(recorder.calls().len(), 1);
+}
@@ -0,0 +1,154 @@
+mod common;
+
+use std::path::{Path, PathBuf};
+
+use common::{stderr, Sandbox};
+use daggy::select::{glob_match, owning_module, ModuleKind};
+use daggy::Module;
+
+fn sandbox() -> Sandbox {
+    let sandbox = Sandbox::new();
+    for module in ["alpha", "alpha/tests", "alpha/examples/go", "beta", "beta/tests"] {
+        sandbox.add_module(module);
+    }
+    sandbox
+}
+
+fn developed(sandbox: &Sandbox) -> Vec<String> {
+    sandbox.invocations().into_iter().map(|(cwd, _)| cwd).collect()
+}
+
+fn module(name: &str) -> Module {
+    Module { name: name.to_string(), path: PathBuf::from("/repo").join(name) }
+}
+
+#[test]
+fn module_selects_a_module_and_the_modules_nested_in_it() {
+    let sandbox = sandbox();
+
+    let output = sandbox.run(&["--task", "develop", "--module", "beta"]);
+
+    assert!(output.status.success(), "develop failed: {}", stderr(&output));
+    assert_eq!(developed(&sandbox), ["beta", "beta/tests"]);
+    assert!(stderr(&output).contains("Selected 2 of 5 modules"));
+}
+
+#[test]
+fn module_is_repeatable_and_combines_with_exclude() {
+    let sandbox = sandbox();
+
+    let output = sandbox.run(&["--task", "develop", "-m", "alpha", "-m", "beta/", "--exclude", "*/examples/**"]);
+
+    assert!(output.status.success(), "develop failed: {}", stderr(&output));
+    assert_eq!(developed(&sandbox), ["alpha", "alpha/tests", "beta", "beta/tests"]);
+}
+
+#[test]
+fn only_flags_select_by_kind() {
+    let sandbox = sandbox();
+
+    let output = sandbox.run(&["--task", "develop", "--only-tests", "--only-examples"]);
+
+    assert!(output.status.success(), "develop failed: {}", stderr(&output));
+    assert_eq!(developed(&sandbox), ["alpha/examples/go", "alpha/tests", "beta/tests"]);
+
+    let before = sandbox.invocations().len();
+    sandbox.run(&["--task", "develop", "--only-parents", "--force"]);
+    assert_eq!(developed(&sandbox).split_off(before), ["alpha", "beta"]);
+}
+
+#[test]
+fn changed_since_selects_the_modules_owning_changed_files() {
+    let sandbox = sandbox();
+    sandbox.git(&["add", "-A"]);
+    sandbox.git(&["commit", "-qm", "modules"]);
+    sandbox.write("alpha/tests/main.go", "package main\n");
+    sandbox.write("beta/main.go", "package main\n");
+    sandbox.git(&["add", "-A"]);
+    sandbox.git(&["commit", "-qm", "change"]);
+    sandbox.write("README.md", "outside of any module\n");
+
+    let output = sandbox.run(&["--task", "develop", "--changed-since", "HEAD~1"]);
+
+    assert!(output.status.success(), "develop failed: {}", stderr(&output));
+    assert_eq!(developed(&sandbox), ["alpha/tests", "beta"]);
+}
+
+#[test]
+fn changed_since_selects_the_modules_owning_untracked_files() {
+    let sandbox = sandbox();
+    sandbox.write(".gitignore", "*.log\n");
+    sandbox.git(&["add", "-A"]);
+    sandbox.git(&["commit", "-qm", "modules"]);
+    sandbox.write("beta/tests/new.go", "package main\n");
+    sandbox.write("alpha/debug.log", "ignored\n");
+
+    let output = sandbox.run(&["--task", "develop", "--changed-since", "HEAD"]);
+
+    assert!(output.status.success(), "develop failed: {}", stderr(&output));
+    assert_eq!(developed(&sandbox), ["beta/tests"]);
+}
+
+#[test]
+fn changed_since_needs_a_valid_ref() {
+    let sandbox = sandbox();
+
+    let output = sandbox.run(&["--task", "develop", "--changed-since", "no-such-ref"]);
+
+    assert_eq!(output.status.code(), Some(7));
+    assert!(stderr(&output).contains("listing the files changed since no-such-ref"));
+    assert!(sandbox.invocations().is_empty());
+}
+
+#[test]
+fn an_unknown_module_is_a_usage_error() {
+    let sandbox = sandbox();
+
+    let output = sandbox.run(&["--task", "develop", "--module", "gamma"]);
+
+    assert_eq!(output.status.code(), Some(2));
+    assert!(stderr(&output).contains("no module named \"gamma\""));
+}
+
+#[test]
+fn create_takes_a_single_module() {
+    let sandbox = Sandbox::new();
+
+    let output = sandbox.run(&["--task", "create", "--module", "foo", "--module", "bar"]);
+
+    assert_eq!(output.status.code(), Some(2));
+    assert!(stderr(&output).contains("create takes a single --module"));
+}
+
+#[test]
+fn module_kinds_follow_the_repository_layout() {
+    assert_eq!(ModuleKind::of(&module("alpha")), ModuleKind::Parent);
+    assert_eq!(ModuleKind::of(&module("alpha/tests")), ModuleKind::Tests);
+    assert_eq!(ModuleKind::of(&module("alpha/examples/go")), ModuleKind::Examples);
+    assert_eq!(ModuleKind::of(&module("tests")), ModuleKind::Parent);
+    assert_eq!(ModuleKind::of(&module("examples")), ModuleKind::Parent);
+}
+
+#[test]
+fn files_belong_to_their_innermost_module() {
+    let modules = [module("alpha"), module("alpha/tests"), module("alphabet")];
+
+    let owner = |file: &str| owning_module(&modules, Path::new(file)).map(|m| m.name.as_str());
+
+    assert_eq!(owner("alpha/main.go"), Some("alpha"));
+    assert_eq!(owner("alpha/tests/main.go"), Some("alpha/tests"));
+    assert_eq!(owner("alphabet/go.mod"), Some("alphabet"));
+    assert_eq!(owner("justfile"), None);
+}
+
+#[test]
+fn globs_match_within_and_across_segments() {
+    assert!(glob_match("alpha", "alpha"));
+    assert!(glob_match("*/tests", "alpha/tests"));
+    assert!(!glob_match("*", "alpha/tests"));
+    assert!(glob_match("alpha/**", "alpha/examples/go"));
+    assert!(glob_match("**/go", "alpha/examples/go"));
+    assert!(glob_match("alpha/**/go", "alpha/go"));
+    assert!(glob_match("bet?", "beta"));
+    assert!(!glob_match("alph?", "alpha/tests"));
+}
@@ -0,0 +1,134 @@
+mod common;
+
+use std::path::PathBuf;
+
+use common::{stderr, stdout, Sandbox};
+use daggy::command::SystemRunner;
+use daggy::status::{table, ModuleStatus};
+use daggy::Repo;
+
+/// `alpha`, released as v1.2.0 with a fix and a feat since; `beta`, never released; `gamma`,
+/// released with nothing since but a malformed tag and an uncommitted file.
+fn sandbox() -> Sandbox {
+    let sandbox = Sandbox::new();
+    for module in ["alpha", "alpha/tests", "beta", "gamma"] {
+        sandbox.add_module(module);
+    }
+    commit(&sandbox, "alpha/main.go", "feat: initial modules");
+    sandbox.git(&["tag", "-a", "alpha/v1.2.0", "-m", "v1.2.0"]);
+    sandbox.git(&["tag", "-a", "gamma/v0.1.0", "-m", "v0.1.0"]);
+    sandbox.git(&["tag", "gamma/vnext"]);
+    commit(&sandbox, "alpha/main.go", "fix: handle empty input");
+    commit(&sandbox, "alpha/tests/main.go", "feat: test the flag");
+    sandbox.write("gamma/main.go", "// work in progress\n");
+    sandbox
+}
+
+fn commit(sandbox: &Sandbox, file: &str, message: &str) {
+    sandbox.write(file, &format!("// {}\n", message));
+    sandbox.git(&["add", "-A"]);
+    sandbox.git(&["commit", "-qm", message]);
+}
+
+#[test]
+fn status_prints_a_table_of_the_parent_modules() {
+    let sandbox = sandbox();
+
+    let output = sandbox.run(&["--task", "status"]);
+
+    assert!(output.status.success(), "status failed: {}", stderr(&output));
+    assert_eq!(
+        stdout(&output),
+        "\
+module  latest  commits  uncommitted  next    notes
+alpha   v1.2.0  2        0            v1.3.0
+beta    -       1        0            v0.1.0  no release tag
+gamma   v0.1.0  0        1            -       malformed tag gamma/vnext
+"
+    );
+    let err = stderr(&output);
+    assert!(err.contains("beta: no release tag"), "stderr: {}", err);
+    assert!(err.contains("gamma: malformed tag gamma/vnext"));
+    assert!(err.contains("3 modules: 2 with unreleased commits, 2 flagged."));
+    assert!(sandbox.invocations().is_empty());
+}
+
+#[test]
+fn the_next_version_follows_the_bump_options() {
+    let sandbox = sandbox();
+
+    let output = sandbox.run(&["--task", "status", "--module", "alpha", "--bump", "prerelease", "--preid", "beta"]);
+
+    assert!(output.status.success(), "status failed: {}", stderr(&output));
+    assert!(stdout(&output).contains("alpha   v1.2.0  2        0            v1.2.1-beta.1"), "stdout: {}", stdout(&output));
+    let bump = sandbox.run(&["--task", "bump", "--module", "alpha", "--bump", "prerelease", "--preid", "beta"]);
+    assert_eq!(stdout(&bump), "alpha/v1.2.1-beta.1\n");
+}
+
+#[test]
+fn status_prints_json() {
+    let sandbox = sandbox();
+
+    let output = sandbox.run(&["--task", "status", "--format", "json", "--module", "gamma"]);
+
+    assert!(output.status.success(), "status failed: {}", stderr(&output));
+    let statuses: serde_json::Value = serde_json::from_str(&stdout(&output)).unwrap();
+    assert_eq!(
+        statuses,
+        serde_json::json!([{
+            "module": "gamma",
+            "latest_tag": "gamma/v0.1.0",
+            "commits_since": 0,
+            "uncommitted": 1,
+            "next_version": null,
+            "malformed_tags": ["gamma/vnext"],
+        }])
+    );
+}
+
+#[test]
+fn the_table_leaves_notes_out_when_nothing_is_flagged() {
+    let status = ModuleStatus {
+        module: "alpha".to_string(),
+        latest_tag: Some("alpha/v1.0.0".to_string()),
+        commits_since: 0,
+        uncommitted: 0,
+        next_version: None,
+        malformed_tags: Vec::new(),
+    };
+
+    assert!(status.problems().is_empty());
+    assert_eq!(table(&[status]), "module  latest  commits  uncommitted  next  notes\nalpha   v1.0.0  0        0            -\n");
+}
+
+#[test]
+fn json_output_reports_one_status_event_per_module() {
+    let sandbox = sandbox();
+
+    let output = sandbox.run(&["--task", "status", "--output", "json"]);
+
+    assert!(output.status.success(), "status failed: {}", stderr(&output));
+    let events: Vec<serde_json::Value> = stdout(&output).lines().map(|line| serde_json::from_str(line).unwrap()).collect();
+    let statuses: Vec<(&str, Option<&str>)> = events
+        .iter()
+        .filter(|e| e["event"] == "release_status")
+        .map(|e| (e["module"].as_str().unwrap(), e["next_version"].as_str()))
+        .collect();
+    assert_eq!(statuses, [("alpha", Some("v1.3.0")), ("beta", Some("v0.1.0")), ("gamma", None)]);
+    assert_eq!(events.last().unwrap()["total"], 3);
+}
+
+#[test]
+fn uncommitted_files_keep_renames_and_unusual_names_intact() {
+    let sandbox = sandbox();
+    sandbox.git(&["mv", "alpha/main.go", "alpha/renamed.go"]);
+    sandbox.write("alpha/a b.go", "// spaces\n");
+    sandbox.write("alpha/caf\u{e9}.go", "// non-ASCII\n");
+
+    let mut files = Repo::at(&sandbox.root).uncommitted_files(&SystemRunner::new(), "alpha").unwrap();
+    files.sort();
+
+    let expected: Vec<PathBuf> = ["alpha/a b.go", "alpha/caf\u{e9}.go", "alpha/renamed.go"].iter().map(PathBuf::from).collect();
+    assert_eq!(files, expected);
+    assert!(Repo::at(&sandbox.root).uncommitted_files(&SystemRunner::new(), "beta").unwrap().is_empty());
+}
@@ -0,0 +1,165 @@
+mod common;
+
+use std::fs;
+use std::os::unix::fs::PermissionsExt;
+
+use common::{install_fake_tool, stderr, stdout, Sandbox};
+use serde_json::Value;
+
+fn matrix_sandbox() -> Sandbox {
+    let sandbox = Sandbox::new();
+    install_fake_tool(&sandbox.bin, "dagger-0.12.0");
+    install_fake_tool(&sandbox.bin, "dagger-0.12.4");
+    sandbox.add_module("alpha");
+    sandbox.add_module("beta");
+    sandbox
+}
+
+#[test]
+fn create_runs_the_configured_binaries() {
+    let sandbox = Sandbox::new();
+    install_fake_tool(&sandbox.bin, "dagger-0.12.4");
+    install_fake_tool(&sandbox.bin, "go-1.22");
+    let dagger = sandbox.bin.join("dagger-0.12.4");
+
+    let output = sandbox.run(&["--task", "create", "--module", "foo", "--dagger-bin", dagger.to_str().unwrap(), "--go-bin", "go-1.22"]);
+
+    assert!(output.status.success(), "create failed: {}", stderr(&output));
+    let programs: Vec<String> = sandbox.invocations().into_iter().map(|(_, cmd)| cmd.split(' ').next().unwrap().to_string()).collect();
+    assert!(programs.iter().all(|program| program == "dagger-0.12.4" || program == "go-1.22"), "ran: {:?}", programs);
+    assert!(programs.contains(&"go-1.22".to_string()));
+}
+
+#[test]
+fn create_accepts_a_single_dagger_binary_only() {
+    let sandbox = matrix_sandbox();
+
+    let output = sandbox.run(&["--task", "create", "--module", "foo", "--dagger-bin", "dagger-0.12.0", "--dagger-bin", "dagger-0.12.4"]);
+
+    assert_eq!(output.status.code(), Some(2));
+    assert!(stderr(&output).contains("create runs with a single --dagger-bin"));
+}
+
+#[test]
+fn develop_runs_every_module_against_every_engine() {
+    let sandbox = matrix_sandbox();
+
+    let output = sandbox.run(&["--task", "develop", "--dagger-bin", "dagger-0.12.0", "--dagger-bin", "dagger-0.12.4"]);
+
+    assert!(output.status.success(), "develop failed: {}", stderr(&output));
+    let invocations: Vec<(String, String)> = ["alpha", "alpha", "beta", "beta"]
+        .iter()
+        .zip(["dagger-0.12.0", "dagger-0.12.4", "dagger-0.12.0", "dagger-0.12.4"])
+        .map(|(module, bin)| (module.to_string(), format!("{} develop", bin)))
+        .collect();
+    assert_eq!(sandbox.invocations(), invocations);
+    assert!(stderr(&output).contains("Dagger develop completed for all 2 modules with 2 engines successfully!"));
+}
+
+#[test]
+fn develop_reports_a_module_engine_matrix() {
+    let sandbox = matrix_sandbox();
+
+    let output = sandbox.run_with_env(
+        &["--task", "develop", "--dagger-bin", "dagger-0.12.0,dagger-0.12.4", "--output", "json"],
+        &[("DAGGY_FAKE_FAIL_BIN", "dagger-0.12.0")],
+    );
+
+    assert_eq!(output.status.code(), Some(8));
+    let err = stderr(&output);
+    assert!(err.contains("Failed to develop module: alpha (dagger v0.12.0)"));
+    assert!(err.contains(
+        "Module × engine matrix:\n  module  v0.12.0  v0.12.4\n  alpha   FAILED   ok\n  beta    FAILED   ok\n"
+    ), "stderr: {}", err);
+
+    let events: Vec<Value> = stdout(&output).lines().map(|line| serde_json::from_str(line).unwrap()).collect();
+    let developed: Vec<(&str, &str, &str)> = events
+        .iter()
+        .filter(|e| e["event"].as_str().is_some_and(|kind| kind.starts_with("develop_")))
+        .map(|e| (e["event"].as_str().unwrap(), e["module"].as_str().unwrap(), e["engine"].as_str().unwrap()))
+        .collect();
+    assert_eq!(developed, [
+        ("develop_failed", "alpha", "v0.12.0"),
+        ("develop_succeeded", "alpha", "v0.12.4"),
+        ("develop_failed", "beta", "v0.12.0"),
+        ("develop_succeeded", "beta", "v0.12.4"),
+    ]);
+    assert_eq!(events.last().unwrap()["total"], 4);
+}
+
+#[test]
+fn older_engines_in_a_matrix_are_not_held_to_engine_version() {
+    let sandbox = matrix_sandbox();
+
+    // The modules pin v0.12.4, which would reject v0.12.0 outside a matrix.
+    let single = sandbox.run(&["--task", "develop", "--dagger-bin", "dagger-0.12.0"]);
+    assert_eq!(single.status.code(), Some(11));
+
+    let matrix = sandbox.run(&["--task", "develop", "--dagger-bin", "dagger-0.12.0", "--dagger-bin", "dagger-0.12.4"]);
+    assert!(matrix.status.success(), "develop failed: {}", stderr(&matrix));
+}
+
+#[test]
+fn binaries_come_from_the_environment_and_expand_home() {
+    let sandbox = matrix_sandbox();
+    let home = sandbox.bin.parent().unwrap();
+
+    let output = sandbox.run_with_env(&["--task", "develop"], &[
+        ("HOME", home.to_str().unwrap()),
+        ("DAGGY_DAGGER_BIN", "~/bin/dagger-0.12.4"),
+    ]);
+
+    assert!(output.status.success(), "develop failed: {}", stderr(&output));
+    assert!(sandbox.invocations().iter().all(|(_, cmd)| cmd == "dagger-0.12.4 develop"));
+}
+
+#[test]
+fn the_repository_config_pins_binaries_unless_flags_override_them() {
+    let sandbox = matrix_sandbox();
+    install_fake_tool(&sandbox.bin, "go-1.22");
+    sandbox.write(".daggerx/toolchain.json", "{ \"dagger\": \"dagger-0.12.4\", \"go\": \"go-1.22\" }\n");
+
+    let output = sandbox.run(&["--task", "create", "--module", "foo"]);
+    assert!(output.status.success(), "create failed: {}", stderr(&output));
+    let programs: Vec<String> = sandbox.invocations().into_iter().map(|(_, cmd)| cmd.split(' ').next().unwrap().to_string()).collect();
+    assert!(programs.iter().all(|program| program == "dagger-0.12.4" || program == "go-1.22"), "ran: {:?}", programs);
+    let before = sandbox.invocations().len();
+
+    let output = sandbox.run(&["--task", "develop", "--module", "alpha", "--dagger-bin", "dagger"]);
+    assert!(output.status.success(), "develop failed: {}", stderr(&output));
+    assert_eq!(sandbox.invocations().split_off(before), [("alpha".to_string(), "dagger develop".to_string())]);
+}
+
+#[test]
+fn git_runs_from_the_configured_binary() {
+    let sandbox = matrix_sandbox();
+    sandbox.git(&["add", "-A"]);
+    sandbox.git(&["commit", "-qm", "feat: initial modules"]);
+    let git = sandbox.bin.join("logged-git");
+    fs::write(&git, "#!/bin/sh\nprintf '%s\\n' \"$*\" >> \"$0.log\"\nexec git \"$@\"\n").unwrap();
+    fs::set_permissions(&git, fs::Permissions::from_mode(0o755)).unwrap();
+    sandbox.write(".daggerx/toolchain.json", "{ \"git\": \"../bin/logged-git\" }\n");
+
+    let output = sandbox.run(&["--task", "status"]);
+
+    assert!(output.status.success(), "status failed: {}", stderr(&output));
+    let log = fs::read_to_string(sandbox.bin.join("logged-git.log")).unwrap();
+    assert!(log.starts_with("rev-parse --show-toplevel\n"), "git log: {}", log);
+    assert!(log.lines().count() > 1, "every git command runs the configured binary: {}", log);
+
+    let output = sandbox.run(&["--task", "status", "--git-bin", "missing-git"]);
+    assert_eq!(output.status.code(), Some(5), "the flag takes precedence over the config");
+    assert!(stderr(&output).contains("missing-git is not installed or not on PATH"));
+}
+
+#[test]
+fn a_malformed_toolchain_config_is_reported() {
+    let sandbox = matrix_sandbox();
+    sandbox.write(".daggerx/toolchain.json", "{ \"dagger\": [\"dagger-0.12.4\"], \"node\": \"node\" }\n");
+
+    let output = sandbox.run(&["--task", "develop"]);
+
+    assert_eq!(output.status.code(), Some(1));
+    assert!(stderr(&output).contains(".daggerx/toolchain.json"), "stderr: {}", stderr(&output));
+    assert!(sandbox.invocations().is_empty());
+}
@@ -0,0 +1,160 @@
+mod common;
+
+use std::fs;
+use std::process::{Child, Stdio};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use common::{stderr, Sandbox};
+use daggy::watch::Watcher;
+
+fn watch(sandbox: &Sandbox, module: &str) -> Child {
+    sandbox
+        .daggy()
+        .args(["--task", "develop", "--watch", module, "--debounce", "100ms"])
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .expect("spawn daggy")
+}
+
+/// Wait until the fake tools were run `count` times.
+fn wait_for_invocations(sandbox: &Sandbox, count: usize) {
+    let started = Instant::now();
+    while sandbox.invocations().len() < count {
+        assert!(started.elapsed() < Duration::from_secs(10), "only saw {:?}", sandbox.invocations());
+        thread::sleep(Duration::from_millis(50));
+    }
+}
+
+fn stop(child: Child) -> std::process::Output {
+    // SAFETY: plain kill(2) on the child we spawned.
+    unsafe { libc::kill(child.id() as libc::pid_t, libc::SIGINT) };
+    child.wait_with_output().expect("wait for daggy")
+}
+
+fn watched_sandbox() -> Sandbox {
+    let sandbox = Sandbox::new();
+    sandbox.add_module("alpha");
+    sandbox.add_module("alpha/tests");
+    sandbox.add_module("alpha/examples/go");
+    sandbox.add_module("beta");
+    sandbox.write("alpha/main.go", "package main\n");
+    sandbox
+}
+
+#[test]
+fn watch_develops_the_module_and_its_children_after_each_change() {
+    let sandbox = watched_sandbox();
+    let child = watch(&sandbox, "alpha");
+
+    wait_for_invocations(&sandbox, 3);
+    sandbox.write("alpha/main.go", "package main\n\n// changed\n");
+    wait_for_invocations(&sandbox, 6);
+    let output = stop(child);
+
+    assert!(output.status.success(), "stderr: {}", stderr(&output));
+    let dirs: Vec<String> = sandbox.invocations().into_iter().map(|(cwd, _)| cwd).collect();
+    assert_eq!(dirs, ["alpha", "alpha/tests", "alpha/examples/go", "alpha", "alpha/tests", "alpha/examples/go"]);
+    let err = stderr(&output);
+    assert!(err.contains("Cycle 1 passed in "), "stderr: {}", err);
+    assert!(err.contains("s: alpha ok, alpha/tests ok, alpha/examples/go ok"));
+    assert!(err.contains("Changed: alpha/main.go"));
+    assert!(err.contains("Stopped watching alpha after 2 cycles."));
+}
+
+#[test]
+fn watch_ignores_generated_code() {
+    let sandbox = watched_sandbox();
+    let child = watch(&sandbox, "alpha");
+
+    wait_for_invocations(&sandbox, 3);
+    sandbox.write("alpha/dagger.gen.go", "package main\n");
+    sandbox.write("alpha/internal/dagger/dagger.gen.go", "package dagger\n");
+    sandbox.write("alpha/tests/internal/querybuilder/marshal.go", "package querybuilder\n");
+    sandbox.write("alpha/README.md", "# alpha\n");
+    thread::sleep(Duration::from_millis(800));
+    let output = stop(child);
+
+    assert!(output.status.success(), "stderr: {}", stderr(&output));
+    assert_eq!(sandbox.invocations().len(), 3, "ran: {:?}", sandbox.invocations());
+}
+
+#[test]
+fn watch_reports_failed_cycles_and_keeps_watching() {
+    let sandbox = watched_sandbox();
+    let child = sandbox
+        .daggy()
+        .args(["--task", "develop", "--watch", "alpha", "--debounce", "100ms"])
+        .env("DAGGY_FAKE_FAIL_AT", "2")
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .expect("spawn daggy");
+
+    wait_for_invocations(&sandbox, 2);
+    sandbox.write("alpha/tests/dagger.json", "{\"name\": \"tests\", \"sdk\": \"go\"}\n");
+    wait_for_invocations(&sandbox, 5);
+    let output = stop(child);
+
+    assert!(output.status.success(), "stderr: {}", stderr(&output));
+    let err = stderr(&output);
+    assert!(err.contains("s: alpha ok, alpha/tests FAILED, alpha/examples/go not run"), "stderr: {}", err);
+    assert!(err.contains("Cycle 2 passed in "), "stderr: {}", err);
+}
+
+#[test]
+fn watch_needs_an_existing_module() {
+    let sandbox = watched_sandbox();
+
+    let output = sandbox.run(&["--task", "develop", "--watch", "gamma"]);
+
+    assert_eq!(output.status.code(), Some(2));
+    assert!(stderr(&output).contains("no dagger.json found for module \"gamma\" to watch"));
+}
+
+#[test]
+fn files_saved_while_develop_runs_start_another_cycle() {
+    let sandbox = watched_sandbox();
+    let child = sandbox
+        .daggy()
+        .args(["--task", "develop", "--watch", "alpha", "--debounce", "100ms"])
+        .env("DAGGY_FAKE_SLEEP", "0.5")
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .expect("spawn daggy");
+
+    wait_for_invocations(&sandbox, 1);
+    sandbox.write("alpha/main.go", "package main\n\n// saved during develop\n");
+    wait_for_invocations(&sandbox, 4);
+    let output = stop(child);
+
+    let err = stderr(&output);
+    assert!(err.contains("Cycle 1 passed in "), "stderr: {}", err);
+    assert!(err.contains("Changed: alpha/main.go"), "stderr: {}", err);
+}
+
+#[test]
+fn directories_removed_while_polling_are_not_fatal() {
+    let sandbox = watched_sandbox();
+    let dir = sandbox.root.join("alpha");
+    let mut watcher = Watcher::new(&dir).unwrap().with_poll_interval(Duration::from_millis(1)).with_debounce(Duration::from_millis(300));
+
+    let churn = {
+        let dir = dir.clone();
+        thread::spawn(move || {
+            let started = Instant::now();
+            while started.elapsed() < Duration::from_millis(200) {
+                fs::create_dir_all(dir.join("scratch/a/b/c")).unwrap();
+                fs::write(dir.join("scratch/a/b/c/tmp.go"), "package c\n").unwrap();
+                fs::remove_dir_all(dir.join("scratch")).unwrap();
+            }
+            fs::write(dir.join("main.go"), "package main\n\n// saved\n").unwrap();
+        })
+    };
+    let changed = watcher.wait().unwrap().expect("a change was reported");
+    churn.join().unwrap();
+
+    assert_eq!(changed, [dir.join("main.go")]);
+}